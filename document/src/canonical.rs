@@ -0,0 +1,300 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use serde_json::{Map, Value};
+
+use node_engine::NodeGraph;
+
+use crate::document::{graph_to_value, read_graph_value, write_graph_value};
+
+/// Key of the editor view state in a graph document.
+const VIEW_KEY: &str = "editor";
+
+/// Layout fields, rounded to `layout_decimals`.
+const LAYOUT_KEYS: &[&str] = &[VIEW_KEY, "area"];
+
+/// How graphs are written by `save_graph`.
+#[derive(Clone, Debug)]
+pub struct SaveOptions {
+    /// Sort nodes, groups and connections and round floats, so that saving
+    /// an unchanged graph doesn't change the file.
+    pub canonical: bool,
+    /// Write the editor view state (zoom, scroll offset) to a `.view` sidecar file,
+    /// so that panning around doesn't change the graph file.
+    pub view_sidecar: bool,
+    /// Decimal places kept for node positions and the view state.
+    pub layout_decimals: i32,
+}
+
+impl Default for SaveOptions {
+    fn default() -> Self {
+        Self {
+            canonical: true,
+            view_sidecar: true,
+            layout_decimals: 2,
+        }
+    }
+}
+
+/// The sidecar file holding the editor view state of a graph file, `<stem>.view` (JSON).
+///
+/// It doesn't use a graph extension, so it isn't matched by the graph merge driver's
+/// `.gitattributes` pattern.
+pub fn view_sidecar_path<P: AsRef<Path>>(path: P) -> PathBuf {
+    let path = path.as_ref();
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    path.with_file_name(format!("{stem}.view"))
+}
+
+/// Convert a graph document into canonical form.
+///
+/// Returns the view state when it is split out into a sidecar.
+pub fn canonicalize_graph(doc: &mut Value, options: &SaveOptions) -> Option<Value> {
+    let view = if options.view_sidecar {
+        doc.as_object_mut().and_then(|obj| obj.remove(VIEW_KEY))
+    } else {
+        None
+    };
+    if !options.canonical {
+        return view;
+    }
+
+    let by_id = |a: &Value, b: &Value| id_of(a).cmp(id_of(b));
+    if let Some(nodes) = doc.get_mut("nodes").and_then(Value::as_array_mut) {
+        nodes.sort_by(by_id);
+    }
+    if let Some(groups) = doc.get_mut("groups").and_then(Value::as_array_mut) {
+        groups.sort_by(by_id);
+    }
+    if let Some(connections) = doc.get_mut("connections").and_then(Value::as_array_mut) {
+        let key = |conn: &Value| {
+            (
+                conn["input"]["node"].as_str().unwrap_or_default().to_string(),
+                conn["input"]["idx"].as_u64().unwrap_or_default(),
+            )
+        };
+        connections.sort_by_key(key);
+    }
+
+    round_floats(doc, false, options.layout_decimals);
+    view.map(|mut view| {
+        round_floats(&mut view, true, options.layout_decimals);
+        view
+    })
+}
+
+fn id_of(item: &Value) -> &str {
+    item.get("id").and_then(Value::as_str).unwrap_or_default()
+}
+
+/// Round layout floats to `decimals` and other floats to the nearest `f32`,
+/// which removes `f64` noise like `0.2800000011920929`.
+fn round_floats(value: &mut Value, layout: bool, decimals: i32) {
+    match value {
+        Value::Number(num) if num.is_f64() => {
+            let Some(v) = num.as_f64() else {
+                return;
+            };
+            let rounded = if layout {
+                let scale = 10f64.powi(decimals);
+                (v * scale).round() / scale
+            } else {
+                // Shortest representation of the `f32` value.
+                (v as f32).to_string().parse().unwrap_or(v)
+            };
+            if let Some(n) = serde_json::Number::from_f64(rounded) {
+                *num = n;
+            }
+        }
+        Value::Array(arr) => {
+            for v in arr {
+                round_floats(v, layout, decimals);
+            }
+        }
+        Value::Object(obj) => {
+            for (key, v) in obj.iter_mut() {
+                let layout = layout || LAYOUT_KEYS.contains(&key.as_str());
+                round_floats(v, layout, decimals);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Write a graph document, splitting the view state into a sidecar if enabled.
+pub fn write_canonical_graph<P: AsRef<Path>>(
+    path: P,
+    graph: &NodeGraph,
+    options: &SaveOptions,
+) -> Result<Value> {
+    let mut doc = graph_to_value(graph)?;
+    crate::document::set_format_version(&mut doc);
+    write_canonical_value(path, &mut doc, options)?;
+    Ok(doc)
+}
+
+/// Write a graph document in canonical form, splitting the view state into a sidecar if enabled.
+pub fn write_canonical_value<P: AsRef<Path>>(
+    path: P,
+    doc: &mut Value,
+    options: &SaveOptions,
+) -> Result<()> {
+    let path = path.as_ref();
+    if let Some(view) = canonicalize_graph(doc, options) {
+        let mut sidecar = Map::new();
+        sidecar.insert(VIEW_KEY.into(), view);
+        write_graph_value(view_sidecar_path(path), &Value::Object(sidecar))?;
+    }
+    write_graph_value(path, doc)?;
+    Ok(())
+}
+
+/// Add the view state from the sidecar file (if any) back into a graph document.
+pub fn load_view_sidecar<P: AsRef<Path>>(path: P, doc: &mut Value) -> Result<()> {
+    let Some(obj) = doc.as_object_mut() else {
+        return Ok(());
+    };
+    if obj.contains_key(VIEW_KEY) {
+        return Ok(());
+    }
+    let sidecar = view_sidecar_path(path);
+    let view = if sidecar.exists() {
+        read_graph_value(sidecar)?.get(VIEW_KEY).cloned()
+    } else {
+        None
+    };
+    // Fall back to the default view state.
+    let view = match view {
+        Some(view) => view,
+        None => graph_to_value(&NodeGraph::new())?
+            .get(VIEW_KEY)
+            .cloned()
+            .unwrap_or(Value::Null),
+    };
+    if !view.is_null() {
+        obj.insert(VIEW_KEY.into(), view);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use serde_json::json;
+
+    use node_engine::NodeRegistry;
+
+    use super::*;
+    use crate::document::load_graph;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "bevy_shader_graph_canonical_{}_{name}.json",
+            std::process::id()
+        ))
+    }
+
+    fn doc(nodes: Value, connections: Value) -> Value {
+        json!({
+            "nodes": nodes,
+            "groups": [{ "id": "g2" }, { "id": "g1" }],
+            "connections": connections,
+        })
+    }
+
+    #[test]
+    fn saving_twice_is_identical() {
+        let registry = NodeRegistry::build();
+        let mut graph = NodeGraph::new();
+        graph.add(registry.new_by_name("Add").unwrap());
+        graph.add(registry.new_by_name("Multiply").unwrap());
+        let path = temp_path("twice");
+
+        let options = SaveOptions::default();
+        write_canonical_graph(&path, &graph, &options).unwrap();
+        let first = fs::read(&path).unwrap();
+        let (loaded, _) = load_graph(&path).unwrap();
+        write_canonical_graph(&path, &loaded, &options).unwrap();
+        let second = fs::read(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        fs::remove_file(view_sidecar_path(&path)).unwrap();
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn order_is_independent_of_input_order() {
+        let conn = |node: &str, idx: u32| json!({ "input": { "node": node, "idx": idx } });
+        let mut a = doc(
+            json!([{ "id": "n2" }, { "id": "n1" }, { "id": "n3" }]),
+            json!([conn("n2", 1), conn("n1", 0), conn("n2", 0)]),
+        );
+        let mut b = doc(
+            json!([{ "id": "n3" }, { "id": "n1" }, { "id": "n2" }]),
+            json!([conn("n2", 0), conn("n2", 1), conn("n1", 0)]),
+        );
+        b["groups"] = json!([{ "id": "g1" }, { "id": "g2" }]);
+
+        let options = SaveOptions::default();
+        canonicalize_graph(&mut a, &options);
+        canonicalize_graph(&mut b, &options);
+        assert_eq!(a, b);
+        let ids = |key: &str| -> Vec<&str> { a[key].as_array().unwrap().iter().map(id_of).collect() };
+        assert_eq!(ids("nodes"), ["n1", "n2", "n3"]);
+        assert_eq!(ids("groups"), ["g1", "g2"]);
+        assert_eq!(a["connections"][0], conn("n1", 0));
+        assert_eq!(a["connections"][2], conn("n2", 1));
+    }
+
+    #[test]
+    fn float_rounding_is_stable() {
+        let mut doc = doc(
+            json!([{
+                "id": "n1",
+                "area": { "min": { "x": 12.345678, "y": -0.004 } },
+                "node": { "a": { "value": 0.2800000011920929 } },
+            }]),
+            json!([]),
+        );
+        let options = SaveOptions::default();
+        canonicalize_graph(&mut doc, &options);
+        let node = &doc["nodes"][0];
+        assert_eq!(node["area"]["min"]["x"], 12.35);
+        assert_eq!(node["area"]["min"]["y"], 0.0);
+        assert_eq!(node["node"]["a"]["value"], 0.28);
+
+        let rounded = doc.clone();
+        canonicalize_graph(&mut doc, &options);
+        assert_eq!(doc, rounded);
+    }
+
+    #[test]
+    fn view_state_in_sidecar() {
+        let path = temp_path("view");
+        let mut doc = doc(json!([]), json!([]));
+        doc[VIEW_KEY] = json!({ "zoom": 1.23456, "offset": [10.001, 20.0] });
+
+        write_canonical_value(&path, &mut doc, &SaveOptions::default()).unwrap();
+        let saved = read_graph_value(&path).unwrap();
+        let sidecar = read_graph_value(view_sidecar_path(&path)).unwrap();
+        assert!(saved.get(VIEW_KEY).is_none());
+        assert_eq!(
+            sidecar[VIEW_KEY],
+            json!({ "zoom": 1.23, "offset": [10.0, 20.0] })
+        );
+
+        let mut loaded = saved.clone();
+        load_view_sidecar(&path, &mut loaded).unwrap();
+        fs::remove_file(&path).unwrap();
+        fs::remove_file(view_sidecar_path(&path)).unwrap();
+        assert_eq!(loaded[VIEW_KEY], sidecar[VIEW_KEY]);
+
+        assert_eq!(
+            view_sidecar_path("graphs/wall.json"),
+            Path::new("graphs/wall.view")
+        );
+    }
+}
@@ -0,0 +1,344 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use serde_json::{json, Map, Value};
+
+use node_engine::{NodeGraph, NodeRegistry};
+
+use crate::canonical::{
+    load_view_sidecar, write_canonical_graph, write_canonical_value, SaveOptions,
+};
+use crate::encoding::{from_compact_slice, from_ron_slice, to_compact_vec, to_ron_string};
+use crate::migrate::{graph_format_version, GraphMigrations, MigrationReport, FORMAT_VERSION_KEY};
+
+/// Serialize a graph into its JSON document form.
+pub fn graph_to_value(graph: &NodeGraph) -> Result<Value> {
+    Ok(serde_json::to_value(graph)?)
+}
+
+/// Deserialize a graph from its JSON document form.
+pub fn graph_from_value(mut value: Value) -> Result<NodeGraph> {
+    if let Some(obj) = value.as_object_mut() {
+        obj.remove(FORMAT_VERSION_KEY);
+    }
+    Ok(serde_json::from_value(value)?)
+}
+
+/// File format of a graph document.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum GraphFormat {
+    /// Pretty JSON (`.json`).
+    #[default]
+    Json,
+    /// RON (`.ron`), objects are written as structs.
+    Ron,
+    /// Compact binary MessagePack (`.sgb`), the object keys are only stored once.
+    Binary,
+}
+
+impl GraphFormat {
+    /// Choose the format from the file extension, defaults to JSON.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Self {
+        match path.as_ref().extension().and_then(|ext| ext.to_str()) {
+            Some("ron") => Self::Ron,
+            Some("sgb") => Self::Binary,
+            _ => Self::Json,
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Ron => "ron",
+            Self::Binary => "sgb",
+        }
+    }
+
+    pub fn parse(&self, data: &[u8]) -> Result<Value> {
+        Ok(match self {
+            Self::Json => serde_json::from_slice(data)?,
+            Self::Ron => from_ron_slice(data)?,
+            Self::Binary => from_compact_slice(data)?,
+        })
+    }
+
+    pub fn serialize(&self, value: &Value) -> Result<Vec<u8>> {
+        Ok(match self {
+            Self::Json => serde_json::to_vec_pretty(value)?,
+            Self::Ron => to_ron_string(value)?.into_bytes(),
+            Self::Binary => to_compact_vec(value)?,
+        })
+    }
+}
+
+/// Read a graph document, the format is chosen by the file extension.
+pub fn read_graph_value<P: AsRef<Path>>(path: P) -> Result<Value> {
+    let path = path.as_ref();
+    GraphFormat::from_path(path).parse(&fs::read(path)?)
+}
+
+/// Write a graph document, the format is chosen by the file extension.
+pub fn write_graph_value<P: AsRef<Path>>(path: P, value: &Value) -> Result<()> {
+    let path = path.as_ref();
+    fs::write(path, GraphFormat::from_path(path).serialize(value)?)?;
+    Ok(())
+}
+
+/// Load a graph document, upgrading it to the current format.
+pub fn load_graph<P: AsRef<Path>>(path: P) -> Result<(NodeGraph, MigrationReport)> {
+    let path = path.as_ref();
+    let mut value = read_graph_value(path)?;
+    load_view_sidecar(path, &mut value)?;
+    let report = GraphMigrations::default().migrate(&mut value)?;
+    Ok((graph_from_value(value)?, report))
+}
+
+/// Save a graph document in the current format, in canonical form.
+pub fn save_graph<P: AsRef<Path>>(path: P, graph: &NodeGraph) -> Result<()> {
+    save_graph_with(path, graph, &SaveOptions::default())
+}
+
+/// Save a graph document in the current format.
+pub fn save_graph_with<P: AsRef<Path>>(
+    path: P,
+    graph: &NodeGraph,
+    options: &SaveOptions,
+) -> Result<()> {
+    write_canonical_graph(path, graph, options)?;
+    Ok(())
+}
+
+/// Convert a graph file between formats, upgrading it to the current format version.
+///
+/// The view state moves to the sidecar of the new file.
+pub fn convert_graph<P: AsRef<Path>, Q: AsRef<Path>>(from: P, to: Q) -> Result<MigrationReport> {
+    let from = from.as_ref();
+    let mut value = read_graph_value(from)?;
+    load_view_sidecar(from, &mut value)?;
+    let report = GraphMigrations::default().migrate(&mut value)?;
+    write_canonical_value(to, &mut value, &SaveOptions::default())?;
+    Ok(report)
+}
+
+/// Stamp a graph document with the current format version.
+pub fn set_format_version(value: &mut Value) {
+    if let Some(obj) = value.as_object_mut() {
+        obj.insert(FORMAT_VERSION_KEY.into(), graph_format_version().into());
+    }
+}
+
+/// The nodes of a graph document.
+pub fn doc_nodes(doc: &Value) -> impl Iterator<Item = &Value> {
+    doc.get("nodes")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+}
+
+/// The nodes of a graph document.
+pub fn doc_nodes_mut(doc: &mut Value) -> impl Iterator<Item = &mut Value> {
+    doc.get_mut("nodes")
+        .and_then(Value::as_array_mut)
+        .into_iter()
+        .flatten()
+}
+
+/// Default state of a node type, as serialized in a graph document.
+#[derive(Clone, Debug)]
+pub struct NodeTemplate {
+    /// Registry name.
+    pub name: String,
+    /// Node type UUID.
+    pub node_type: String,
+    /// Default node state (inputs and parameters, in input order).
+    pub node: Value,
+}
+
+impl NodeTemplate {
+    /// The input names, in input index order.
+    pub fn inputs(&self) -> Vec<&str> {
+        self.node
+            .as_object()
+            .map(|inputs| {
+                inputs
+                    .iter()
+                    .filter(|(_, input)| is_input(input))
+                    .map(|(name, _)| name.as_str())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// The index of a named input.
+    pub fn input_idx(&self, name: &str) -> Option<u32> {
+        self.inputs()
+            .iter()
+            .position(|input| *input == name)
+            .map(|idx| idx as u32)
+    }
+}
+
+/// The registry name of a document node, using the node type if the template is loaded.
+pub fn node_kind<'a>(templates: &'a NodeTemplates, node: &'a Value) -> &'a str {
+    node.get("node_type")
+        .and_then(Value::as_str)
+        .and_then(|ty| templates.name_of(ty))
+        .or_else(|| node.get("name").and_then(Value::as_str))
+        .unwrap_or_default()
+}
+
+/// The id of a document node.
+pub fn node_id(node: &Value) -> &str {
+    node.get("id").and_then(Value::as_str).unwrap_or_default()
+}
+
+/// The source `(node, output idx)` of a connected input.
+pub fn input_source(field: &Value) -> Option<(&str, u64)> {
+    let output = field.get("connected")?.get(0)?;
+    Some((output.get("node")?.as_str()?, output.get("idx")?.as_u64()?))
+}
+
+/// The floats of a constant input value.
+///
+/// Handles `1.0`, `[1.0, 2.0]`, `{ "data": [..], "size": "D2" }` and UV values `[[u, v], "UV0"]`.
+pub fn value_floats(value: &Value) -> Option<Vec<f32>> {
+    match value {
+        Value::Number(num) => Some(vec![num.as_f64()? as f32]),
+        Value::Array(arr) => match arr.first() {
+            Some(Value::Array(uv)) => uv.iter().map(|v| Some(v.as_f64()? as f32)).collect(),
+            _ => arr.iter().map(|v| Some(v.as_f64()? as f32)).collect(),
+        },
+        Value::Object(obj) => {
+            let size = obj
+                .get("size")
+                .and_then(Value::as_str)
+                .and_then(|s| s.strip_prefix('D'))
+                .and_then(|s| s.parse::<usize>().ok())
+                .unwrap_or(4);
+            let data = obj.get("data")?.as_array()?;
+            data.iter()
+                .take(size)
+                .map(|v| Some(v.as_f64()? as f32))
+                .collect()
+        }
+        _ => None,
+    }
+}
+
+/// Set a constant input value from floats, keeping the value's shape.
+pub fn set_value_floats(value: &mut Value, values: &[f32]) -> bool {
+    let get = |idx: usize| values.get(idx).copied().unwrap_or(0.0);
+    match value {
+        Value::Number(_) => {
+            *value = get(0).into();
+        }
+        Value::Object(obj) if obj.contains_key("data") => {
+            let len = values.len().clamp(1, 4);
+            obj.insert("data".into(), json!([get(0), get(1), get(2), get(3)]));
+            obj.insert("size".into(), format!("D{len}").into());
+        }
+        Value::Array(arr) => match arr.first_mut() {
+            Some(Value::Array(uv)) => {
+                *uv = vec![get(0).into(), get(1).into()];
+            }
+            _ => {
+                for (idx, v) in arr.iter_mut().enumerate() {
+                    *v = get(idx).into();
+                }
+            }
+        },
+        _ => return false,
+    }
+    true
+}
+
+/// Check if a node field is an input (`{ "value": .., "connected": .. }`).
+pub fn is_input(field: &Value) -> bool {
+    field
+        .as_object()
+        .is_some_and(|obj| obj.contains_key("value") && obj.contains_key("connected"))
+}
+
+/// Lookup of node templates by registry name or node type.
+pub struct NodeTemplates {
+    registry: NodeRegistry,
+    by_name: HashMap<String, Option<NodeTemplate>>,
+    names: HashMap<String, String>,
+}
+
+impl fmt::Debug for NodeTemplates {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NodeTemplates")
+            .field("loaded", &self.by_name.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for NodeTemplates {
+    fn default() -> Self {
+        Self::new(NodeRegistry::build())
+    }
+}
+
+impl NodeTemplates {
+    pub fn new(registry: NodeRegistry) -> Self {
+        Self {
+            registry,
+            by_name: HashMap::new(),
+            names: HashMap::new(),
+        }
+    }
+
+    /// Get the template for a registry node name.
+    pub fn get(&mut self, name: &str) -> Option<&NodeTemplate> {
+        if !self.by_name.contains_key(name) {
+            let template = self.build(name).ok();
+            if let Some(template) = &template {
+                self.names
+                    .insert(template.node_type.clone(), template.name.clone());
+            }
+            self.by_name.insert(name.to_string(), template);
+        }
+        self.by_name.get(name).and_then(Option::as_ref)
+    }
+
+    /// Get the template for a registry node name, failing if it doesn't exist.
+    pub fn require(&mut self, name: &str) -> Result<&NodeTemplate> {
+        self.get(name)
+            .ok_or_else(|| anyhow!("Unknown node type: {name}"))
+    }
+
+    /// Get the registry name of a node type, if the template has been loaded.
+    pub fn name_of(&self, node_type: &str) -> Option<&str> {
+        self.names.get(node_type).map(String::as_str)
+    }
+
+    /// Load the templates for a list of registry node names.
+    pub fn preload(&mut self, names: &[&str]) {
+        for name in names {
+            self.get(name);
+        }
+    }
+
+    fn build(&self, name: &str) -> Result<NodeTemplate> {
+        let node = self.registry.new_by_name(name)?;
+        let mut graph = NodeGraph::new();
+        graph.add(node);
+        let doc = graph_to_value(&graph)?;
+        let node = doc_nodes(&doc)
+            .next()
+            .ok_or_else(|| anyhow!("Failed to create node: {name}"))?;
+        Ok(NodeTemplate {
+            name: name.to_string(),
+            node_type: node
+                .get("node_type")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string(),
+            node: node.get("node").cloned().unwrap_or(Value::Object(Map::new())),
+        })
+    }
+}
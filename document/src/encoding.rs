@@ -0,0 +1,293 @@
+//! RON and binary encodings of graph documents.
+//!
+//! Both work on the JSON document form, so migrations still see the raw document.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use anyhow::Result;
+use serde::de::{self, DeserializeSeed, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde::ser::{SerializeMap, SerializeSeq, Serializer};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// Write a document as RON, objects with identifier keys are written as structs
+/// (`(nodes: [..])`) and other objects as maps.
+pub fn to_ron_string(value: &Value) -> Result<String> {
+    let mut out = String::new();
+    write_ron(&mut out, value, 0)?;
+    out.push('\n');
+    Ok(out)
+}
+
+/// Read a RON document, structs are read as objects.
+pub fn from_ron_slice(data: &[u8]) -> Result<Value> {
+    Ok(ron::de::from_bytes(data)?)
+}
+
+fn write_ron(out: &mut String, value: &Value, depth: usize) -> Result<()> {
+    match value {
+        Value::Null => out.push_str("None"),
+        Value::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+        Value::Number(num) => out.push_str(&num.to_string()),
+        Value::String(s) => out.push_str(&ron::to_string(s)?),
+        Value::Array(arr) if arr.is_empty() => out.push_str("[]"),
+        Value::Array(arr) => {
+            out.push_str("[\n");
+            for item in arr {
+                indent(out, depth + 1);
+                write_ron(out, item, depth + 1)?;
+                out.push_str(",\n");
+            }
+            indent(out, depth);
+            out.push(']');
+        }
+        // `()` would be read back as `null`.
+        Value::Object(obj) if obj.is_empty() => out.push_str("{}"),
+        Value::Object(obj) => {
+            let is_struct = obj.keys().all(|key| is_ident(key));
+            out.push_str(if is_struct { "(\n" } else { "{\n" });
+            for (key, item) in obj {
+                indent(out, depth + 1);
+                if is_struct {
+                    out.push_str(key);
+                } else {
+                    out.push_str(&ron::to_string(key)?);
+                }
+                out.push_str(": ");
+                write_ron(out, item, depth + 1)?;
+                out.push_str(",\n");
+            }
+            indent(out, depth);
+            out.push(if is_struct { ')' } else { '}' });
+        }
+    }
+    Ok(())
+}
+
+fn indent(out: &mut String, depth: usize) {
+    for _ in 0..depth {
+        out.push_str("    ");
+    }
+}
+
+fn is_ident(key: &str) -> bool {
+    let mut chars = key.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && !matches!(key, "true" | "false" | "None" | "Some")
+}
+
+/// Write a document as MessagePack `[keys, value]`, with the object keys stored once
+/// in `keys` and the objects keyed by their index.
+pub fn to_compact_vec(value: &Value) -> Result<Vec<u8>> {
+    let mut keys = Vec::new();
+    let mut index = HashMap::new();
+    collect_keys(value, &mut keys, &mut index);
+    let doc = (
+        &keys,
+        CompactValue {
+            value,
+            index: &index,
+        },
+    );
+    Ok(rmp_serde::to_vec(&doc)?)
+}
+
+/// Read a document written by `to_compact_vec`.
+pub fn from_compact_slice(data: &[u8]) -> Result<Value> {
+    Ok(rmp_serde::from_slice::<CompactDoc>(data)?.0)
+}
+
+fn collect_keys<'a>(value: &'a Value, keys: &mut Vec<&'a str>, index: &mut HashMap<&'a str, u32>) {
+    match value {
+        Value::Array(arr) => {
+            for item in arr {
+                collect_keys(item, keys, index);
+            }
+        }
+        Value::Object(obj) => {
+            for (key, item) in obj {
+                index.entry(key.as_str()).or_insert_with(|| {
+                    keys.push(key.as_str());
+                    keys.len() as u32 - 1
+                });
+                collect_keys(item, keys, index);
+            }
+        }
+        _ => {}
+    }
+}
+
+struct CompactValue<'a> {
+    value: &'a Value,
+    index: &'a HashMap<&'a str, u32>,
+}
+
+impl Serialize for CompactValue<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let child = |value| CompactValue {
+            value,
+            index: self.index,
+        };
+        match self.value {
+            Value::Array(arr) => {
+                let mut seq = serializer.serialize_seq(Some(arr.len()))?;
+                for item in arr {
+                    seq.serialize_element(&child(item))?;
+                }
+                seq.end()
+            }
+            Value::Object(obj) => {
+                let mut map = serializer.serialize_map(Some(obj.len()))?;
+                for (key, item) in obj {
+                    map.serialize_entry(&self.index[key.as_str()], &child(item))?;
+                }
+                map.end()
+            }
+            value => value.serialize(serializer),
+        }
+    }
+}
+
+/// `[keys, value]` written by `to_compact_vec`.
+struct CompactDoc(Value);
+
+impl<'de> Deserialize<'de> for CompactDoc {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct DocVisitor;
+
+        impl<'de> Visitor<'de> for DocVisitor {
+            type Value = CompactDoc;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a `[keys, value]` graph document")
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<CompactDoc, A::Error> {
+                let keys: Vec<String> = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+                let value = seq
+                    .next_element_seed(CompactSeed(&keys))?
+                    .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+                Ok(CompactDoc(value))
+            }
+        }
+
+        deserializer.deserialize_seq(DocVisitor)
+    }
+}
+
+/// Reads a value, mapping the object key indexes back to the keys.
+#[derive(Clone, Copy)]
+struct CompactSeed<'k>(&'k [String]);
+
+impl<'de> DeserializeSeed<'de> for CompactSeed<'_> {
+    type Value = Value;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Value, D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for CompactSeed<'_> {
+    type Value = Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a graph document value")
+    }
+
+    fn visit_unit<E>(self) -> Result<Value, E> {
+        Ok(Value::Null)
+    }
+
+    fn visit_none<E>(self) -> Result<Value, E> {
+        Ok(Value::Null)
+    }
+
+    fn visit_bool<E>(self, v: bool) -> Result<Value, E> {
+        Ok(v.into())
+    }
+
+    fn visit_i64<E>(self, v: i64) -> Result<Value, E> {
+        Ok(v.into())
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<Value, E> {
+        Ok(v.into())
+    }
+
+    fn visit_f64<E>(self, v: f64) -> Result<Value, E> {
+        Ok(v.into())
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Value, E> {
+        Ok(v.into())
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Value, A::Error> {
+        let mut arr = Vec::with_capacity(seq.size_hint().unwrap_or_default());
+        while let Some(item) = seq.next_element_seed(self)? {
+            arr.push(item);
+        }
+        Ok(Value::Array(arr))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Value, A::Error> {
+        let mut obj = Map::new();
+        while let Some(idx) = map.next_key::<u32>()? {
+            let key = self
+                .0
+                .get(idx as usize)
+                .ok_or_else(|| de::Error::custom(format!("Unknown key index {idx}")))?;
+            obj.insert(key.clone(), map.next_value_seed(self)?);
+        }
+        Ok(Value::Object(obj))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn interior_mapping() -> Value {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/../interior_mapping.json");
+        serde_json::from_slice(&std::fs::read(path).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn ron_round_trip() {
+        let doc = interior_mapping();
+        let ron = to_ron_string(&doc).unwrap();
+        assert!(ron.starts_with("(\n"));
+        assert!(ron.contains("    nodes: [\n"));
+        assert!(!ron.contains("\"nodes\""));
+        assert_eq!(from_ron_slice(ron.as_bytes()).unwrap(), doc);
+    }
+
+    #[test]
+    fn ron_special_values() {
+        let doc = serde_json::json!({
+            "empty": {},
+            "unit": [],
+            "null": null,
+            "map": { "not an ident": 1, "x": -2.5 },
+            "text": "quote \" and \\ newline\n",
+        });
+        let ron = to_ron_string(&doc).unwrap();
+        assert_eq!(from_ron_slice(ron.as_bytes()).unwrap(), doc);
+    }
+
+    #[test]
+    fn compact_round_trip() {
+        let doc = interior_mapping();
+        let data = to_compact_vec(&doc).unwrap();
+        assert_eq!(from_compact_slice(&data).unwrap(), doc);
+        // Each key is only stored once.
+        let named = rmp_serde::to_vec_named(&doc).unwrap();
+        assert!(data.len() < named.len());
+    }
+}
@@ -0,0 +1,14 @@
+//! Graph documents for `bevy_shader_graph`.
+//!
+//! Loading, saving, migrating and optimizing graph files without Bevy, shared by
+//! the editor and the `shader_graph!` macro.
+
+pub mod canonical;
+pub use canonical::*;
+pub mod document;
+pub use document::*;
+mod encoding;
+pub mod migrate;
+pub use migrate::*;
+pub mod optimize;
+pub use optimize::*;
@@ -0,0 +1,457 @@
+use anyhow::{anyhow, Result};
+use serde_json::{Map, Value};
+
+use crate::document::{doc_nodes_mut, node_id, NodeTemplates};
+
+/// Top-level key holding the format version in graph files.
+pub const FORMAT_VERSION_KEY: &str = "format_version";
+
+/// What a migration changed in a graph document.
+#[derive(Clone, Debug, Default)]
+pub struct MigrationReport {
+    pub from_version: u32,
+    pub to_version: u32,
+    pub changes: Vec<String>,
+}
+
+impl MigrationReport {
+    /// Check if the graph was upgraded.
+    pub fn migrated(&self) -> bool {
+        self.from_version != self.to_version
+    }
+}
+
+/// A single change applied to a graph document.
+#[derive(Clone, Debug)]
+pub enum MigrationStep {
+    /// Replace a node type (by its old node type UUID) with a registry node,
+    /// renaming inputs and parameters `(from, to)` to the new node's fields.
+    RenameNodeType {
+        node_type: &'static str,
+        name: &'static str,
+        fields: &'static [(&'static str, &'static str)],
+    },
+    /// Rename an input or parameter of a node type.
+    RenamePort {
+        node_type: &'static str,
+        from: &'static str,
+        to: &'static str,
+    },
+    /// Custom change to the whole document.
+    Custom(fn(&mut Value, &mut Vec<String>) -> Result<()>),
+}
+
+/// The steps to upgrade a graph document to `version`.
+#[derive(Clone, Debug)]
+pub struct Migration {
+    pub version: u32,
+    pub description: &'static str,
+    pub steps: Vec<MigrationStep>,
+}
+
+impl Migration {
+    pub fn new(version: u32, description: &'static str) -> Self {
+        Self {
+            version,
+            description,
+            steps: Vec::new(),
+        }
+    }
+
+    pub fn step(mut self, step: MigrationStep) -> Self {
+        self.steps.push(step);
+        self
+    }
+}
+
+/// Current graph file format version, the newest version of the default migrations.
+pub fn graph_format_version() -> u32 {
+    GraphMigrations::default().latest_version()
+}
+
+/// Pipeline of migrations, applied in version order.
+#[derive(Clone, Debug)]
+pub struct GraphMigrations {
+    migrations: Vec<Migration>,
+}
+
+impl Default for GraphMigrations {
+    fn default() -> Self {
+        Self {
+            migrations: vec![
+                Migration::new(1, "Add the graph format version"),
+                Migration::new(2, "Move the nodes to `bevy_shader_graph_nodes`")
+                    .step(MigrationStep::Custom(move_node_types)),
+            ],
+        }
+    }
+}
+
+impl GraphMigrations {
+    pub fn add(&mut self, migration: Migration) -> &mut Self {
+        self.migrations.push(migration);
+        self.migrations.sort_by_key(|m| m.version);
+        self
+    }
+
+    /// The newest version these migrations upgrade to.
+    pub fn latest_version(&self) -> u32 {
+        self.migrations
+            .iter()
+            .map(|m| m.version)
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// Upgrade a graph document in place.
+    pub fn migrate(&self, doc: &mut Value) -> Result<MigrationReport> {
+        // No version, the graph was saved before versioning.
+        let from_version = match doc.get(FORMAT_VERSION_KEY) {
+            None => 0,
+            Some(version) => version
+                .as_u64()
+                .and_then(|version| u32::try_from(version).ok())
+                .ok_or_else(|| anyhow!("Invalid graph format version: {version}"))?,
+        };
+        let to_version = self.latest_version();
+        if from_version > to_version {
+            return Err(anyhow!(
+                "Graph format version {from_version} is newer than supported version {to_version}"
+            ));
+        }
+
+        let mut report = MigrationReport {
+            from_version,
+            to_version,
+            changes: Vec::new(),
+        };
+        let mut templates = None;
+        for migration in self.migrations.iter().filter(|m| m.version > from_version) {
+            report
+                .changes
+                .push(format!("v{}: {}", migration.version, migration.description));
+            for step in &migration.steps {
+                let templates = templates.get_or_insert_with(NodeTemplates::default);
+                apply_step(doc, step, templates, &mut report.changes)?;
+            }
+        }
+
+        if let Some(obj) = doc.as_object_mut() {
+            obj.insert(FORMAT_VERSION_KEY.into(), to_version.into());
+        }
+        Ok(report)
+    }
+}
+
+fn apply_step(
+    doc: &mut Value,
+    step: &MigrationStep,
+    templates: &mut NodeTemplates,
+    changes: &mut Vec<String>,
+) -> Result<()> {
+    match step {
+        MigrationStep::RenameNodeType {
+            node_type,
+            name,
+            fields,
+        } => {
+            let template = templates.require(name)?;
+            for node in doc_nodes_mut(doc) {
+                if node.get("node_type").and_then(Value::as_str) != Some(node_type) {
+                    continue;
+                }
+                let id = node_id(node).to_string();
+                node["node_type"] = template.node_type.clone().into();
+                node["name"] = template.name.clone().into();
+                changes.push(format!("Node {id}: node type changed to `{name}`"));
+                if let Some(node_fields) = node.get_mut("node").and_then(Value::as_object_mut) {
+                    for (from, to) in rename_fields(node_fields, fields) {
+                        changes.push(format!("Node {id}: renamed `{from}` to `{to}`"));
+                    }
+                }
+            }
+        }
+        MigrationStep::RenamePort {
+            node_type,
+            from,
+            to,
+        } => {
+            for node in doc_nodes_mut(doc) {
+                if node.get("node_type").and_then(Value::as_str) != Some(node_type) {
+                    continue;
+                }
+                let id = node_id(node).to_string();
+                let Some(fields) = node.get_mut("node").and_then(Value::as_object_mut) else {
+                    continue;
+                };
+                for (from, to) in rename_fields(fields, &[(from, to)]) {
+                    changes.push(format!("Node {id}: renamed `{from}` to `{to}`"));
+                }
+            }
+        }
+        MigrationStep::Custom(func) => {
+            func(doc, changes)?;
+        }
+    }
+    Ok(())
+}
+
+/// Node types of the nodes moved from `bevy_shader_graph::nodes` to the
+/// `bevy_shader_graph_nodes` crate, `(old node type, new node type)`.  Node
+/// types are derived from the node's module path, so the move changed them.
+const MOVED_NODE_TYPES: &[(&str, &str)] = &[
+    // Decal Fragment
+    ("498a73b5-cb7f-50db-b26a-da31df19933c", "53419234-6fd3-5991-b374-13c91cc37062"),
+    // Decal UV
+    ("0263cd0a-8029-59a4-9415-15ad84958c17", "e487b7df-cdfc-57ad-af3b-ba8b8cc710e2"),
+    // Decal Angle Fade
+    ("7e4447d0-dacf-5b06-9548-dcd836349c02", "556c3b96-0d57-5aaa-b0a4-d22c3c16a292"),
+    // Time
+    ("f7679d0e-2273-5a1f-9abd-91e42321af67", "e0ff8cfb-4fd7-5f30-a961-89e6876a1573"),
+    // Main Light
+    ("08fbfce7-4765-53d7-8c44-2a389c3427b7", "fca84037-e50d-52b2-9960-5882c3c8b24d"),
+    // Directional Lights
+    ("5d422b0b-7997-55c0-9eab-89336c14e62d", "96905f47-622f-5a7a-b5eb-058464cc8d51"),
+    // Ambient Light
+    ("6457707b-5eae-57f3-b5ee-1a0d64245a3c", "88ec185c-aae8-5167-b88d-265b5cd8986b"),
+    // PBR Fragment
+    ("2ffc6f4f-2e5e-5213-b9d6-6d299b94b05a", "e6affb0f-bccb-5d59-82ae-a2c3b9b3663d"),
+    // Unlit Fragment
+    ("353678bd-fe7f-5ddb-b3cf-55e93449a0ff", "595afe97-2144-5b3a-b374-9379912806d1"),
+    // Custom Lighting Fragment
+    ("b368e230-1021-5f99-a8a4-8a8efcba2008", "1232faf5-0fc0-5f4a-8f87-4d59d955c4d2"),
+    // Object Transform
+    ("b07f2e54-ba78-5a0c-9dfb-57385749f4c0", "b4ae3a79-ce21-5d31-b940-382c45a9e3c5"),
+    // Instance
+    ("f3ea2f5f-eb26-5f0f-9120-984753d673b7", "a9150287-db47-5c25-9853-e995cb13d1cc"),
+    // Local Position
+    ("7c7a0c5b-2ff5-591e-b4a7-caacfc24bf68", "1c91a486-9c53-5166-a395-bc97c20e7246"),
+    // 2D Fragment
+    ("c3047165-b61f-57a0-8d26-f803f04036f6", "801636eb-1be9-5f4f-a4bd-7583afb79e55"),
+    // Sprite UV
+    ("1b118716-b425-58bc-b243-52e98f29823c", "7f5811a4-91a6-58f6-aafb-4f26fb509787"),
+    // World Position 2D
+    ("1e1a8173-2c31-59d2-ad44-9b052498b2a6", "7baed0e9-c041-5129-a541-c6e8bc6e7f5b"),
+    // Placeholder
+    ("e7ce6331-e7e2-52a4-bf88-1ec7f20dbb59", "96bf38e3-6d85-529b-acf1-15a8ae3d6f59"),
+    // Post Process
+    ("8c9ca08a-fae5-56b2-8943-db4e7ab81e4d", "edcad71b-41be-5844-89ec-d1b33337e708"),
+    // Screen UV
+    ("a9afa5a0-a963-5e2e-97cb-2f564a96843b", "7bc1cfaa-e540-57e3-b8aa-f4bd5f2371b9"),
+    // Screen Color
+    ("0e0fbf49-a7c1-5eca-b63f-e1ca26ba4cfb", "81bcc2bc-93c9-5640-af08-1b4f1ae7ec92"),
+    // Scene Depth
+    ("313fd87a-0f5e-5fdc-bef3-a2b4bd2f6baf", "e767cd46-ca0f-5e6c-9353-37d2a7a35965"),
+    // Scene Normal
+    ("72de5dc9-adab-51bf-a690-576501676c8d", "26d1abaa-fa44-570a-b993-e6ba76763959"),
+    // UI Fragment
+    ("89d6c4f0-42ff-5565-ab44-f7d20fa9d878", "3a9e25e0-c76c-5254-9b8c-b0838431ca33"),
+    // UI Node UV
+    ("8ed3ee0a-8a9c-5309-947d-dfbe3eda69dd", "c7bb48f9-3a05-5133-a76d-69eada257023"),
+    // UI Node Size
+    ("bf6b8d83-2f95-5a40-95af-2d903319c618", "a7d017c7-3afc-58de-978b-806b31722573"),
+    // UI Border Radius
+    ("4e0c8e99-3c88-5b4a-b78f-53a06a586889", "56a615df-c751-5b53-bee6-f559911b8068"),
+    // Camera
+    ("ac035005-e5ac-5d9c-8c65-49d604427219", "dd7f6e12-28d4-5aa3-bf48-04e37a3e31b2"),
+    // View Matrices
+    ("a22288fc-d4d8-57de-897b-870585c5cc2d", "530b6176-9511-5ea8-9785-62f27fa2cc78"),
+    // Screen Position
+    ("f0d8d85e-d9ca-5b5c-8e4d-ba1ba1c61d16", "212823cb-4cef-55d1-ac7e-a374a3fd7824"),
+];
+
+fn move_node_types(doc: &mut Value, changes: &mut Vec<String>) -> Result<()> {
+    for node in doc_nodes_mut(doc) {
+        let Some(node_type) = node.get("node_type").and_then(Value::as_str) else {
+            continue;
+        };
+        let Some((_, moved)) = MOVED_NODE_TYPES.iter().find(|(old, _)| *old == node_type) else {
+            continue;
+        };
+        let id = node_id(node).to_string();
+        node["node_type"] = (*moved).into();
+        changes.push(format!("Node {id}: node type changed to `{moved}`"));
+    }
+    Ok(())
+}
+
+/// Rename node fields, keeping the input order.  Returns the renamed fields.
+fn rename_fields<'a>(
+    fields: &mut Map<String, Value>,
+    renames: &[(&'a str, &'a str)],
+) -> Vec<(&'a str, &'a str)> {
+    let renamed: Vec<_> = renames
+        .iter()
+        .copied()
+        .filter(|(from, _)| fields.contains_key(*from))
+        .collect();
+    if renamed.is_empty() {
+        return renamed;
+    }
+    // Rebuild the map to keep the input order.
+    *fields = std::mem::take(fields)
+        .into_iter()
+        .map(
+            |(key, value)| match renamed.iter().find(|(from, _)| *from == key) {
+                Some((_, to)) => (to.to_string(), value),
+                None => (key, value),
+            },
+        )
+        .collect();
+    renamed
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn doc(version: Option<u32>) -> Value {
+        let mut doc = json!({
+            "nodes": [{
+                "id": "n1",
+                "name": "Old Add",
+                "node_type": "old-add",
+                "node": {
+                    "x": { "value": 1.0, "connected": null },
+                    "y": { "value": 2.0, "connected": null },
+                },
+            }],
+        });
+        if let Some(version) = version {
+            doc[FORMAT_VERSION_KEY] = version.into();
+        }
+        doc
+    }
+
+    fn field_names(doc: &Value) -> Vec<String> {
+        doc["nodes"][0]["node"]
+            .as_object()
+            .unwrap()
+            .keys()
+            .cloned()
+            .collect()
+    }
+
+    #[test]
+    fn stamps_latest_version() {
+        let mut doc = doc(None);
+        let report = GraphMigrations::default().migrate(&mut doc).unwrap();
+        assert_eq!(report.from_version, 0);
+        assert_eq!(report.to_version, graph_format_version());
+        assert!(report.migrated());
+        assert_eq!(doc[FORMAT_VERSION_KEY], graph_format_version());
+
+        // Already current, nothing to do.
+        let report = GraphMigrations::default().migrate(&mut doc).unwrap();
+        assert!(!report.migrated());
+        assert!(report.changes.is_empty());
+    }
+
+    #[test]
+    fn set_format_version_matches_migrations() {
+        let mut migrations = GraphMigrations::default();
+        let mut doc = doc(None);
+        crate::document::set_format_version(&mut doc);
+        assert_eq!(doc[FORMAT_VERSION_KEY], migrations.latest_version());
+
+        migrations.add(Migration::new(graph_format_version() + 1, "Test"));
+        assert_eq!(migrations.latest_version(), graph_format_version() + 1);
+    }
+
+    #[test]
+    fn rejects_newer_version() {
+        let mut doc = doc(Some(graph_format_version() + 1));
+        assert!(GraphMigrations::default().migrate(&mut doc).is_err());
+    }
+
+    #[test]
+    fn rejects_invalid_version() {
+        for version in [json!(1.5), json!("1"), json!(-1), json!(null), json!(u64::MAX)] {
+            let mut doc = doc(None);
+            doc[FORMAT_VERSION_KEY] = version.clone();
+            let err = GraphMigrations::default().migrate(&mut doc).unwrap_err();
+            assert!(err.to_string().contains("Invalid graph format version"), "{version}");
+        }
+    }
+
+    #[test]
+    fn only_newer_migrations_run() {
+        fn mark(doc: &mut Value, changes: &mut Vec<String>) -> Result<()> {
+            doc["marked"] = true.into();
+            changes.push("marked".into());
+            Ok(())
+        }
+        let latest = graph_format_version();
+        let mut migrations = GraphMigrations::default();
+        migrations.add(Migration::new(latest + 1, "Mark").step(MigrationStep::Custom(mark)));
+
+        let mut current = doc(Some(latest));
+        let report = migrations.migrate(&mut current).unwrap();
+        assert_eq!(current["marked"], true);
+        assert_eq!(
+            report.changes,
+            [format!("v{}: Mark", latest + 1), "marked".into()]
+        );
+        assert_eq!(current[FORMAT_VERSION_KEY], latest + 1);
+
+        let mut upgraded = doc(Some(latest + 1));
+        migrations.migrate(&mut upgraded).unwrap();
+        assert!(upgraded.get("marked").is_none());
+    }
+
+    #[test]
+    fn rename_port_keeps_order() {
+        let mut migrations = GraphMigrations::default();
+        migrations.add(Migration::new(graph_format_version() + 1, "Rename x").step(
+            MigrationStep::RenamePort {
+                node_type: "old-add",
+                from: "x",
+                to: "a",
+            },
+        ));
+        let mut doc = doc(Some(graph_format_version()));
+        let report = migrations.migrate(&mut doc).unwrap();
+        assert_eq!(field_names(&doc), ["a", "y"]);
+        assert_eq!(doc["nodes"][0]["node"]["a"]["value"], 1.0);
+        assert!(report
+            .changes
+            .contains(&"Node n1: renamed `x` to `a`".to_string()));
+    }
+
+    #[test]
+    fn rename_node_type_and_fields() {
+        let mut migrations = GraphMigrations::default();
+        migrations.add(
+            Migration::new(graph_format_version() + 1, "Replace Old Add").step(
+                MigrationStep::RenameNodeType {
+                    node_type: "old-add",
+                    name: "Add",
+                    fields: &[("x", "a"), ("y", "b")],
+                },
+            ),
+        );
+        let mut doc = doc(Some(graph_format_version()));
+        migrations.migrate(&mut doc).unwrap();
+
+        let add = NodeTemplates::default().require("Add").unwrap().clone();
+        assert_eq!(doc["nodes"][0]["name"], "Add");
+        assert_eq!(doc["nodes"][0]["node_type"], add.node_type.as_str());
+        assert_eq!(field_names(&doc), ["a", "b"]);
+        assert_eq!(add.inputs(), ["a", "b"]);
+    }
+
+    #[test]
+    fn moves_node_types() {
+        // `PBR Fragment` from `bevy_shader_graph::nodes`.
+        let mut doc = doc(Some(1));
+        doc["nodes"][0]["node_type"] = "2ffc6f4f-2e5e-5213-b9d6-6d299b94b05a".into();
+        let report = GraphMigrations::default().migrate(&mut doc).unwrap();
+        assert_eq!(
+            doc["nodes"][0]["node_type"],
+            "e6affb0f-bccb-5d59-82ae-a2c3b9b3663d"
+        );
+        assert_eq!(report.changes.len(), 2);
+    }
+}
@@ -0,0 +1,583 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use anyhow::Result;
+use serde_json::{json, Value};
+
+use node_engine::NodeGraph;
+
+use crate::document::{
+    doc_nodes, doc_nodes_mut, graph_from_value, graph_to_value, input_source, is_input, node_id,
+    node_kind, set_value_floats, value_floats, NodeTemplates,
+};
+
+/// A math node that can be evaluated when all its inputs are constants.
+struct FoldOp {
+    name: &'static str,
+    inputs: &'static [&'static str],
+    /// Evaluate one component.
+    eval: fn(&[f32]) -> f32,
+}
+
+const BINARY: &[&str] = &["a", "b"];
+
+const FOLD_OPS: &[FoldOp] = &[
+    FoldOp {
+        name: "Add",
+        inputs: BINARY,
+        eval: |v| v[0] + v[1],
+    },
+    FoldOp {
+        name: "Subtract",
+        inputs: BINARY,
+        eval: |v| v[0] - v[1],
+    },
+    FoldOp {
+        name: "Multiply",
+        inputs: BINARY,
+        eval: |v| v[0] * v[1],
+    },
+    FoldOp {
+        name: "Divide",
+        inputs: BINARY,
+        eval: |v| v[0] / v[1],
+    },
+    FoldOp {
+        name: "Min",
+        inputs: BINARY,
+        eval: |v| v[0].min(v[1]),
+    },
+    FoldOp {
+        name: "Max",
+        inputs: BINARY,
+        eval: |v| v[0].max(v[1]),
+    },
+    FoldOp {
+        name: "Absolute",
+        inputs: &["a"],
+        eval: |v| v[0].abs(),
+    },
+    FoldOp {
+        name: "Fraction",
+        inputs: &["a"],
+        eval: |v| v[0] - v[0].floor(),
+    },
+    FoldOp {
+        name: "Clamp",
+        inputs: &["input", "min", "max"],
+        eval: |v| v[0].max(v[1]).min(v[2]),
+    },
+    FoldOp {
+        name: "Lerp",
+        inputs: &["a", "b", "t"],
+        eval: |v| v[0] + (v[1] - v[0]) * v[2],
+    },
+];
+
+/// Which optimizations to run before compiling.
+#[derive(Clone, Debug)]
+pub struct OptimizeOptions {
+    /// Evaluate math nodes with constant inputs and simplify identities (`x * 1`, `lerp(a, b, 0)`).
+    pub fold_constants: bool,
+    /// Merge nodes doing the same computation on the same inputs.
+    pub merge_duplicates: bool,
+    /// Remove nodes that don't contribute to the output.
+    pub remove_dead_nodes: bool,
+}
+
+impl Default for OptimizeOptions {
+    fn default() -> Self {
+        Self {
+            fold_constants: true,
+            merge_duplicates: true,
+            remove_dead_nodes: true,
+        }
+    }
+}
+
+/// What the optimizer changed.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct OptimizeReport {
+    /// Nodes replaced by constants or bypassed.
+    pub folded: usize,
+    /// Duplicate nodes merged.
+    pub merged: usize,
+    /// Dead nodes removed.
+    pub removed: usize,
+}
+
+impl OptimizeReport {
+    pub fn is_empty(&self) -> bool {
+        self.folded == 0 && self.merged == 0 && self.removed == 0
+    }
+}
+
+impl fmt::Display for OptimizeReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} folded, {} merged, {} removed",
+            self.folded, self.merged, self.removed
+        )
+    }
+}
+
+/// Optimize a copy of a shader graph.
+pub fn optimize_graph(
+    graph: &NodeGraph,
+    options: &OptimizeOptions,
+) -> Result<(NodeGraph, OptimizeReport)> {
+    let mut doc = graph_to_value(graph)?;
+    let mut templates = NodeTemplates::default();
+    let report = optimize_document(&mut doc, &mut templates, options);
+    Ok((graph_from_value(doc)?, report))
+}
+
+/// Optimize a graph document in place.
+pub fn optimize_document(
+    doc: &mut Value,
+    templates: &mut NodeTemplates,
+    options: &OptimizeOptions,
+) -> OptimizeReport {
+    let mut report = OptimizeReport::default();
+    let Some(output) = doc
+        .get("output")
+        .and_then(Value::as_str)
+        .map(str::to_string)
+    else {
+        return report;
+    };
+    let names: Vec<&str> = FOLD_OPS.iter().map(|op| op.name).collect();
+    templates.preload(&names);
+
+    // Folding can expose duplicates and the other way around.
+    loop {
+        let mut changed = false;
+        if options.fold_constants {
+            changed |= fold_constants(doc, templates, &output, &mut report);
+        }
+        if options.merge_duplicates {
+            changed |= merge_duplicates(doc, &output, &mut report);
+        }
+        if !changed {
+            break;
+        }
+    }
+    if options.remove_dead_nodes {
+        remove_dead_nodes(doc, &output, &mut report);
+    }
+    sync_connections(doc);
+    report
+}
+
+/// What replaces the output of a node.
+#[derive(Debug, PartialEq)]
+enum Replacement {
+    /// Another node's output and its data type.
+    Output(String, u64, Value),
+    /// A constant value.
+    Value(Vec<f32>),
+}
+
+/// The replacement for reading an input: its source or constant value.
+fn input_replacement(field: &Value) -> Option<Replacement> {
+    match input_source(field) {
+        Some((node, idx)) => Some(Replacement::Output(
+            node.to_string(),
+            idx,
+            field["connected"][1].clone(),
+        )),
+        None => value_floats(&field["value"]).map(Replacement::Value),
+    }
+}
+
+/// Number of components of an input: its constant value or the type of the
+/// connected output.  `None` for matrices and dynamic types.
+fn input_width(field: &Value) -> Option<usize> {
+    if field["connected"].is_null() {
+        return value_floats(&field["value"]).map(|v| v.len());
+    }
+    match field["connected"][1].as_str()? {
+        "F32" => Some(1),
+        "Vec2" => Some(2),
+        "Vec3" => Some(3),
+        "Vec4" => Some(4),
+        _ => None,
+    }
+}
+
+/// The constant value of an unconnected input.
+fn constant_input(node: &Value, name: &str) -> Option<Vec<f32>> {
+    let field = node["node"].get(name)?;
+    if !field["connected"].is_null() {
+        return None;
+    }
+    value_floats(&field["value"]).filter(|v| !v.is_empty() && v.len() <= 4)
+}
+
+/// Evaluate a fold op component-wise, scalars are splatted.
+fn eval_op(op: &FoldOp, args: &[Vec<f32>]) -> Option<Vec<f32>> {
+    let len = args.iter().map(Vec::len).max()?;
+    let splat = args.iter().all(|arg| arg.len() == 1 || arg.len() == len);
+    if !splat {
+        return None;
+    }
+    let values: Vec<f32> = (0..len)
+        .map(|c| {
+            let v: Vec<f32> = args
+                .iter()
+                .map(|arg| if arg.len() == 1 { arg[0] } else { arg[c] })
+                .collect();
+            (op.eval)(&v)
+        })
+        .collect();
+    values.iter().all(|v| v.is_finite()).then_some(values)
+}
+
+/// Find the replacement for a node's output (idx 0), if it can be simplified.
+fn simplify(kind: &str, node: &Value) -> Option<Replacement> {
+    let op = FOLD_OPS.iter().find(|op| op.name == kind)?;
+    let args: Option<Vec<Vec<f32>>> = op.inputs.iter().map(|i| constant_input(node, i)).collect();
+    if let Some(values) = args.and_then(|args| eval_op(op, &args)) {
+        return Some(Replacement::Value(values));
+    }
+
+    // Identities, only for scalar constants so the result type doesn't change.
+    let scalar = |name: &str| {
+        constant_input(node, name)
+            .filter(|v| v.len() == 1)
+            .map(|v| v[0])
+    };
+    // The result has the width of the widest input (scalars are splatted), only
+    // pass through an input of that width.
+    let widths: Option<Vec<usize>> = op
+        .inputs
+        .iter()
+        .map(|i| input_width(node["node"].get(*i)?))
+        .collect();
+    let result_width = widths.and_then(|w| w.into_iter().max());
+    let pass = |name: &str| {
+        let field = node["node"].get(name)?;
+        if result_width.is_none() || input_width(field) != result_width {
+            return None;
+        }
+        input_replacement(field)
+    };
+    match (kind, scalar("a"), scalar("b")) {
+        ("Add", _, Some(0.0)) | ("Subtract", _, Some(0.0)) => pass("a"),
+        ("Add", Some(0.0), _) => pass("b"),
+        ("Multiply", _, Some(1.0)) | ("Divide", _, Some(1.0)) => pass("a"),
+        ("Multiply", Some(1.0), _) => pass("b"),
+        ("Lerp", _, _) => match scalar("t") {
+            Some(0.0) => pass("a"),
+            Some(1.0) => pass("b"),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Point all consumers of `node`'s output `idx` at the replacement.
+///
+/// Returns the number of inputs changed.  Constants can only replace
+/// connections into inputs that have a value, scalars are splatted like the
+/// connection would.
+fn replace_output(doc: &mut Value, node: &str, idx: u64, with: &Replacement) -> usize {
+    let mut count = 0;
+    for consumer in doc_nodes_mut(doc) {
+        let Some(fields) = consumer.get_mut("node").and_then(Value::as_object_mut) else {
+            continue;
+        };
+        for field in fields.values_mut() {
+            if !is_input(field) || input_source(field) != Some((node, idx)) {
+                continue;
+            }
+            match with {
+                Replacement::Output(src, src_idx, data_type) => {
+                    field["connected"] = json!([{ "node": src, "idx": src_idx }, data_type]);
+                }
+                Replacement::Value(values) => {
+                    let width = value_floats(&field["value"]).map_or(0, |v| v.len());
+                    let values = match values.as_slice() {
+                        [v] if width > 1 && !field["value"].is_object() => vec![*v; width],
+                        _ => values.clone(),
+                    };
+                    if field["value"].is_null() || !set_value_floats(&mut field["value"], &values) {
+                        continue;
+                    }
+                    field["connected"] = Value::Null;
+                }
+            }
+            count += 1;
+        }
+    }
+    count
+}
+
+fn fold_constants(
+    doc: &mut Value,
+    templates: &NodeTemplates,
+    output: &str,
+    report: &mut OptimizeReport,
+) -> bool {
+    let replacements: Vec<(String, Replacement)> = doc_nodes(doc)
+        .filter(|node| node_id(node) != output)
+        .filter_map(|node| {
+            let with = simplify(node_kind(templates, node), node)?;
+            Some((node_id(node).to_string(), with))
+        })
+        .collect();
+    let mut changed = false;
+    for (id, with) in replacements {
+        if replace_output(doc, &id, 0, &with) > 0 {
+            report.folded += 1;
+            changed = true;
+        }
+    }
+    changed
+}
+
+fn merge_duplicates(doc: &mut Value, output: &str, report: &mut OptimizeReport) -> bool {
+    let mut nodes: Vec<&Value> = doc_nodes(doc).filter(|n| node_id(n) != output).collect();
+    nodes.sort_by_key(|n| node_id(n));
+    let mut seen: HashMap<String, &str> = HashMap::new();
+    let mut duplicates = Vec::new();
+    for node in nodes {
+        let key = format!("{}{}", node["node_type"], node["node"]);
+        match seen.get(&key) {
+            Some(first) => duplicates.push((node_id(node).to_string(), first.to_string())),
+            None => {
+                seen.insert(key, node_id(node));
+            }
+        }
+    }
+
+    let mut changed = false;
+    for (duplicate, first) in duplicates {
+        let mut count = 0;
+        for consumer in doc_nodes_mut(doc) {
+            let Some(fields) = consumer.get_mut("node").and_then(Value::as_object_mut) else {
+                continue;
+            };
+            for field in fields.values_mut() {
+                if is_input(field) && input_source(field).is_some_and(|(src, _)| src == duplicate) {
+                    field["connected"][0]["node"] = first.clone().into();
+                    count += 1;
+                }
+            }
+        }
+        if count > 0 {
+            report.merged += 1;
+            changed = true;
+        }
+    }
+    changed
+}
+
+fn remove_dead_nodes(doc: &mut Value, output: &str, report: &mut OptimizeReport) {
+    let nodes: HashMap<&str, &Value> = doc_nodes(doc).map(|n| (node_id(n), n)).collect();
+    let mut reachable = HashSet::new();
+    let mut stack = vec![output];
+    while let Some(id) = stack.pop() {
+        if !reachable.insert(id.to_string()) {
+            continue;
+        }
+        let Some(fields) = nodes.get(id).and_then(|n| n["node"].as_object()) else {
+            continue;
+        };
+        stack.extend(
+            fields
+                .values()
+                .filter_map(|field| input_source(field).map(|(src, _)| src)),
+        );
+    }
+
+    if let Some(nodes) = doc.get_mut("nodes").and_then(Value::as_array_mut) {
+        let before = nodes.len();
+        nodes.retain(|node| reachable.contains(node_id(node)));
+        report.removed += before - nodes.len();
+    }
+}
+
+/// Rebuild the `connections` list from the node inputs.
+fn sync_connections(doc: &mut Value) {
+    let mut connections = Vec::new();
+    for node in doc_nodes(doc) {
+        let Some(fields) = node["node"].as_object() else {
+            continue;
+        };
+        let inputs = fields.values().filter(|field| is_input(field));
+        for (idx, field) in inputs.enumerate() {
+            if let Some((src, src_idx)) = input_source(field) {
+                connections.push(json!({
+                    "input": { "node": node_id(node), "idx": idx },
+                    "output": { "node": src, "idx": src_idx },
+                }));
+            }
+        }
+    }
+    if let Some(obj) = doc.as_object_mut() {
+        obj.insert("connections".into(), connections.into());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input(value: Value) -> Value {
+        json!({ "value": value, "connected": null })
+    }
+
+    fn connected(value: Value, node: &str, data_type: &str) -> Value {
+        json!({ "value": value, "connected": [{ "node": node, "idx": 0 }, data_type] })
+    }
+
+    fn node(id: &str, name: &str, fields: Value) -> Value {
+        json!({ "id": id, "name": name, "node_type": format!("test::{name}"), "node": fields })
+    }
+
+    /// `uv` (a node without inputs) and the `out` node reading `result`.
+    fn graph(nodes: Vec<Value>, out_type: &str) -> Value {
+        let mut nodes = nodes;
+        nodes.push(node("uv", "UV", json!({})));
+        nodes.push(node(
+            "out",
+            "Output",
+            json!({ "color": connected(json!([1.0, 1.0, 1.0]), "result", out_type) }),
+        ));
+        json!({ "nodes": nodes, "connections": [], "output": "out" })
+    }
+
+    fn optimize(doc: &mut Value) -> OptimizeReport {
+        optimize_document(
+            doc,
+            &mut NodeTemplates::default(),
+            &OptimizeOptions::default(),
+        )
+    }
+
+    fn find<'a>(doc: &'a Value, id: &str) -> Option<&'a Value> {
+        doc_nodes(doc).find(|node| node_id(node) == id)
+    }
+
+    fn color(doc: &Value) -> &Value {
+        &find(doc, "out").unwrap()["node"]["color"]
+    }
+
+    #[test]
+    fn folds_constants() {
+        let mut doc = graph(
+            vec![
+                node(
+                    "add",
+                    "Add",
+                    json!({ "a": input(json!(1.0)), "b": input(json!(2.0)) }),
+                ),
+                node(
+                    "result",
+                    "Multiply",
+                    json!({ "a": connected(json!(0.0), "add", "F32"), "b": input(json!(3.0)) }),
+                ),
+            ],
+            "F32",
+        );
+        let report = optimize(&mut doc);
+        assert_eq!(report.folded, 2);
+        assert_eq!(report.removed, 3);
+        // The scalar is splatted into the `vec3` input.
+        assert_eq!(color(&doc)["value"], json!([9.0, 9.0, 9.0]));
+        assert!(color(&doc)["connected"].is_null());
+        assert_eq!(doc["connections"], json!([]));
+    }
+
+    #[test]
+    fn identity_keeps_source_type() {
+        let mut doc = graph(
+            vec![node(
+                "result",
+                "Multiply",
+                json!({ "a": connected(json!(0.0), "uv", "Vec2"), "b": input(json!(1.0)) }),
+            )],
+            "DynamicVector",
+        );
+        let report = optimize(&mut doc);
+        assert_eq!(report.folded, 1);
+        assert_eq!(
+            color(&doc)["connected"],
+            json!([{ "node": "uv", "idx": 0 }, "Vec2"])
+        );
+        assert!(find(&doc, "result").is_none());
+        assert_eq!(
+            doc["connections"],
+            json!([{ "input": { "node": "out", "idx": 0 }, "output": { "node": "uv", "idx": 0 } }])
+        );
+    }
+
+    #[test]
+    fn skips_identity_that_changes_type() {
+        // `lerp(a, b, 0)` is `a`, but the result is a `vec3`.
+        let lerp = |a: Value, b: Value| {
+            graph(
+                vec![node(
+                    "result",
+                    "Lerp",
+                    json!({ "a": a, "b": b, "t": input(json!(0.0)) }),
+                )],
+                "Vec3",
+            )
+        };
+        let mut doc = lerp(
+            input(json!(0.5)),
+            connected(json!([0.0, 0.0, 0.0]), "uv", "Vec3"),
+        );
+        assert!(optimize(&mut doc).is_empty());
+        assert_eq!(color(&doc)["connected"][0]["node"], "result");
+
+        // Same width, passed through.
+        let mut doc = lerp(
+            connected(json!([0.0, 0.0, 0.0]), "uv", "Vec3"),
+            input(json!([1.0, 1.0, 1.0])),
+        );
+        assert_eq!(optimize(&mut doc).folded, 1);
+        assert_eq!(color(&doc)["connected"][0]["node"], "uv");
+
+        // Unknown (dynamic) width, kept.
+        let mut doc = lerp(
+            connected(json!([0.0, 0.0, 0.0]), "uv", "Dynamic"),
+            input(json!([1.0, 1.0, 1.0])),
+        );
+        assert_eq!(optimize(&mut doc).folded, 0);
+
+        // Constants are folded to the widest input.
+        let mut doc = lerp(input(json!(0.5)), input(json!([1.0, 2.0, 3.0])));
+        assert_eq!(optimize(&mut doc).folded, 1);
+        assert_eq!(color(&doc)["value"], json!([0.5, 0.5, 0.5]));
+    }
+
+    #[test]
+    fn merges_duplicates() {
+        let add = || json!({ "a": connected(json!(0.0), "uv", "Vec2"), "b": input(json!(0.5)) });
+        let mut doc = graph(
+            vec![
+                node("add1", "Add", add()),
+                node("add2", "Add", add()),
+                node(
+                    "result",
+                    "Multiply",
+                    json!({
+                        "a": connected(json!(0.0), "add1", "Vec2"),
+                        "b": connected(json!(0.0), "add2", "Vec2"),
+                    }),
+                ),
+            ],
+            "Vec2",
+        );
+        let report = optimize(&mut doc);
+        assert_eq!(report.merged, 1);
+        assert_eq!(report.removed, 1);
+        let result = find(&doc, "result").unwrap();
+        assert_eq!(input_source(&result["node"]["b"]), Some(("add1", 0)));
+        assert!(find(&doc, "add2").is_none());
+    }
+}
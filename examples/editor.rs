@@ -37,7 +37,7 @@ fn main() -> Result<()> {
         FrameTimeDiagnosticsPlugin::default(),
     ));
 
-    app.add_plugins(ShaderGraphMaterialPlugin)
+    app.add_plugins(ShaderGraphMaterialPlugin::<StandardMaterial>::default())
         .add_plugins(
             bevy_inspector_egui::quick::WorldInspectorPlugin::new()
                 .run_if(common_conditions::input_toggle_active(false, KeyCode::KeyE)),
@@ -46,7 +46,8 @@ fn main() -> Result<()> {
         .insert_resource(editor)
         .add_systems(Startup, setup)
         .add_systems(Update, handle_quit)
-        .add_systems(EguiPrimaryContextPass, shader_editor);
+        .add_systems(EguiPrimaryContextPass, shader_editor)
+        .add_systems(Update, update_graph_materials::<StandardShaderGraphMaterial>);
 
     app.run();
 
@@ -117,7 +118,7 @@ fn setup(
     // wall
     let mut wall = commands.spawn((
         mesh.clone(),
-        Transform::from_xyz(0.0, 0.0, 0.0).with_rotation(Quat::from_rotation_x(1.570796)),
+        Transform::from_xyz(0.0, 0.0, 0.0).with_rotation(Quat::from_rotation_x(std::f32::consts::FRAC_PI_2)),
         MeshMaterial3d(mat1.clone()),
     ));
     wall.insert(Name::new("Wall"));
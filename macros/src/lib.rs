@@ -0,0 +1,97 @@
+//! Compile-time shader graph embedding for `bevy_shader_graph`.
+
+use std::path::PathBuf;
+
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, LitStr};
+
+use node_engine::NodeGraphCompile;
+
+use bevy_shader_graph_document::{graph_to_value, load_graph, optimize_graph, OptimizeOptions};
+// Linking the nodes crate also registers its nodes (output nodes, mesh, view and
+// light data) with the `NodeRegistry` of the compiler.
+use bevy_shader_graph_nodes::doc_vertex_attributes;
+
+/// Compile a shader graph file during `cargo build` and embed the WGSL.
+///
+/// The path is relative to the crate's `Cargo.toml`.  The graph is loaded like the
+/// editor does (JSON, RON or `.sgb`, migrated to the current format) and optimized.
+/// Expands to a `bevy_shader_graph::EmbeddedShaderGraph` constant expression, register
+/// it with `App::embed_shader_graph` and load its shader with `EmbeddedShaderGraph::load`.
+///
+/// ```ignore
+/// const WALL: EmbeddedShaderGraph = shader_graph!("assets/graphs/wall.json");
+/// ```
+#[proc_macro]
+pub fn shader_graph(input: TokenStream) -> TokenStream {
+    let path = parse_macro_input!(input as LitStr);
+    match expand(&path) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => syn::Error::new(path.span(), err).to_compile_error().into(),
+    }
+}
+
+fn expand(path: &LitStr) -> Result<proc_macro2::TokenStream, String> {
+    let rel_path = path.value();
+    let manifest_dir = std::env::var("CARGO_MANIFEST_DIR")
+        .map_err(|_| "`CARGO_MANIFEST_DIR` not set".to_string())?;
+    let full_path = PathBuf::from(manifest_dir).join(&rel_path);
+    let full_path_str = full_path.to_string_lossy().to_string();
+
+    let (graph, _) = load_graph(&full_path)
+        .map_err(|err| format!("Failed to load shader graph `{full_path_str}`: {err:?}"))?;
+    // Same fallback as the editor, compile the graph as-is if it can't be optimized.
+    let graph = match optimize_graph(&graph, &OptimizeOptions::default()) {
+        Ok((optimized, _)) => optimized,
+        Err(_) => graph,
+    };
+
+    let mut compiler = NodeGraphCompile::new();
+    compiler.define_block("imports");
+    compiler.define_block("bindings");
+    compiler
+        .compile_graph(&graph)
+        .map_err(|err| format!("Failed to compile shader graph `{rel_path}`: {err:?}"))?;
+    let code = compiler.dump();
+
+    let doc = graph_to_value(&graph).map_err(|err| format!("{err:?}"))?;
+    let attributes = doc_vertex_attributes(&doc);
+
+    Ok(quote! {
+        {
+            // Rebuild when the graph file changes.
+            const _: &[u8] = include_bytes!(#full_path_str);
+            ::bevy_shader_graph::EmbeddedShaderGraph {
+                path: #rel_path,
+                code: #code,
+                attributes: &[#(#attributes),*],
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use bevy_shader_graph_document::save_graph;
+    use bevy_shader_graph_nodes::MasterMode;
+    use proc_macro2::Span;
+
+    #[test]
+    fn embeds_pbr_graph() -> anyhow::Result<()> {
+        let path = std::env::temp_dir().join(format!(
+            "bevy_shader_graph_macros_{}.json",
+            std::process::id()
+        ));
+        save_graph(&path, &MasterMode::Pbr.new_graph()?)?;
+
+        let tokens = expand(&LitStr::new(&path.to_string_lossy(), Span::call_site()));
+        std::fs::remove_file(&path)?;
+        let tokens = tokens.map_err(anyhow::Error::msg)?.to_string();
+        assert!(tokens.contains("EmbeddedShaderGraph"));
+        assert!(tokens.contains("graph_pbr_input"));
+        Ok(())
+    }
+}
@@ -0,0 +1,138 @@
+//! Output and input nodes for decal graphs.
+
+use glam::{Vec2, Vec3};
+
+use anyhow::Result;
+
+use node_engine::*;
+
+use super::{append_once, begin_fragment, emit, output};
+
+const DECAL_IMPORTS: &str = "#import bevy_pbr::{
+	decal::forward::get_forward_decal_info,
+	forward_io::{VertexOutput, FragmentOutput},
+	pbr_functions::{apply_pbr_lighting, main_pass_post_lighting_processing},
+}
+#import bevy_shader_graph::pbr_input::graph_pbr_input
+";
+
+/// Moves the fragment onto the surface below the decal, so `in` (and the
+/// `UV` node) are in decal space.
+const DECAL_HEADER: &str = "@fragment
+fn fragment(
+  decal_vertex: VertexOutput,
+  @builtin(front_facing) is_front: bool,
+) -> FragmentOutput {
+	let decal_info = get_forward_decal_info(decal_vertex);
+	var in = decal_vertex;
+	in.world_position = decal_info.world_position;
+	in.uv = decal_info.uv;
+	var pbr_input = graph_pbr_input(in, is_front);
+";
+
+impl_node! {
+  mod decal_fragment_node {
+    NodeInfo {
+      name: "Decal Fragment",
+      category: ["Output"],
+    }
+
+    /// Output of a `ShaderGraphDecalMaterial`, lit with PBR lighting.
+    #[derive(Default)]
+    pub struct DecalFragmentNode {
+      pub color: Input<Vec3>,
+      /// Limited by the decal's depth fade.
+      pub alpha: Input<f32>,
+    }
+
+    impl DecalFragmentNode {
+      pub fn new() -> Self {
+        Self {
+          alpha: InputTyped::new(1.0),
+          ..Default::default()
+        }
+      }
+    }
+
+    impl NodeImpl for DecalFragmentNode {
+      fn compile(&self, graph: &NodeGraph, compile: &mut NodeGraphCompile, _id: NodeId) -> Result<()> {
+        append_once(compile, "imports", super::DECAL_IMPORTS)?;
+        begin_fragment(compile, super::DECAL_HEADER)?;
+        let color = self.color.compile(graph, compile)?;
+        let alpha = self.alpha.compile(graph, compile)?;
+        emit(compile, format!("\tpbr_input.material.base_color = vec4<f32>({color}, 1.0);
+	var out: FragmentOutput;
+	out.color = apply_pbr_lighting(pbr_input);
+	out.color = main_pass_post_lighting_processing(pbr_input, out.color);
+	out.color.a = min({alpha}, decal_info.alpha);
+	return out;
+}}
+"))
+      }
+    }
+  }
+}
+
+impl_node! {
+  mod decal_uv_node {
+    NodeInfo {
+      name: "Decal UV",
+      category: ["Input", "Decal"],
+    }
+
+    /// Position in the decal, corrected for the depth of the surface below it.
+    #[derive(Default)]
+    pub struct DecalUvNode {
+      pub uv: Output<Vec2>,
+    }
+
+    impl DecalUvNode {
+      pub fn new() -> Self {
+        Default::default()
+      }
+    }
+
+    impl NodeImpl for DecalUvNode {
+      fn compile(&self, _graph: &NodeGraph, compile: &mut NodeGraphCompile, id: NodeId) -> Result<()> {
+        output(compile, id, &self.uv, "decal_info.uv")
+      }
+    }
+  }
+}
+
+impl_node! {
+  mod decal_angle_fade_node {
+    NodeInfo {
+      name: "Decal Angle Fade",
+      category: ["Input", "Decal"],
+    }
+
+    /// How directly the decal faces the surface below it, 1 when facing it and 0
+    /// when projected along it.  Raise it to a power for a sharper fade.
+    #[derive(Default)]
+    pub struct DecalAngleFadeNode {
+      pub fade: Output<f32>,
+    }
+
+    impl DecalAngleFadeNode {
+      pub fn new() -> Self {
+        Default::default()
+      }
+    }
+
+    impl NodeImpl for DecalAngleFadeNode {
+      fn compile(&self, _graph: &NodeGraph, compile: &mut NodeGraphCompile, id: NodeId) -> Result<()> {
+        // Surface normal from the screen space derivatives of the surface position.
+        let position = "decal_info.world_position.xyz";
+        output(
+          compile,
+          id,
+          &self.fade,
+          format!(
+            "abs(dot(normalize(cross(dpdx({position}), dpdy({position}))), normalize(decal_vertex.world_normal)))"
+          ),
+        )
+      }
+    }
+  }
+}
@@ -0,0 +1,42 @@
+//! Nodes reading Bevy's `globals` uniform.
+
+use anyhow::Result;
+
+use node_engine::*;
+
+use super::{add_view_binding_import, output};
+
+impl_node! {
+  mod time_node {
+    NodeInfo {
+      name: "Time",
+      category: ["Input", "Globals"],
+    }
+
+    /// Time values from Bevy's `globals` uniform, for animating a graph.
+    #[derive(Default)]
+    pub struct TimeNode {
+      /// Seconds since startup, wraps after an hour.
+      pub time: Output<f32>,
+      /// Seconds since the last frame.
+      pub delta_time: Output<f32>,
+      /// Frames since startup.
+      pub frame_count: Output<f32>,
+    }
+
+    impl TimeNode {
+      pub fn new() -> Self {
+        Default::default()
+      }
+    }
+
+    impl NodeImpl for TimeNode {
+      fn compile(&self, graph: &NodeGraph, compile: &mut NodeGraphCompile, id: NodeId) -> Result<()> {
+        add_view_binding_import(graph, compile, "Time", "globals")?;
+        output(compile, id, &self.time, "globals.time")?;
+        output(compile, id, &self.delta_time, "globals.delta_time")?;
+        output(compile, id, &self.frame_count, "f32(globals.frame_count)")
+      }
+    }
+  }
+}
@@ -0,0 +1,91 @@
+//! Shader graph nodes of `bevy_shader_graph`.
+//!
+//! Nodes are registered with `impl_node!`, so `NodeRegistry::build()` picks
+//! them up next to the `node_engine` nodes.
+
+use anyhow::{anyhow, Result};
+
+use node_engine::{NodeGraph, NodeGraphCompile, NodeId, OutputTyped, ValueType};
+
+pub mod decal;
+pub use decal::*;
+pub mod globals;
+pub use globals::*;
+pub mod lighting;
+pub use lighting::*;
+pub mod master;
+pub use master::*;
+pub mod mesh;
+pub use mesh::*;
+pub mod mesh2d;
+pub use mesh2d::*;
+pub mod placeholder;
+pub use placeholder::*;
+pub mod post_process;
+pub use post_process::*;
+pub mod ui;
+pub use ui::*;
+pub mod vertex_attributes;
+pub use vertex_attributes::*;
+pub mod view;
+pub use view::*;
+
+/// Add an `#import` to the `imports` block, once.
+pub(crate) fn add_import(compile: &mut NodeGraphCompile, import: &str) -> Result<()> {
+    append_once(compile, "imports", &format!("#import {import}\n"))
+}
+
+/// Import a view binding (`view` or `globals`) of the graph's mode.
+///
+/// Fails for UI graphs, UI materials don't get the view bindings.
+pub(crate) fn add_view_binding_import(
+    graph: &NodeGraph,
+    compile: &mut NodeGraphCompile,
+    node: &str,
+    binding: &str,
+) -> Result<()> {
+    let module = match MasterMode::from_graph(graph)? {
+        Some(
+            MasterMode::Pbr | MasterMode::Unlit | MasterMode::CustomLighting | MasterMode::Decal,
+        ) => "bevy_pbr::mesh_view_bindings",
+        Some(MasterMode::Mesh2d) => "bevy_sprite::mesh2d_view_bindings",
+        Some(MasterMode::PostProcess) => "bevy_shader_graph::post_process",
+        Some(MasterMode::Ui) => return Err(anyhow!("`{node}` is not supported in UI graphs")),
+        None => return Err(anyhow!("`{node}` needs a graph with an output node")),
+    };
+    add_import(compile, &format!("{module}::{binding}"))
+}
+
+/// Append code to a named block, unless the block already has it.
+pub(crate) fn append_once(compile: &mut NodeGraphCompile, block: &str, code: &str) -> Result<()> {
+    let block = compile
+        .get_block_mut(block)
+        .ok_or_else(|| anyhow!("Undefined block: {block:?}"))?;
+    if !block.dump().contains(code) {
+        block.append(code.into());
+    }
+    Ok(())
+}
+
+/// Start the `fragment` block with the entry point's header, the code of the
+/// output node's inputs goes after it.
+pub(crate) fn begin_fragment(compile: &mut NodeGraphCompile, header: impl Into<String>) -> Result<()> {
+    compile.push_new_block("fragment");
+    emit(compile, header)
+}
+
+/// Append code to the current block.
+pub(crate) fn emit(compile: &mut NodeGraphCompile, code: impl Into<String>) -> Result<()> {
+    compile.current_block()?.append(code.into());
+    Ok(())
+}
+
+/// Define the value of a node output as a WGSL expression.
+pub(crate) fn output<T: ValueType + Default, const N: u32, const C: u32>(
+    compile: &mut NodeGraphCompile,
+    id: NodeId,
+    output: &OutputTyped<T, N, C>,
+    expr: impl Into<String>,
+) -> Result<()> {
+    output.compile(compile, id, "graph_value", expr.into(), output.data_type())
+}
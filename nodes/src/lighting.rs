@@ -0,0 +1,125 @@
+//! Light data for custom lighting.
+//!
+//! Only directional and ambient lights are exposed, point and spot lights
+//! are not.  The nodes read the `PbrInput` of the `Custom Lighting Fragment`
+//! output, so they fail to compile in other graphs.
+
+use glam::Vec3;
+
+use anyhow::{anyhow, Result};
+
+use node_engine::*;
+
+use super::{add_import, output, MasterMode};
+
+/// Fail unless the graph has a `Custom Lighting Fragment` output.
+fn require_custom_lighting(graph: &NodeGraph, node: &str) -> Result<()> {
+    match MasterMode::from_graph(graph)? {
+        Some(MasterMode::CustomLighting) => Ok(()),
+        _ => Err(anyhow!(
+            "`{node}` is only supported in Custom Lighting graphs"
+        )),
+    }
+}
+
+impl_node! {
+  mod main_light_node {
+    NodeInfo {
+      name: "Main Light",
+      category: ["Input", "Lighting"],
+    }
+
+    /// The first directional light, zero if the scene has none.
+    #[derive(Default)]
+    pub struct MainLightNode {
+      /// World space direction towards the light.
+      pub direction: Output<Vec3>,
+      /// Light color, scaled by illuminance and exposure.
+      pub color: Output<Vec3>,
+      /// `dot(normal, direction)`, clamped to `[0, 1]`.
+      pub n_dot_l: Output<f32>,
+      /// Shadow attenuation, 1 is fully lit.
+      pub shadow: Output<f32>,
+    }
+
+    impl MainLightNode {
+      pub fn new() -> Self {
+        Default::default()
+      }
+    }
+
+    impl NodeImpl for MainLightNode {
+      fn compile(&self, graph: &NodeGraph, compile: &mut NodeGraphCompile, id: NodeId) -> Result<()> {
+        super::require_custom_lighting(graph, "Main Light")?;
+        add_import(
+          compile,
+          "bevy_shader_graph::lighting::{graph_light_color, graph_light_direction, graph_light_shadow}",
+        )?;
+        output(compile, id, &self.direction, "graph_light_direction(0u)")?;
+        output(compile, id, &self.color, "graph_light_color(0u)")?;
+        output(compile, id, &self.n_dot_l, "saturate(dot(pbr_input.N, graph_light_direction(0u)))")?;
+        output(compile, id, &self.shadow, "graph_light_shadow(pbr_input, 0u)")
+      }
+    }
+  }
+}
+
+impl_node! {
+  mod directional_lights_node {
+    NodeInfo {
+      name: "Directional Lights",
+      category: ["Input", "Lighting"],
+    }
+
+    /// All directional lights of the scene.
+    #[derive(Default)]
+    pub struct DirectionalLightsNode {
+      /// Sum of the lights' Lambert diffuse light, with shadows and exposure.
+      pub diffuse: Output<Vec3>,
+    }
+
+    impl DirectionalLightsNode {
+      pub fn new() -> Self {
+        Default::default()
+      }
+    }
+
+    impl NodeImpl for DirectionalLightsNode {
+      fn compile(&self, graph: &NodeGraph, compile: &mut NodeGraphCompile, id: NodeId) -> Result<()> {
+        super::require_custom_lighting(graph, "Directional Lights")?;
+        add_import(compile, "bevy_shader_graph::lighting::graph_directional_diffuse")?;
+        output(compile, id, &self.diffuse, "graph_directional_diffuse(pbr_input)")
+      }
+    }
+  }
+}
+
+impl_node! {
+  mod ambient_light_node {
+    NodeInfo {
+      name: "Ambient Light",
+      category: ["Input", "Lighting"],
+    }
+
+    /// The scene's ambient light.
+    #[derive(Default)]
+    pub struct AmbientLightNode {
+      /// Ambient color, scaled by brightness and exposure.
+      pub color: Output<Vec3>,
+    }
+
+    impl AmbientLightNode {
+      pub fn new() -> Self {
+        Default::default()
+      }
+    }
+
+    impl NodeImpl for AmbientLightNode {
+      fn compile(&self, graph: &NodeGraph, compile: &mut NodeGraphCompile, id: NodeId) -> Result<()> {
+        super::require_custom_lighting(graph, "Ambient Light")?;
+        add_import(compile, "bevy_shader_graph::lighting::graph_ambient_light")?;
+        output(compile, id, &self.color, "graph_ambient_light()")
+      }
+    }
+  }
+}
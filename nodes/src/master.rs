@@ -0,0 +1,309 @@
+//! Master (output) nodes for the lighting modes.
+
+use glam::{Vec3, Vec4};
+
+use anyhow::Result;
+
+use bevy_shader_graph_document::{doc_nodes, graph_to_value, node_id};
+use node_engine::*;
+
+use super::{append_once, begin_fragment, emit};
+
+/// Lighting mode (or target) of a graph, selected by its output node.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum MasterMode {
+    /// Standard PBR lighting of the graph's surface inputs (`PBR Fragment`).
+    #[default]
+    Pbr,
+    /// The graph's color is output as-is.
+    Unlit,
+    /// The graph computes the lit color itself, using the light data nodes.
+    CustomLighting,
+    /// A `Mesh2d` graph, for `ShaderGraphMaterial2d`.
+    Mesh2d,
+    /// A UI graph, for `ShaderGraphUiMaterial`.
+    Ui,
+    /// A fullscreen pass, for `ShaderGraphPostProcess`.
+    PostProcess,
+    /// A projected decal, for `ShaderGraphDecalMaterial`.
+    Decal,
+}
+
+impl MasterMode {
+    pub const ALL: [Self; 7] = [
+        Self::Pbr,
+        Self::Unlit,
+        Self::CustomLighting,
+        Self::Mesh2d,
+        Self::Ui,
+        Self::PostProcess,
+        Self::Decal,
+    ];
+
+    /// Registry name of the output node for this mode.
+    pub fn node_name(&self) -> &'static str {
+        match self {
+            Self::Pbr => "Pbr Fragment",
+            Self::Unlit => "Unlit Fragment",
+            Self::CustomLighting => "Custom Lighting Fragment",
+            Self::Mesh2d => "2d Fragment",
+            Self::Ui => "Ui Fragment",
+            Self::PostProcess => "Post Process",
+            Self::Decal => "Decal Fragment",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Pbr => "PBR",
+            Self::Unlit => "Unlit",
+            Self::CustomLighting => "Custom Lighting",
+            Self::Mesh2d => "2D",
+            Self::Ui => "UI",
+            Self::PostProcess => "Post Process",
+            Self::Decal => "Decal",
+        }
+    }
+
+    /// Find the mode of an output node.
+    pub fn from_node_name(name: &str) -> Option<Self> {
+        match name {
+            // `node_engine`'s color-only output, used by older graphs.
+            "Fragment" => Some(Self::Pbr),
+            _ => Self::ALL.into_iter().find(|mode| mode.node_name() == name),
+        }
+    }
+
+    /// Find the mode of a graph from its output node.
+    pub fn from_graph(graph: &NodeGraph) -> Result<Option<Self>> {
+        Ok(Self::from_document(&graph_to_value(graph)?))
+    }
+
+    /// Find the mode of a graph document from its output node.
+    pub fn from_document(doc: &serde_json::Value) -> Option<Self> {
+        let output = doc.get("output")?.as_str()?;
+        let node = doc_nodes(doc).find(|node| node_id(node) == output)?;
+        Self::from_node_name(node.get("name")?.as_str()?)
+    }
+
+    /// Create a graph with only the output node for this mode.
+    pub fn new_graph(&self) -> Result<NodeGraph> {
+        let registry = NodeRegistry::build();
+        let mut graph = NodeGraph::new();
+        let root = registry.new_by_name(self.node_name())?;
+        let output_id = graph.add(root);
+        graph.set_output(Some(output_id));
+        Ok(graph)
+    }
+}
+
+const MASTER_IMPORTS: &str = "#import bevy_pbr::pbr_types::STANDARD_MATERIAL_FLAGS_UNLIT_BIT
+#import bevy_shader_graph::pbr_input::graph_pbr_input
+#ifdef PREPASS_PIPELINE
+#import bevy_pbr::{
+	prepass_io::{VertexOutput, FragmentOutput},
+	pbr_deferred_functions::deferred_output,
+}
+#else
+#import bevy_pbr::{
+	forward_io::{VertexOutput, FragmentOutput},
+	pbr_functions::main_pass_post_lighting_processing,
+}
+#endif
+";
+
+const MASTER_HEADER: &str = "@fragment
+fn fragment(
+  in: VertexOutput,
+  @builtin(front_facing) is_front: bool,
+) -> FragmentOutput {
+	var pbr_input = graph_pbr_input(in, is_front);
+";
+
+/// Writes `color` as the final color.  The deferred pass gets it as an unlit
+/// base color, so the lighting pass doesn't light it again.
+const MASTER_FOOTER: &str = "#ifdef PREPASS_PIPELINE
+	pbr_input.material.base_color = graph_color;
+	pbr_input.material.flags |= STANDARD_MATERIAL_FLAGS_UNLIT_BIT;
+	let out = deferred_output(in, pbr_input);
+#else
+	var out: FragmentOutput;
+	out.color = main_pass_post_lighting_processing(pbr_input, graph_color);
+#endif
+	return out;
+}
+";
+
+const PBR_IMPORTS: &str = "#import bevy_shader_graph::pbr_input::graph_normal_mapping
+#import bevy_pbr::pbr_functions::alpha_discard
+#ifndef PREPASS_PIPELINE
+#import bevy_pbr::pbr_functions::apply_pbr_lighting
+#endif
+";
+
+/// Lights the graph's `PbrInput`.  The deferred pass gets the surface inputs
+/// and lights them in the lighting pass.
+const PBR_FOOTER: &str = "	pbr_input.material.base_color = alpha_discard(pbr_input.material, pbr_input.material.base_color);
+#ifdef PREPASS_PIPELINE
+	let out = deferred_output(in, pbr_input);
+#else
+	var out: FragmentOutput;
+	out.color = apply_pbr_lighting(pbr_input);
+	out.color = main_pass_post_lighting_processing(pbr_input, out.color);
+#endif
+	return out;
+}
+";
+
+const CUSTOM_LIGHTING_IMPORTS: &str = "#import bevy_shader_graph::pbr_input::graph_normal_mapping
+";
+
+/// Emit a fragment entry point that outputs `color` without PBR lighting.
+fn compile_color_fragment<const N: u32>(
+    graph: &NodeGraph,
+    compile: &mut NodeGraphCompile,
+    color: &InputTyped<Vec4, N>,
+) -> Result<()> {
+    append_once(compile, "imports", MASTER_IMPORTS)?;
+    begin_fragment(compile, MASTER_HEADER)?;
+    let color = color.compile(graph, compile)?;
+    emit(compile, format!("\tlet graph_color = {color};\n"))?;
+    emit(compile, MASTER_FOOTER)
+}
+
+impl_node! {
+  mod pbr_fragment_node {
+    NodeInfo {
+      name: "PBR Fragment",
+      category: ["Output"],
+    }
+
+    /// PBR output, the surface inputs are lit by the scene's lights.
+    #[derive(Default)]
+    pub struct PbrFragmentNode {
+      /// Base color.
+      pub color: Input<Vec3>,
+      pub alpha: Input<f32>,
+      pub metallic: Input<f32>,
+      /// Perceptual roughness, `1 - smoothness`.
+      pub roughness: Input<f32>,
+      /// Emitted light, not affected by lights.
+      pub emission: Input<Vec3>,
+      /// Ambient occlusion of the diffuse light.
+      pub occlusion: Input<f32>,
+      /// Tangent space normal, only applied when the mesh has tangents.
+      pub normal: Input<Vec3>,
+    }
+
+    impl PbrFragmentNode {
+      pub fn new() -> Self {
+        Self {
+          color: InputTyped::new(Vec3::ONE),
+          alpha: InputTyped::new(1.0),
+          roughness: InputTyped::new(0.5),
+          occlusion: InputTyped::new(1.0),
+          normal: InputTyped::new(Vec3::Z),
+          ..Default::default()
+        }
+      }
+    }
+
+    impl NodeImpl for PbrFragmentNode {
+      fn compile(&self, graph: &NodeGraph, compile: &mut NodeGraphCompile, _id: NodeId) -> Result<()> {
+        append_once(compile, "imports", super::MASTER_IMPORTS)?;
+        append_once(compile, "imports", super::PBR_IMPORTS)?;
+        begin_fragment(compile, super::MASTER_HEADER)?;
+        let color = self.color.compile(graph, compile)?;
+        let alpha = self.alpha.compile(graph, compile)?;
+        let metallic = self.metallic.compile(graph, compile)?;
+        let roughness = self.roughness.compile(graph, compile)?;
+        let emission = self.emission.compile(graph, compile)?;
+        let occlusion = self.occlusion.compile(graph, compile)?;
+        let normal = self.normal.compile(graph, compile)?;
+        let material = [
+          format!("base_color = vec4<f32>({color}, {alpha})"),
+          format!("metallic = {metallic}"),
+          format!("perceptual_roughness = {roughness}"),
+          format!("emissive = vec4<f32>({emission}, 1.0)"),
+        ];
+        for field in material {
+          emit(compile, format!("\tpbr_input.material.{field};\n"))?;
+        }
+        emit(compile, format!("\tpbr_input.diffuse_occlusion = vec3<f32>({occlusion});\n"))?;
+        emit(compile, format!("\tpbr_input.N = graph_normal_mapping(pbr_input, in, is_front, {normal});\n"))?;
+        emit(compile, super::PBR_FOOTER)
+      }
+    }
+  }
+}
+
+impl_node! {
+  mod unlit_fragment_node {
+    NodeInfo {
+      name: "Unlit Fragment",
+      category: ["Output"],
+    }
+
+    /// Unlit output, the color is not affected by lights.
+    #[derive(Default)]
+    pub struct UnlitFragmentNode {
+      /// Final color (with alpha).
+      pub color: Input<Vec4>,
+    }
+
+    impl UnlitFragmentNode {
+      pub fn new() -> Self {
+        Default::default()
+      }
+    }
+
+    impl NodeImpl for UnlitFragmentNode {
+      fn compile(&self, graph: &NodeGraph, compile: &mut NodeGraphCompile, _id: NodeId) -> Result<()> {
+        super::compile_color_fragment(graph, compile, &self.color)
+      }
+    }
+  }
+}
+
+impl_node! {
+  mod custom_lighting_fragment_node {
+    NodeInfo {
+      name: "Custom Lighting Fragment",
+      category: ["Output"],
+    }
+
+    /// Custom lighting output, build the lit color from the `Main Light`,
+    /// `Directional Lights` and `Ambient Light` nodes (e.g. for toon shading).
+    #[derive(Default)]
+    pub struct CustomLightingFragmentNode {
+      /// Lit color (with alpha), fog and tonemapping are still applied.
+      pub color: Input<Vec4>,
+      /// Tangent space normal, used by the light nodes.  Only applied when
+      /// the mesh has tangents.
+      pub normal: Input<Vec3>,
+    }
+
+    impl CustomLightingFragmentNode {
+      pub fn new() -> Self {
+        Self {
+          normal: InputTyped::new(Vec3::Z),
+          ..Default::default()
+        }
+      }
+    }
+
+    impl NodeImpl for CustomLightingFragmentNode {
+      fn compile(&self, graph: &NodeGraph, compile: &mut NodeGraphCompile, _id: NodeId) -> Result<()> {
+        append_once(compile, "imports", super::MASTER_IMPORTS)?;
+        append_once(compile, "imports", super::CUSTOM_LIGHTING_IMPORTS)?;
+        begin_fragment(compile, super::MASTER_HEADER)?;
+        // Set the normal first, the light nodes read `pbr_input.N`.
+        let normal = self.normal.compile(graph, compile)?;
+        emit(compile, format!("\tpbr_input.N = graph_normal_mapping(pbr_input, in, is_front, {normal});\n"))?;
+        let color = self.color.compile(graph, compile)?;
+        emit(compile, format!("\tlet graph_color = {color};\n"))?;
+        emit(compile, super::MASTER_FOOTER)
+      }
+    }
+  }
+}
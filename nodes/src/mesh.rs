@@ -0,0 +1,115 @@
+//! Per-instance mesh data, from `bevy_pbr::mesh_functions`.
+
+use glam::{Mat4, Vec3};
+
+use anyhow::Result;
+
+use node_engine::*;
+
+use super::{add_import, output};
+
+impl_node! {
+  mod object_transform_node {
+    NodeInfo {
+      name: "Object Transform",
+      category: ["Input", "Mesh"],
+    }
+
+    /// Transform of the mesh instance.
+    #[derive(Default)]
+    pub struct ObjectTransformNode {
+      /// Object to world space.
+      pub world_from_local: Output<Mat4>,
+      /// World space position of the object's origin.
+      pub origin: Output<Vec3>,
+      /// Object to world space in the previous frame.
+      pub previous_world_from_local: Output<Mat4>,
+      /// World space position of the object's origin in the previous frame.
+      pub previous_origin: Output<Vec3>,
+    }
+
+    impl ObjectTransformNode {
+      pub fn new() -> Self {
+        Default::default()
+      }
+    }
+
+    impl NodeImpl for ObjectTransformNode {
+      fn compile(&self, _graph: &NodeGraph, compile: &mut NodeGraphCompile, id: NodeId) -> Result<()> {
+        add_import(compile, "bevy_pbr::mesh_functions::get_world_from_local")?;
+        add_import(compile, "bevy_pbr::mesh_functions::get_previous_world_from_local")?;
+        output(compile, id, &self.world_from_local, "get_world_from_local(in.instance_index)")?;
+        output(compile, id, &self.origin, "get_world_from_local(in.instance_index)[3].xyz")?;
+        output(compile, id, &self.previous_world_from_local, "get_previous_world_from_local(in.instance_index)")?;
+        output(compile, id, &self.previous_origin, "get_previous_world_from_local(in.instance_index)[3].xyz")
+      }
+    }
+  }
+}
+
+impl_node! {
+  mod instance_node {
+    NodeInfo {
+      name: "Instance",
+      category: ["Input", "Mesh"],
+    }
+
+    /// Per-instance values.
+    #[derive(Default)]
+    pub struct InstanceNode {
+      /// Index of the instance in the mesh buffer, changes between frames.
+      pub index: Output<f32>,
+      /// `MeshTag` of the entity, 0 without one.
+      pub tag: Output<f32>,
+    }
+
+    impl InstanceNode {
+      pub fn new() -> Self {
+        Default::default()
+      }
+    }
+
+    impl NodeImpl for InstanceNode {
+      fn compile(&self, _graph: &NodeGraph, compile: &mut NodeGraphCompile, id: NodeId) -> Result<()> {
+        add_import(compile, "bevy_pbr::mesh_functions::get_tag")?;
+        output(compile, id, &self.index, "f32(in.instance_index)")?;
+        output(compile, id, &self.tag, "f32(get_tag(in.instance_index))")
+      }
+    }
+  }
+}
+
+impl_node! {
+  mod local_position_node {
+    NodeInfo {
+      name: "Local Position",
+      category: ["Input", "Mesh"],
+    }
+
+    /// Object space position of the fragment.
+    #[derive(Default)]
+    pub struct LocalPositionNode {
+      pub position: Output<Vec3>,
+    }
+
+    impl LocalPositionNode {
+      pub fn new() -> Self {
+        Default::default()
+      }
+    }
+
+    impl NodeImpl for LocalPositionNode {
+      fn compile(&self, _graph: &NodeGraph, compile: &mut NodeGraphCompile, id: NodeId) -> Result<()> {
+        add_import(compile, "bevy_pbr::mesh_bindings::mesh")?;
+        add_import(compile, "bevy_pbr::mesh_functions::get_world_from_local")?;
+        add_import(compile, "bevy_render::maths::mat2x4_f32_to_mat3x3_unpack")?;
+        // The mesh stores the inverse transpose of the object's 3x3 transform.
+        let local_from_world = "transpose(mat2x4_f32_to_mat3x3_unpack(\
+          mesh[in.instance_index].local_from_world_transpose_a, \
+          mesh[in.instance_index].local_from_world_transpose_b))";
+        let offset = "in.world_position.xyz - get_world_from_local(in.instance_index)[3].xyz";
+        output(compile, id, &self.position, format!("({local_from_world} * ({offset}))"))
+      }
+    }
+  }
+}
@@ -0,0 +1,101 @@
+//! Output and input nodes for `Mesh2d` graphs.
+
+use glam::{Vec2, Vec3};
+
+use anyhow::Result;
+
+use node_engine::*;
+
+use super::{append_once, begin_fragment, emit, output};
+
+const FRAGMENT_2D_IMPORTS: &str = "#import bevy_sprite::mesh2d_vertex_output::VertexOutput\n";
+
+impl_node! {
+  mod fragment_2d_node {
+    NodeInfo {
+      name: "2D Fragment",
+      category: ["Output"],
+    }
+
+    /// Output of a `ShaderGraphMaterial2d`.
+    #[derive(Default)]
+    pub struct Fragment2dNode {
+      pub color: Input<Vec3>,
+      pub alpha: Input<f32>,
+    }
+
+    impl Fragment2dNode {
+      pub fn new() -> Self {
+        Self {
+          alpha: InputTyped::new(1.0),
+          ..Default::default()
+        }
+      }
+    }
+
+    impl NodeImpl for Fragment2dNode {
+      fn compile(&self, graph: &NodeGraph, compile: &mut NodeGraphCompile, _id: NodeId) -> Result<()> {
+        append_once(compile, "imports", super::FRAGMENT_2D_IMPORTS)?;
+        begin_fragment(compile, "@fragment\nfn fragment(in: VertexOutput) -> @location(0) vec4<f32> {\n")?;
+        let color = self.color.compile(graph, compile)?;
+        let alpha = self.alpha.compile(graph, compile)?;
+        emit(compile, format!("\treturn vec4<f32>({color}, {alpha});\n}}\n"))
+      }
+    }
+  }
+}
+
+impl_node! {
+  mod sprite_uv_node {
+    NodeInfo {
+      name: "Sprite UV",
+      category: ["Input", "2D"],
+      custom: { vertex_attributes: "uv0" },
+    }
+
+    /// UV of the `Mesh2d`, `(0, 0)` at the top left of a sprite quad.
+    #[derive(Default)]
+    pub struct SpriteUvNode {
+      pub uv: Output<Vec2>,
+    }
+
+    impl SpriteUvNode {
+      pub fn new() -> Self {
+        Default::default()
+      }
+    }
+
+    impl NodeImpl for SpriteUvNode {
+      fn compile(&self, _graph: &NodeGraph, compile: &mut NodeGraphCompile, id: NodeId) -> Result<()> {
+        output(compile, id, &self.uv, "in.uv")
+      }
+    }
+  }
+}
+
+impl_node! {
+  mod world_position_2d_node {
+    NodeInfo {
+      name: "World Position 2D",
+      category: ["Input", "2D"],
+    }
+
+    /// World position of the fragment on the 2D plane.
+    #[derive(Default)]
+    pub struct WorldPosition2dNode {
+      pub position: Output<Vec2>,
+    }
+
+    impl WorldPosition2dNode {
+      pub fn new() -> Self {
+        Default::default()
+      }
+    }
+
+    impl NodeImpl for WorldPosition2dNode {
+      fn compile(&self, _graph: &NodeGraph, compile: &mut NodeGraphCompile, id: NodeId) -> Result<()> {
+        output(compile, id, &self.position, "in.world_position.xy")
+      }
+    }
+  }
+}
@@ -0,0 +1,37 @@
+//! Stand-in node for graph importers.
+
+use glam::Vec4;
+
+use anyhow::Result;
+
+use node_engine::*;
+
+use super::output;
+
+impl_node! {
+  mod placeholder_node {
+    NodeInfo {
+      name: "Placeholder",
+      category: ["Utility"],
+    }
+
+    /// A source node an importer couldn't convert, see the group title for the
+    /// original node.  Outputs zero.
+    #[derive(Default)]
+    pub struct PlaceholderNode {
+      pub out: Output<Vec4>,
+    }
+
+    impl PlaceholderNode {
+      pub fn new() -> Self {
+        Default::default()
+      }
+    }
+
+    impl NodeImpl for PlaceholderNode {
+      fn compile(&self, _graph: &NodeGraph, compile: &mut NodeGraphCompile, id: NodeId) -> Result<()> {
+        output(compile, id, &self.out, "vec4<f32>(0.0)")
+      }
+    }
+  }
+}
@@ -0,0 +1,154 @@
+//! Output and input nodes for post-process graphs.
+
+use glam::{Vec2, Vec3, Vec4};
+
+use anyhow::Result;
+
+use node_engine::*;
+
+use super::{add_import, append_once, begin_fragment, emit, output};
+
+const POST_PROCESS_IMPORTS: &str =
+    "#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput\n";
+
+impl_node! {
+  mod post_process_node {
+    NodeInfo {
+      name: "Post Process",
+      category: ["Output"],
+    }
+
+    /// Output of a `ShaderGraphPostProcess` pass.
+    #[derive(Default)]
+    pub struct PostProcessNode {
+      /// New screen color (with alpha).
+      pub color: Input<Vec4>,
+    }
+
+    impl PostProcessNode {
+      pub fn new() -> Self {
+        Default::default()
+      }
+    }
+
+    impl NodeImpl for PostProcessNode {
+      fn compile(&self, graph: &NodeGraph, compile: &mut NodeGraphCompile, _id: NodeId) -> Result<()> {
+        append_once(compile, "imports", super::POST_PROCESS_IMPORTS)?;
+        begin_fragment(compile, "@fragment\nfn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {\n")?;
+        let color = self.color.compile(graph, compile)?;
+        emit(compile, format!("\treturn {color};\n}}\n"))
+      }
+    }
+  }
+}
+
+impl_node! {
+  mod screen_uv_node {
+    NodeInfo {
+      name: "Screen UV",
+      category: ["Input", "Post Process"],
+    }
+
+    /// Position on the screen, `(0, 0)` at the top left.
+    #[derive(Default)]
+    pub struct ScreenUvNode {
+      pub uv: Output<Vec2>,
+    }
+
+    impl ScreenUvNode {
+      pub fn new() -> Self {
+        Default::default()
+      }
+    }
+
+    impl NodeImpl for ScreenUvNode {
+      fn compile(&self, _graph: &NodeGraph, compile: &mut NodeGraphCompile, id: NodeId) -> Result<()> {
+        output(compile, id, &self.uv, "in.uv")
+      }
+    }
+  }
+}
+
+impl_node! {
+  mod screen_color_node {
+    NodeInfo {
+      name: "Screen Color",
+      category: ["Input", "Post Process"],
+    }
+
+    /// Color of the rendered scene at this pixel.
+    #[derive(Default)]
+    pub struct ScreenColorNode {
+      pub color: Output<Vec4>,
+    }
+
+    impl ScreenColorNode {
+      pub fn new() -> Self {
+        Default::default()
+      }
+    }
+
+    impl NodeImpl for ScreenColorNode {
+      fn compile(&self, _graph: &NodeGraph, compile: &mut NodeGraphCompile, id: NodeId) -> Result<()> {
+        add_import(compile, "bevy_shader_graph::post_process::screen_color")?;
+        output(compile, id, &self.color, "screen_color(in.uv)")
+      }
+    }
+  }
+}
+
+impl_node! {
+  mod scene_depth_node {
+    NodeInfo {
+      name: "Scene Depth",
+      category: ["Input", "Post Process"],
+    }
+
+    /// Non-linear (reverse-z) depth of the scene at this pixel, 0 at the far plane.
+    #[derive(Default)]
+    pub struct SceneDepthNode {
+      pub depth: Output<f32>,
+    }
+
+    impl SceneDepthNode {
+      pub fn new() -> Self {
+        Default::default()
+      }
+    }
+
+    impl NodeImpl for SceneDepthNode {
+      fn compile(&self, _graph: &NodeGraph, compile: &mut NodeGraphCompile, id: NodeId) -> Result<()> {
+        add_import(compile, "bevy_shader_graph::post_process::scene_depth")?;
+        output(compile, id, &self.depth, "scene_depth(in.uv)")
+      }
+    }
+  }
+}
+
+impl_node! {
+  mod scene_normal_node {
+    NodeInfo {
+      name: "Scene Normal",
+      category: ["Input", "Post Process"],
+    }
+
+    /// World space normal of the scene at this pixel.
+    #[derive(Default)]
+    pub struct SceneNormalNode {
+      pub normal: Output<Vec3>,
+    }
+
+    impl SceneNormalNode {
+      pub fn new() -> Self {
+        Default::default()
+      }
+    }
+
+    impl NodeImpl for SceneNormalNode {
+      fn compile(&self, _graph: &NodeGraph, compile: &mut NodeGraphCompile, id: NodeId) -> Result<()> {
+        add_import(compile, "bevy_shader_graph::post_process::scene_normal")?;
+        output(compile, id, &self.normal, "scene_normal(in.uv)")
+      }
+    }
+  }
+}
@@ -0,0 +1,124 @@
+//! Output and input nodes for UI graphs.
+
+use glam::{Vec2, Vec4};
+
+use anyhow::Result;
+
+use node_engine::*;
+
+use super::{append_once, begin_fragment, emit, output};
+
+const UI_FRAGMENT_IMPORTS: &str = "#import bevy_ui::ui_vertex_output::UiVertexOutput\n";
+
+impl_node! {
+  mod ui_fragment_node {
+    NodeInfo {
+      name: "UI Fragment",
+      category: ["Output"],
+    }
+
+    /// Output of a `ShaderGraphUiMaterial`.
+    #[derive(Default)]
+    pub struct UiFragmentNode {
+      /// Final color (with alpha).
+      pub color: Input<Vec4>,
+    }
+
+    impl UiFragmentNode {
+      pub fn new() -> Self {
+        Default::default()
+      }
+    }
+
+    impl NodeImpl for UiFragmentNode {
+      fn compile(&self, graph: &NodeGraph, compile: &mut NodeGraphCompile, _id: NodeId) -> Result<()> {
+        append_once(compile, "imports", super::UI_FRAGMENT_IMPORTS)?;
+        begin_fragment(compile, "@fragment\nfn fragment(in: UiVertexOutput) -> @location(0) vec4<f32> {\n")?;
+        let color = self.color.compile(graph, compile)?;
+        emit(compile, format!("\treturn {color};\n}}\n"))
+      }
+    }
+  }
+}
+
+impl_node! {
+  mod ui_node_uv_node {
+    NodeInfo {
+      name: "UI Node UV",
+      category: ["Input", "UI"],
+    }
+
+    /// Position within the UI node, `(0, 0)` at the top left and `(1, 1)` at the bottom right.
+    #[derive(Default)]
+    pub struct UiNodeUvNode {
+      pub uv: Output<Vec2>,
+    }
+
+    impl UiNodeUvNode {
+      pub fn new() -> Self {
+        Default::default()
+      }
+    }
+
+    impl NodeImpl for UiNodeUvNode {
+      fn compile(&self, _graph: &NodeGraph, compile: &mut NodeGraphCompile, id: NodeId) -> Result<()> {
+        output(compile, id, &self.uv, "in.uv")
+      }
+    }
+  }
+}
+
+impl_node! {
+  mod ui_node_size_node {
+    NodeInfo {
+      name: "UI Node Size",
+      category: ["Input", "UI"],
+    }
+
+    /// Size of the UI node in logical pixels.
+    #[derive(Default)]
+    pub struct UiNodeSizeNode {
+      pub size: Output<Vec2>,
+    }
+
+    impl UiNodeSizeNode {
+      pub fn new() -> Self {
+        Default::default()
+      }
+    }
+
+    impl NodeImpl for UiNodeSizeNode {
+      fn compile(&self, _graph: &NodeGraph, compile: &mut NodeGraphCompile, id: NodeId) -> Result<()> {
+        output(compile, id, &self.size, "in.size")
+      }
+    }
+  }
+}
+
+impl_node! {
+  mod ui_border_radius_node {
+    NodeInfo {
+      name: "UI Border Radius",
+      category: ["Input", "UI"],
+    }
+
+    /// Corner radii of the UI node in pixels.
+    #[derive(Default)]
+    pub struct UiBorderRadiusNode {
+      /// Top left, top right, bottom right, bottom left.
+      pub radius: Output<Vec4>,
+    }
+
+    impl UiBorderRadiusNode {
+      pub fn new() -> Self {
+        Default::default()
+      }
+    }
+
+    impl NodeImpl for UiBorderRadiusNode {
+      fn compile(&self, _graph: &NodeGraph, compile: &mut NodeGraphCompile, id: NodeId) -> Result<()> {
+        output(compile, id, &self.radius, "in.border_radius")
+      }
+    }
+  }
+}
@@ -0,0 +1,128 @@
+//! Mesh vertex attributes read by the nodes.
+//!
+//! Nodes of this crate declare the attributes they read in their `NodeInfo`,
+//! `custom: { vertex_attributes: "uv0" }` (comma separated).
+
+use std::collections::HashMap;
+
+use serde_json::Value;
+
+use bevy_shader_graph_document::{doc_nodes, is_input};
+use node_engine::NodeRegistry;
+
+/// `NodeInfo` custom key listing the vertex attributes a node reads.
+pub const VERTEX_ATTRIBUTES_KEY: &str = "vertex_attributes";
+
+/// The vertex attribute names nodes can declare.
+pub const VERTEX_ATTRIBUTES: [&str; 3] = ["uv0", "uv1", "tangent"];
+
+/// Attributes read by `node_engine` nodes, they can't declare them.
+fn engine_node_attributes(name: &str, node: &Value) -> &'static [&'static str] {
+    match name {
+        "Uv" => &["uv0"],
+        // The tangent space needs the mesh tangents.
+        "View Direction" if node["space"].as_str() == Some("Tangent") => &["tangent"],
+        _ => &[],
+    }
+}
+
+/// The mesh vertex attributes (`uv0`, `uv1`, `tangent`) read by the nodes of a
+/// graph document.
+///
+/// Unconnected UV inputs (`[[u, v], "UV0"]`) read the UV channel they name.
+pub fn doc_vertex_attributes(doc: &Value) -> Vec<&'static str> {
+    let declared: HashMap<String, Vec<&'static str>> = NodeRegistry::build()
+        .nodes()
+        .into_iter()
+        .filter_map(|def| {
+            let names = def.custom.get(VERTEX_ATTRIBUTES_KEY)?;
+            let attributes = names
+                .split(',')
+                .filter_map(|name| {
+                    VERTEX_ATTRIBUTES
+                        .into_iter()
+                        .find(|attr| *attr == name.trim())
+                })
+                .collect();
+            Some((def.id.to_string(), attributes))
+        })
+        .collect();
+
+    let mut attributes = Vec::new();
+    let mut add = |attr: &'static str| {
+        if !attributes.contains(&attr) {
+            attributes.push(attr);
+        }
+    };
+    for node in doc_nodes(doc) {
+        let node_type = node
+            .get("node_type")
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        declared
+            .get(node_type)
+            .into_iter()
+            .flatten()
+            .for_each(|attr| add(attr));
+        let name = node.get("name").and_then(Value::as_str).unwrap_or_default();
+        let fields = node.get("node").unwrap_or(&Value::Null);
+        engine_node_attributes(name, fields)
+            .iter()
+            .for_each(|attr| add(attr));
+        for field in fields
+            .as_object()
+            .into_iter()
+            .flat_map(|fields| fields.values())
+        {
+            if !is_input(field) || !field["connected"].is_null() {
+                continue;
+            }
+            match field["value"].get(1).and_then(Value::as_str) {
+                Some("UV0") => add("uv0"),
+                Some("UV1") => add("uv1"),
+                _ => {}
+            }
+        }
+    }
+    attributes
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_shader_graph_document::graph_to_value;
+    use node_engine::NodeGraph;
+
+    use super::*;
+
+    fn doc(names: &[&str]) -> Value {
+        let registry = NodeRegistry::build();
+        let mut graph = NodeGraph::new();
+        for name in names {
+            graph.add(registry.new_by_name(name).unwrap());
+        }
+        graph_to_value(&graph).unwrap()
+    }
+
+    #[test]
+    fn declared_attributes() {
+        assert_eq!(doc_vertex_attributes(&doc(&["Sprite Uv"])), ["uv0"]);
+    }
+
+    #[test]
+    fn engine_node_attributes() {
+        assert_eq!(doc_vertex_attributes(&doc(&["Uv", "Uv"])), ["uv0"]);
+
+        let mut value = doc(&["View Direction"]);
+        assert!(doc_vertex_attributes(&value).is_empty());
+        for node in value["nodes"].as_array_mut().unwrap() {
+            node["node"]["space"] = "Tangent".into();
+        }
+        assert_eq!(doc_vertex_attributes(&value), ["tangent"]);
+    }
+
+    #[test]
+    fn nodes_without_attributes() {
+        let value = doc(&["Local Position", "Object Transform", "World Position 2d"]);
+        assert!(doc_vertex_attributes(&value).is_empty());
+    }
+}
@@ -0,0 +1,187 @@
+//! Nodes reading Bevy's view bindings.
+//!
+//! The `view` binding is imported for the graph's mode, UI graphs don't have it.
+
+use glam::{Mat4, Vec2, Vec3};
+
+use anyhow::{anyhow, Result};
+
+use node_engine::*;
+
+use super::{add_view_binding_import, output, MasterMode};
+
+impl_node! {
+  mod camera_node {
+    NodeInfo {
+      name: "Camera",
+      category: ["Input", "View"],
+    }
+
+    /// The camera rendering the fragment.
+    #[derive(Default)]
+    pub struct CameraNode {
+      /// World space position.
+      pub position: Output<Vec3>,
+      /// World space forward direction.
+      pub direction: Output<Vec3>,
+      /// Distance to the near plane.
+      pub near: Output<f32>,
+      /// Distance to the far plane, infinite for perspective cameras.
+      pub far: Output<f32>,
+      pub exposure: Output<f32>,
+      /// Viewport size in pixels.
+      pub viewport_size: Output<Vec2>,
+    }
+
+    impl CameraNode {
+      pub fn new() -> Self {
+        Default::default()
+      }
+    }
+
+    impl NodeImpl for CameraNode {
+      fn compile(&self, graph: &NodeGraph, compile: &mut NodeGraphCompile, id: NodeId) -> Result<()> {
+        add_view_binding_import(graph, compile, "Camera", "view")?;
+        output(compile, id, &self.position, "view.world_position")?;
+        output(compile, id, &self.direction, "normalize(-view.world_from_view[2].xyz)")?;
+        // Reverse-z: NDC depth 1 is the near plane and 0 the far plane.  View z is
+        // `(view_from_clip * vec4(0, 0, depth, 1)).z / .w`.
+        let near = "-(view.view_from_clip[2].z + view.view_from_clip[3].z) / (view.view_from_clip[2].w + view.view_from_clip[3].w)";
+        output(compile, id, &self.near, near)?;
+        output(compile, id, &self.far, "-view.view_from_clip[3].z / view.view_from_clip[3].w")?;
+        output(compile, id, &self.exposure, "view.exposure")?;
+        output(compile, id, &self.viewport_size, "view.viewport.zw")
+      }
+    }
+  }
+}
+
+impl_node! {
+  mod view_matrices_node {
+    NodeInfo {
+      name: "View Matrices",
+      category: ["Input", "View"],
+    }
+
+    /// Transforms of the current view.
+    #[derive(Default)]
+    pub struct ViewMatricesNode {
+      /// World to view space.
+      pub view: Output<Mat4>,
+      /// View to clip space.
+      pub projection: Output<Mat4>,
+      /// World to clip space.
+      pub view_projection: Output<Mat4>,
+      /// View to world space.
+      pub inverse_view: Output<Mat4>,
+      /// Clip to view space.
+      pub inverse_projection: Output<Mat4>,
+    }
+
+    impl ViewMatricesNode {
+      pub fn new() -> Self {
+        Default::default()
+      }
+    }
+
+    impl NodeImpl for ViewMatricesNode {
+      fn compile(&self, graph: &NodeGraph, compile: &mut NodeGraphCompile, id: NodeId) -> Result<()> {
+        add_view_binding_import(graph, compile, "View Matrices", "view")?;
+        output(compile, id, &self.view, "view.view_from_world")?;
+        output(compile, id, &self.projection, "view.clip_from_view")?;
+        output(compile, id, &self.view_projection, "view.clip_from_world")?;
+        output(compile, id, &self.inverse_view, "view.world_from_view")?;
+        output(compile, id, &self.inverse_projection, "view.view_from_clip")
+      }
+    }
+  }
+}
+
+impl_node! {
+  mod screen_position_node {
+    NodeInfo {
+      name: "Screen Position",
+      category: ["Input", "View"],
+    }
+
+    /// Position of the fragment on the screen.
+    #[derive(Default)]
+    pub struct ScreenPositionNode {
+      /// In the viewport, `(0, 0)` at the top left and `(1, 1)` at the bottom right.
+      pub uv: Output<Vec2>,
+      /// In pixels.
+      pub pixel: Output<Vec2>,
+    }
+
+    impl ScreenPositionNode {
+      pub fn new() -> Self {
+        Default::default()
+      }
+    }
+
+    impl NodeImpl for ScreenPositionNode {
+      fn compile(&self, graph: &NodeGraph, compile: &mut NodeGraphCompile, id: NodeId) -> Result<()> {
+        add_view_binding_import(graph, compile, "Screen Position", "view")?;
+        output(compile, id, &self.uv, "(in.position.xy - view.viewport.xy) / view.viewport.zw")?;
+        output(compile, id, &self.pixel, "in.position.xy")
+      }
+    }
+  }
+}
+
+impl_node! {
+  mod fresnel_effect_node {
+    NodeInfo {
+      name: "Fresnel Effect",
+      category: ["Input", "View"],
+    }
+
+    /// Rim factor, `(1 - saturate(dot(normal, view_dir)))^power`.  0 where the
+    /// surface faces the camera, 1 at grazing angles.
+    #[derive(Default)]
+    pub struct FresnelEffectNode {
+      /// World space normal, the mesh normal if not connected.
+      pub normal: Input<Vec3>,
+      /// Direction from the fragment to the camera, if not connected.
+      pub view_dir: Input<Vec3>,
+      /// Sharpness of the rim.
+      pub power: Input<f32>,
+      pub out: Output<f32>,
+    }
+
+    impl FresnelEffectNode {
+      pub fn new() -> Self {
+        Self {
+          power: InputTyped::new(1.0),
+          ..Default::default()
+        }
+      }
+    }
+
+    impl NodeImpl for FresnelEffectNode {
+      fn compile(&self, graph: &NodeGraph, compile: &mut NodeGraphCompile, id: NodeId) -> Result<()> {
+        if MasterMode::from_graph(graph)? == Some(MasterMode::PostProcess) {
+          return Err(anyhow!("`Fresnel Effect` needs a mesh, it is not supported in post-process graphs"));
+        }
+        add_view_binding_import(graph, compile, "Fresnel Effect", "view")?;
+        let normal = if self.normal.is_connected() {
+          self.normal.compile(graph, compile)?.to_string()
+        } else {
+          "in.world_normal".to_string()
+        };
+        let view_dir = if self.view_dir.is_connected() {
+          self.view_dir.compile(graph, compile)?.to_string()
+        } else {
+          "view.world_position - in.world_position.xyz".to_string()
+        };
+        let power = self.power.compile(graph, compile)?;
+        output(
+          compile,
+          id,
+          &self.out,
+          format!("pow(1.0 - saturate(dot(normalize({normal}), normalize({view_dir}))), {power})"),
+        )
+      }
+    }
+  }
+}
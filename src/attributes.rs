@@ -0,0 +1,167 @@
+use bevy::{
+    mesh::{MeshVertexAttribute, MeshVertexBufferLayoutRef},
+    prelude::*,
+    render::render_resource::SpecializedMeshPipelineError,
+};
+
+use node_engine::NodeGraph;
+
+use crate::document::graph_to_value;
+use crate::nodes::doc_vertex_attributes;
+
+/// A mesh vertex attribute that graph nodes can read.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum GraphAttribute {
+    Uv0,
+    Uv1,
+    Tangent,
+}
+
+impl GraphAttribute {
+    pub const ALL: [Self; 3] = [Self::Uv0, Self::Uv1, Self::Tangent];
+
+    fn bit(&self) -> u8 {
+        1 << (*self as u8)
+    }
+
+    /// Name used by `doc_vertex_attributes`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Uv0 => "uv0",
+            Self::Uv1 => "uv1",
+            Self::Tangent => "tangent",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|attr| attr.name() == name)
+    }
+
+    pub fn mesh_attribute(&self) -> MeshVertexAttribute {
+        match self {
+            Self::Uv0 => Mesh::ATTRIBUTE_UV_0,
+            Self::Uv1 => Mesh::ATTRIBUTE_UV_1,
+            Self::Tangent => Mesh::ATTRIBUTE_TANGENT,
+        }
+    }
+
+    /// Shader location used by Bevy's mesh pipeline.
+    fn shader_location(&self) -> u32 {
+        match self {
+            Self::Uv0 => 2,
+            Self::Uv1 => 3,
+            Self::Tangent => 4,
+        }
+    }
+}
+
+/// Set of mesh vertex attributes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct GraphAttributes(u8);
+
+impl GraphAttributes {
+    pub fn insert(&mut self, attr: GraphAttribute) {
+        self.0 |= attr.bit();
+    }
+
+    pub fn remove(&mut self, attr: GraphAttribute) {
+        self.0 &= !attr.bit();
+    }
+
+    pub fn contains(&self, attr: GraphAttribute) -> bool {
+        self.0 & attr.bit() != 0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = GraphAttribute> + '_ {
+        GraphAttribute::ALL
+            .into_iter()
+            .filter(|attr| self.contains(*attr))
+    }
+}
+
+/// Mesh vertex attributes used by a graph.
+///
+/// The nodes declare the attributes they read (see `doc_vertex_attributes`), the
+/// mesh must have all of them.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct GraphAttributeRequirements {
+    pub required: GraphAttributes,
+}
+
+impl GraphAttributeRequirements {
+    /// The attributes read by the nodes of a graph.
+    pub fn from_graph(graph: &NodeGraph) -> anyhow::Result<Self> {
+        let doc = graph_to_value(graph)?;
+        Ok(Self::from_names(doc_vertex_attributes(&doc)))
+    }
+
+    /// Required attributes from their names, unknown names are ignored.
+    pub fn from_names<'a>(names: impl IntoIterator<Item = &'a str>) -> Self {
+        let mut reqs = Self::default();
+        for attr in names.into_iter().filter_map(GraphAttribute::from_name) {
+            reqs.required.insert(attr);
+        }
+        reqs
+    }
+
+    /// Check the mesh layout for the required attributes.
+    pub fn specialize(
+        &self,
+        layout: &MeshVertexBufferLayoutRef,
+    ) -> Result<(), SpecializedMeshPipelineError> {
+        let attributes: Vec<_> = self
+            .required
+            .iter()
+            .map(|attr| {
+                attr.mesh_attribute()
+                    .at_shader_location(attr.shader_location())
+            })
+            .collect();
+        layout.0.get_layout(&attributes)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{
+        asset::RenderAssetUsages,
+        mesh::{MeshVertexBufferLayouts, PrimitiveTopology},
+    };
+
+    use super::*;
+
+    fn layout(mesh: &Mesh) -> MeshVertexBufferLayoutRef {
+        mesh.get_mesh_vertex_buffer_layout(&mut MeshVertexBufferLayouts::default())
+    }
+
+    #[test]
+    fn requirements_from_names() {
+        let reqs = GraphAttributeRequirements::from_names(["uv0", "tangent", "color"]);
+        assert_eq!(
+            reqs.required.iter().collect::<Vec<_>>(),
+            [GraphAttribute::Uv0, GraphAttribute::Tangent]
+        );
+    }
+
+    #[test]
+    fn missing_attribute_error() {
+        let mut mesh = Mesh::new(
+            PrimitiveTopology::TriangleList,
+            RenderAssetUsages::default(),
+        );
+        mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, vec![[0.0f32; 3]; 3]);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, vec![[0.0f32; 2]; 3]);
+
+        let reqs = GraphAttributeRequirements::from_names(["uv0"]);
+        assert!(reqs.specialize(&layout(&mesh)).is_ok());
+
+        let reqs = GraphAttributeRequirements::from_names(["uv0", "tangent"]);
+        let err = reqs.specialize(&layout(&mesh)).unwrap_err();
+        assert!(err.to_string().contains("Vertex_Tangent"), "{err}");
+    }
+}
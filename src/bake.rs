@@ -0,0 +1,277 @@
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use bevy::asset::RenderAssetUsages;
+use bevy::image::Image;
+use bevy::math::{UVec2, Vec2, Vec4};
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+
+use node_engine::NodeGraph;
+
+use crate::eval::{EvalInputs, EvalSample, GraphEvaluator};
+
+/// File format of a baked texture.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BakeFormat {
+    /// 8-bit sRGB.
+    #[default]
+    Png,
+    /// 32-bit float linear.
+    Exr,
+}
+
+impl BakeFormat {
+    /// Pick the format from the file extension.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self> {
+        match path.as_ref().extension().and_then(|ext| ext.to_str()) {
+            Some("png") => Ok(Self::Png),
+            Some("exr") => Ok(Self::Exr),
+            ext => Err(anyhow!(
+                "Unsupported bake format: {ext:?}, use .png or .exr"
+            )),
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Png => "png",
+            Self::Exr => "exr",
+        }
+    }
+}
+
+/// Largest bake width or height, the CPU evaluation is slow and a bigger
+/// image would take gigabytes.
+pub const MAX_BAKE_SIZE: u32 = 8192;
+
+/// What to bake.
+#[derive(Clone, Debug)]
+pub struct BakeSettings {
+    pub size: UVec2,
+    /// Bake output 0 of this node instead of the graph output.
+    pub node: Option<String>,
+    /// The output node input to bake.
+    pub output: String,
+    /// Format of the written file.
+    pub format: BakeFormat,
+}
+
+impl Default for BakeSettings {
+    fn default() -> Self {
+        Self {
+            size: UVec2::splat(512),
+            node: None,
+            output: "color".to_string(),
+            format: BakeFormat::Png,
+        }
+    }
+}
+
+/// Evaluate a graph over a UV grid, returns a linear `Rgba32Float` image.
+///
+/// Samples are taken at the texel centers, with UV `(0, 0)` at the top left.
+pub fn bake_graph(
+    graph: &NodeGraph,
+    settings: &BakeSettings,
+    inputs: &EvalInputs,
+) -> Result<Image> {
+    let eval = GraphEvaluator::new(graph)?;
+    let UVec2 {
+        x: width,
+        y: height,
+    } = settings.size;
+    if width == 0 || height == 0 {
+        return Err(anyhow!("Bake size must not be zero"));
+    }
+    if width > MAX_BAKE_SIZE || height > MAX_BAKE_SIZE {
+        return Err(anyhow!(
+            "Bake size {width}x{height} is too large, the limit is {MAX_BAKE_SIZE}"
+        ));
+    }
+    let mut data = Vec::with_capacity(width as usize * height as usize * 16);
+    for y in 0..height {
+        for x in 0..width {
+            let uv = (Vec2::new(x as f32, y as f32) + 0.5) / settings.size.as_vec2();
+            let sample = EvalSample::from_uv(uv);
+            let color = match &settings.node {
+                Some(node) => eval.evaluate_node(node, 0, &sample, inputs)?.to_vec4(),
+                None => eval
+                    .evaluate(&sample, inputs)?
+                    .get(&settings.output)
+                    .ok_or_else(|| anyhow!("The graph has no output `{}`", settings.output))?,
+            };
+            for v in color.to_array() {
+                data.extend_from_slice(&v.to_le_bytes());
+            }
+        }
+    }
+    Ok(Image::new(
+        Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba32Float,
+        RenderAssetUsages::default(),
+    ))
+}
+
+/// Write a baked image as PNG (sRGB) or EXR (linear float).
+pub fn save_baked_image<P: AsRef<Path>>(baked: &Image, path: P, format: BakeFormat) -> Result<()> {
+    let path = path.as_ref();
+    let size = baked.size();
+    let data = baked
+        .data
+        .as_ref()
+        .ok_or_else(|| anyhow!("The baked image has no data"))?;
+    let texels = data.chunks_exact(16).map(|texel| {
+        let f = |idx: usize| {
+            let bytes = [
+                texel[idx * 4],
+                texel[idx * 4 + 1],
+                texel[idx * 4 + 2],
+                texel[idx * 4 + 3],
+            ];
+            f32::from_le_bytes(bytes)
+        };
+        Vec4::new(f(0), f(1), f(2), f(3))
+    });
+    match format {
+        BakeFormat::Png => {
+            let pixels = texels
+                .flat_map(|c| {
+                    let c = c.clamp(Vec4::ZERO, Vec4::ONE);
+                    [
+                        linear_to_srgb(c.x),
+                        linear_to_srgb(c.y),
+                        linear_to_srgb(c.z),
+                        (c.w * 255.0).round() as u8,
+                    ]
+                })
+                .collect();
+            image::RgbaImage::from_raw(size.x, size.y, pixels)
+                .ok_or_else(|| anyhow!("Invalid baked image size"))?
+                .save_with_format(path, image::ImageFormat::Png)?;
+        }
+        BakeFormat::Exr => {
+            let pixels = texels.flat_map(|c| c.to_array()).collect();
+            image::Rgba32FImage::from_raw(size.x, size.y, pixels)
+                .ok_or_else(|| anyhow!("Invalid baked image size"))?
+                .save_with_format(path, image::ImageFormat::OpenExr)?;
+        }
+    }
+    Ok(())
+}
+
+/// Bake a graph and write it to `path`.
+pub fn bake_graph_to_file<P: AsRef<Path>>(
+    graph: &NodeGraph,
+    settings: &BakeSettings,
+    inputs: &EvalInputs,
+    path: P,
+) -> Result<Image> {
+    let image = bake_graph(graph, settings, inputs)?;
+    save_baked_image(&image, path, settings.format)?;
+    Ok(image)
+}
+
+fn linear_to_srgb(v: f32) -> u8 {
+    let srgb = if v <= 0.003_130_8 {
+        v * 12.92
+    } else {
+        1.055 * v.powf(1.0 / 2.4) - 0.055
+    };
+    (srgb * 255.0).round() as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::import::GraphBuilder;
+
+    fn uv_graph() -> (NodeGraph, String) {
+        let mut builder = GraphBuilder::default();
+        let output = builder.add_output("Unlit Fragment", Vec2::ZERO).unwrap();
+        let uv = builder.add_node("Uv", Vec2::ZERO).unwrap();
+        let combine = builder.add_node("Combine", Vec2::ZERO).unwrap();
+        builder.connect(&uv, 0, &combine, "r").unwrap();
+        builder.set_input(&combine, "g", &[0.5]);
+        builder.set_input(&combine, "b", &[1.0]);
+        builder.set_input(&combine, "a", &[1.0]);
+        builder.connect(&combine, 0, &output, "color").unwrap();
+        let (graph, _) = builder.finish().unwrap();
+        (graph, uv)
+    }
+
+    fn texel(image: &Image, x: u32, y: u32) -> Vec4 {
+        let color = image.get_color_at(x, y).unwrap().to_linear();
+        Vec4::new(color.red, color.green, color.blue, color.alpha)
+    }
+
+    #[test]
+    fn bake_output() {
+        let (graph, _) = uv_graph();
+        let settings = BakeSettings {
+            size: UVec2::new(2, 1),
+            ..Default::default()
+        };
+        let image = bake_graph(&graph, &settings, &EvalInputs::default()).unwrap();
+        assert_eq!(image.size(), UVec2::new(2, 1));
+        assert!(texel(&image, 0, 0).abs_diff_eq(Vec4::new(0.25, 0.5, 1.0, 1.0), 1e-5));
+        assert!(texel(&image, 1, 0).abs_diff_eq(Vec4::new(0.75, 0.5, 1.0, 1.0), 1e-5));
+
+        let settings = BakeSettings {
+            output: "missing".into(),
+            ..settings
+        };
+        assert!(bake_graph(&graph, &settings, &EvalInputs::default()).is_err());
+    }
+
+    #[test]
+    fn bake_node() {
+        let (graph, uv) = uv_graph();
+        let settings = BakeSettings {
+            size: UVec2::new(1, 2),
+            node: Some(uv),
+            ..Default::default()
+        };
+        let image = bake_graph(&graph, &settings, &EvalInputs::default()).unwrap();
+        // Missing components are zero.
+        assert!(texel(&image, 0, 0).abs_diff_eq(Vec4::new(0.5, 0.25, 0.0, 0.0), 1e-5));
+        assert!(texel(&image, 0, 1).abs_diff_eq(Vec4::new(0.5, 0.75, 0.0, 0.0), 1e-5));
+    }
+
+    #[test]
+    fn bake_size_limits() {
+        let (graph, _) = uv_graph();
+        for size in [UVec2::new(0, 4), UVec2::new(4, MAX_BAKE_SIZE + 1)] {
+            let settings = BakeSettings {
+                size,
+                ..Default::default()
+            };
+            assert!(bake_graph(&graph, &settings, &EvalInputs::default()).is_err());
+        }
+    }
+
+    #[test]
+    fn png_is_srgb() {
+        assert_eq!(linear_to_srgb(0.0), 0);
+        assert_eq!(linear_to_srgb(0.002), 7);
+        assert_eq!(linear_to_srgb(0.5), 188);
+        assert_eq!(linear_to_srgb(1.0), 255);
+
+        let (graph, _) = uv_graph();
+        let settings = BakeSettings {
+            size: UVec2::new(2, 1),
+            ..Default::default()
+        };
+        let path = std::env::temp_dir().join(format!("bevy_shader_graph_bake_{}.png", std::process::id()));
+        bake_graph_to_file(&graph, &settings, &EvalInputs::default(), &path).unwrap();
+        let png = image::open(&path).unwrap().to_rgba8();
+        std::fs::remove_file(&path).unwrap();
+        // Linear 0.25, 0.5 and 1.0, alpha isn't converted.
+        assert_eq!(png.get_pixel(0, 0).0, [137, 188, 255, 255]);
+    }
+}
@@ -0,0 +1,240 @@
+//! Command line tools for shader graph files.
+
+use anyhow::{anyhow, Result};
+
+use bevy_shader_graph::*;
+
+const USAGE: &str = "Usage:
+  shader_graph convert <input> <output>    Convert between .json, .ron and .sgb graph files
+  shader_graph import-unity <input> <output>  Import a Unity .shadergraph file
+  shader_graph import-godot <input> <output>  Import a Godot VisualShader .tres resource
+  shader_graph import-blender <input> <output>  Import a JSON dump of a Blender material node tree
+  shader_graph compile [--no-optimize] <graph> [<output>]
+                                            Compile a graph to WGSL and report lint warnings
+  shader_graph lint <graph>                 Report problems in a graph
+  shader_graph cost [--desktop] <graph>     Estimate the shader cost, fails if over budget
+  shader_graph bake [--size <w>x<h>] [--node <id>] <graph> <output.png|exr>
+                                            Evaluate a graph on the CPU and write a texture
+  shader_graph diff [--layout] <old> <new>   Show the semantic changes between two graphs
+  shader_graph merge <base> <ours> <theirs> [<path> [<output>]]
+                                            Three-way merge (git merge driver), writes to <ours> by default.
+                                            The file format is detected from <path> (git's %P)";
+
+fn convert(args: &[String]) -> Result<()> {
+    let [input, output] = args else {
+        return Err(anyhow!("convert: expected <input> <output>"));
+    };
+    let report = convert_graph(input, output)?;
+    for change in &report.changes {
+        eprintln!("{change}");
+    }
+    Ok(())
+}
+
+fn import(
+    cmd: &str,
+    args: &[String],
+    importer: fn(&str) -> Result<(node_engine::NodeGraph, import::ImportReport)>,
+) -> Result<()> {
+    let [input, output] = args else {
+        return Err(anyhow!("{cmd}: expected <input> <output>"));
+    };
+    let (graph, report) = importer(input)?;
+    save_graph(output, &graph)?;
+    eprint!("{report}");
+    Ok(())
+}
+
+fn read_migrated(path: &str) -> Result<serde_json::Value> {
+    read_migrated_as(path, GraphFormat::from_path(path))
+}
+
+/// Read a graph in the given format, git passes temporary files without an extension.
+fn read_migrated_as(path: &str, format: GraphFormat) -> Result<serde_json::Value> {
+    let mut value = format.parse(&std::fs::read(path)?)?;
+    GraphMigrations::default().migrate(&mut value)?;
+    Ok(value)
+}
+
+fn load_migrated(path: &str) -> Result<node_engine::NodeGraph> {
+    let (graph, report) = load_graph(path)?;
+    if report.migrated() {
+        eprintln!(
+            "{path}: upgraded from format v{} to v{}",
+            report.from_version, report.to_version
+        );
+    }
+    Ok(graph)
+}
+
+/// Print lints, returns `true` if there are warnings.
+fn print_lints(path: &str, graph: &node_engine::NodeGraph) -> Result<bool> {
+    let lints = lint_graph(graph)?;
+    for lint in &lints {
+        eprintln!("{path}: {lint}");
+    }
+    Ok(lints.iter().any(|lint| lint.level == LintLevel::Warning))
+}
+
+fn compile(args: &[String]) -> Result<()> {
+    let (optimize, args) = match args.split_first() {
+        Some((flag, rest)) if flag == "--no-optimize" => (false, rest),
+        _ => (true, args),
+    };
+    let (input, output) = match args {
+        [input] => (input, None),
+        [input, output] => (input, Some(output)),
+        _ => {
+            return Err(anyhow!(
+                "compile: expected [--no-optimize] <graph> [<output>]"
+            ))
+        }
+    };
+    let graph = load_migrated(input)?;
+    print_lints(input, &graph)?;
+    let compiled = if optimize {
+        let (compiled, report) = compile_graph_optimized(&graph, &OptimizeOptions::default());
+        eprintln!("{input}: optimized, {report}");
+        compiled
+    } else {
+        compile_graph(&graph)
+    };
+    let code = compiled.into_result()?;
+    match output {
+        Some(output) => std::fs::write(output, code)?,
+        None => print!("{code}"),
+    }
+    Ok(())
+}
+
+fn lint(args: &[String]) -> Result<()> {
+    let [input] = args else {
+        return Err(anyhow!("lint: expected <graph>"));
+    };
+    let graph = load_migrated(input)?;
+    if print_lints(input, &graph)? {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+fn cost(args: &[String]) -> Result<()> {
+    let (budget, args) = match args.split_first() {
+        Some((flag, rest)) if flag == "--desktop" => (CostBudget::desktop(), rest),
+        _ => (CostBudget::mobile(), args),
+    };
+    let [input] = args else {
+        return Err(anyhow!("cost: expected [--desktop] <graph>"));
+    };
+    let estimate = estimate_cost(&load_migrated(input)?)?;
+    print!("{estimate}");
+    let over_budget = estimate.over_budget(&budget);
+    for over in &over_budget {
+        eprintln!("{input}: over budget: {over}");
+    }
+    if !over_budget.is_empty() {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+fn bake(mut args: &[String]) -> Result<()> {
+    let mut settings = BakeSettings::default();
+    while let [flag, value, rest @ ..] = args {
+        match flag.as_str() {
+            "--size" => {
+                let (w, h) = value
+                    .split_once('x')
+                    .ok_or_else(|| anyhow!("bake: invalid size `{value}`, expected <w>x<h>"))?;
+                settings.size = bevy::math::UVec2::new(w.parse()?, h.parse()?);
+            }
+            "--node" => settings.node = Some(value.clone()),
+            _ => break,
+        }
+        args = rest;
+    }
+    let [input, output] = args else {
+        return Err(anyhow!(
+            "bake: expected [--size <w>x<h>] [--node <id>] <graph> <output>"
+        ));
+    };
+    settings.format = BakeFormat::from_path(output)?;
+    let graph = load_migrated(input)?;
+    bake_graph_to_file(&graph, &settings, &EvalInputs::default(), output)?;
+    Ok(())
+}
+
+fn diff(args: &[String]) -> Result<()> {
+    let (layout, args) = match args.split_first() {
+        Some((flag, rest)) if flag == "--layout" => (true, rest),
+        _ => (false, args),
+    };
+    let [old, new] = args else {
+        return Err(anyhow!("diff: expected <old> <new>"));
+    };
+    let mut diff = diff_graphs(&read_migrated(old)?, &read_migrated(new)?);
+    if !layout {
+        diff.changes.retain(|change| !change.is_layout());
+    }
+    print!("{diff}");
+    Ok(())
+}
+
+fn merge(args: &[String]) -> Result<()> {
+    let (base, ours, theirs, path, output) = match args {
+        [base, ours, theirs] => (base, ours, theirs, ours, ours),
+        [base, ours, theirs, path] => (base, ours, theirs, path, ours),
+        [base, ours, theirs, path, output] => (base, ours, theirs, path, output),
+        _ => {
+            return Err(anyhow!(
+                "merge: expected <base> <ours> <theirs> [<path> [<output>]]"
+            ))
+        }
+    };
+    let format = GraphFormat::from_path(path);
+    let merge = merge_graphs(
+        &read_migrated_as(base, format)?,
+        &read_migrated_as(ours, format)?,
+        &read_migrated_as(theirs, format)?,
+    );
+    let mut merged = merge.merged.clone();
+    set_format_version(&mut merged);
+    // Keep the view state of graphs without a sidecar, there is no graph path to put it next to.
+    let options = SaveOptions {
+        view_sidecar: false,
+        ..Default::default()
+    };
+    canonicalize_graph(&mut merged, &options);
+    std::fs::write(output, format.serialize(&merged)?)?;
+    if merge.has_conflicts() {
+        for conflict in &merge.conflicts {
+            eprintln!("Conflict (kept ours): {conflict}");
+        }
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+fn main() -> Result<()> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let Some((cmd, args)) = args.split_first() else {
+        eprintln!("{USAGE}");
+        return Ok(());
+    };
+    match cmd.as_str() {
+        "convert" => convert(args),
+        "import-unity" => import(cmd, args, |path| import::import_unity(path)),
+        "import-godot" => import(cmd, args, |path| import::import_godot(path)),
+        "import-blender" => import(cmd, args, |path| import::import_blender(path)),
+        "compile" => compile(args),
+        "lint" => lint(args),
+        "cost" => cost(args),
+        "bake" => bake(args),
+        "diff" => diff(args),
+        "merge" => merge(args),
+        _ => {
+            eprintln!("{USAGE}");
+            Err(anyhow!("Unknown command: {cmd}"))
+        }
+    }
+}
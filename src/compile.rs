@@ -0,0 +1,74 @@
+use anyhow::Result;
+
+use node_engine::{NodeGraph, NodeGraphCompile};
+
+use crate::attributes::GraphAttributeRequirements;
+use crate::nodes::MasterMode;
+use crate::optimize::{optimize_graph, OptimizeOptions, OptimizeReport};
+
+/// Compiled WGSL for a shader graph.
+#[derive(Clone, Debug, Default)]
+pub struct CompiledGraph {
+    pub code: String,
+    pub error: Option<String>,
+    /// Mesh vertex attributes read by the graph's nodes.
+    pub attributes: GraphAttributeRequirements,
+    /// Mode of the graph's output node, `None` if it isn't a master node.
+    pub mode: Option<MasterMode>,
+}
+
+/// Compile a shader graph into WGSL.
+///
+/// The code is always returned, even when compiling failed, so that
+/// the editor can still show the partial output.
+pub fn compile_graph(graph: &NodeGraph) -> CompiledGraph {
+    let mut compiler = NodeGraphCompile::new();
+    compiler.define_block("imports");
+    compiler.define_block("bindings");
+
+    let error = compiler
+        .compile_graph(graph)
+        .err()
+        .map(|err| format!("{err:?}"));
+
+    let attributes = GraphAttributeRequirements::from_graph(graph).unwrap_or_else(|err| {
+        log::warn!("Failed to find the mesh attributes of the shader graph: {err:?}");
+        Default::default()
+    });
+    let mode = MasterMode::from_graph(graph).unwrap_or_else(|err| {
+        log::warn!("Failed to find the mode of the shader graph: {err:?}");
+        None
+    });
+    CompiledGraph {
+        code: compiler.dump(),
+        error,
+        attributes,
+        mode,
+    }
+}
+
+/// Optimize a shader graph and compile it into WGSL.
+///
+/// Falls back to the unoptimized graph if the optimizer fails.
+pub fn compile_graph_optimized(
+    graph: &NodeGraph,
+    options: &OptimizeOptions,
+) -> (CompiledGraph, OptimizeReport) {
+    match optimize_graph(graph, options) {
+        Ok((optimized, report)) => (compile_graph(&optimized), report),
+        Err(err) => {
+            log::warn!("Failed to optimize shader graph: {err:?}");
+            (compile_graph(graph), OptimizeReport::default())
+        }
+    }
+}
+
+impl CompiledGraph {
+    /// Convert to a `Result`, failing if the graph didn't compile.
+    pub fn into_result(self) -> Result<String> {
+        match self.error {
+            Some(err) => Err(anyhow::anyhow!("Failed to compile shader graph: {err}")),
+            None => Ok(self.code),
+        }
+    }
+}
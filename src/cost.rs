@@ -0,0 +1,432 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::ops::AddAssign;
+
+use anyhow::Result;
+use serde_json::Value;
+
+use node_engine::NodeGraph;
+
+use crate::document::{
+    doc_nodes, graph_to_value, input_source, is_input, node_id, node_kind, NodeTemplates,
+};
+
+/// Rough ALU weight per node type, unknown nodes weigh one op.
+///
+/// These are hand-picked, not measured from the generated WGSL: the compiled
+/// code imports Bevy's shader modules, so it can't be parsed on its own.
+const NODE_WEIGHTS: &[(&str, u32)] = &[
+    ("Add", 1),
+    ("Subtract", 1),
+    ("Multiply", 1),
+    ("Divide", 4),
+    ("Min", 1),
+    ("Max", 1),
+    ("Absolute", 1),
+    ("Fraction", 2),
+    ("Clamp", 2),
+    ("Lerp", 3),
+    ("Split", 0),
+    ("Combine", 0),
+    ("Swizzle", 0),
+    ("Uv", 0),
+    ("View Direction", 6),
+    ("Tiling And Offset", 2),
+    ("Texture Sample", 1),
+    ("Fresnel Effect", 8),
+    ("Fragment", 0),
+    ("Pbr Fragment", 0),
+    ("Unlit Fragment", 0),
+    ("Custom Lighting Fragment", 0),
+    ("Main Light", 4),
+    ("Directional Lights", 8),
+    ("Ambient Light", 0),
+    ("2d Fragment", 0),
+    ("Sprite Uv", 0),
+    ("World Position 2d", 0),
+    ("Ui Fragment", 0),
+    ("Ui Node Uv", 0),
+    ("Ui Node Size", 0),
+    ("Ui Border Radius", 0),
+    ("Post Process", 0),
+    ("Screen Uv", 0),
+    ("Screen Color", 1),
+    ("Scene Depth", 1),
+    ("Scene Normal", 4),
+    ("Decal Fragment", 0),
+    ("Placeholder", 0),
+    ("Decal Uv", 0),
+    ("Decal Angle Fade", 12),
+    ("Time", 1),
+    ("Camera", 8),
+    ("View Matrices", 0),
+    ("Screen Position", 2),
+    ("Object Transform", 0),
+    ("Instance", 0),
+    ("Local Position", 12),
+];
+
+/// Nodes that sample a texture.
+const SAMPLE_NODES: &[&str] = &[
+    "Texture Sample",
+    "Screen Color",
+    "Scene Depth",
+    "Scene Normal",
+];
+
+/// Estimated cost of (a part of) a shader.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ShaderCost {
+    /// Weighted node ops (see `NODE_WEIGHTS`), not an instruction count.
+    pub alu: u32,
+    pub texture_samples: u32,
+    /// Texture samples with coordinates that depend on another sample.
+    pub dependent_reads: u32,
+}
+
+impl AddAssign for ShaderCost {
+    fn add_assign(&mut self, other: Self) {
+        self.alu += other.alu;
+        self.texture_samples += other.texture_samples;
+        self.dependent_reads += other.dependent_reads;
+    }
+}
+
+impl fmt::Display for ShaderCost {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "~{} ALU, {} samples ({} dependent)",
+            self.alu, self.texture_samples, self.dependent_reads
+        )
+    }
+}
+
+/// Cost of the nodes in one node group.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GroupCost {
+    /// Group title, `None` for nodes outside of a group.
+    pub title: Option<String>,
+    pub nodes: usize,
+    pub cost: ShaderCost,
+}
+
+/// Limits for a target platform.
+#[derive(Clone, Debug)]
+pub struct CostBudget {
+    pub max_alu: u32,
+    pub max_texture_samples: u32,
+    pub max_dependent_reads: u32,
+    /// Max. values alive at the same time (each counted as a `vec4`).
+    pub max_live_values: u32,
+}
+
+impl CostBudget {
+    /// A conservative budget for mobile GPUs.
+    pub fn mobile() -> Self {
+        Self {
+            max_alu: 64,
+            max_texture_samples: 4,
+            max_dependent_reads: 0,
+            max_live_values: 8,
+        }
+    }
+
+    /// A budget for desktop GPUs.
+    pub fn desktop() -> Self {
+        Self {
+            max_alu: 512,
+            max_texture_samples: 16,
+            max_dependent_reads: 4,
+            max_live_values: 32,
+        }
+    }
+}
+
+impl Default for CostBudget {
+    fn default() -> Self {
+        Self::mobile()
+    }
+}
+
+/// Estimated cost of a shader graph.
+///
+/// Texture samples, dependent reads and live values are counted from
+/// the nodes that reach the output.  The ALU number is a per-node weight, so it is
+/// only meant for comparing graphs and spotting expensive parts.  It doesn't
+/// include the lighting done by the output node or the code Bevy adds.
+#[derive(Clone, Debug, Default)]
+pub struct CostEstimate {
+    pub total: ShaderCost,
+    /// Cost per node group, ungrouped nodes first.
+    pub groups: Vec<GroupCost>,
+    /// Max. node outputs alive at the same time, a hint for register pressure.
+    pub live_values: u32,
+}
+
+impl CostEstimate {
+    /// Describe where the estimate exceeds the budget.
+    pub fn over_budget(&self, budget: &CostBudget) -> Vec<String> {
+        let mut over = Vec::new();
+        let mut check = |name: &str, value: u32, max: u32| {
+            if value > max {
+                over.push(format!("{name}: {value} (budget {max})"));
+            }
+        };
+        check("ALU ops", self.total.alu, budget.max_alu);
+        check(
+            "Texture samples",
+            self.total.texture_samples,
+            budget.max_texture_samples,
+        );
+        check(
+            "Dependent texture reads",
+            self.total.dependent_reads,
+            budget.max_dependent_reads,
+        );
+        check(
+            "Live values (register pressure)",
+            self.live_values,
+            budget.max_live_values,
+        );
+        over
+    }
+}
+
+impl fmt::Display for CostEstimate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Total: {}, {} live values", self.total, self.live_values)?;
+        for group in &self.groups {
+            let title = group.title.as_deref().unwrap_or("Ungrouped");
+            writeln!(f, "  {title} ({} nodes): {}", group.nodes, group.cost)?;
+        }
+        Ok(())
+    }
+}
+
+/// Estimate the cost of a shader graph.
+pub fn estimate_cost(graph: &NodeGraph) -> Result<CostEstimate> {
+    estimate_cost_with(graph, &mut NodeTemplates::default())
+}
+
+/// Estimate the cost of a shader graph, reusing the node templates (e.g. the editor's).
+pub fn estimate_cost_with(
+    graph: &NodeGraph,
+    templates: &mut NodeTemplates,
+) -> Result<CostEstimate> {
+    let doc = graph_to_value(graph)?;
+    Ok(estimate_document_cost(&doc, templates))
+}
+
+/// Estimate the cost of a graph document.
+///
+/// Only nodes contributing to the output are counted.
+pub fn estimate_document_cost(doc: &Value, templates: &mut NodeTemplates) -> CostEstimate {
+    let names: Vec<&str> = NODE_WEIGHTS.iter().map(|(name, _)| *name).collect();
+    templates.preload(&names);
+
+    let nodes: HashMap<&str, &Value> = doc_nodes(doc).map(|n| (node_id(n), n)).collect();
+    let Some(output) = doc.get("output").and_then(Value::as_str) else {
+        return CostEstimate::default();
+    };
+
+    // Nodes in dependency order (inputs first).
+    let mut order = Vec::new();
+    let mut visited = HashSet::new();
+    schedule(output, &nodes, &mut visited, &mut order);
+
+    // Nodes depending on a texture sample.
+    let mut sampled: HashSet<&str> = HashSet::new();
+    let mut groups: BTreeMap<Option<&str>, GroupCost> = BTreeMap::new();
+    let mut total = ShaderCost::default();
+    for &id in &order {
+        let node = nodes[id];
+        let kind = node_kind(templates, node);
+        let sources: Vec<&str> = sources(node).collect();
+        let depends_on_sample = sources.iter().any(|src| sampled.contains(src));
+
+        let mut cost = ShaderCost {
+            alu: NODE_WEIGHTS
+                .iter()
+                .find(|(name, _)| *name == kind)
+                .map_or(1, |(_, alu)| *alu),
+            ..Default::default()
+        };
+        if SAMPLE_NODES.contains(&kind) {
+            cost.texture_samples = 1;
+            if depends_on_sample {
+                cost.dependent_reads = 1;
+            }
+        }
+        if depends_on_sample || SAMPLE_NODES.contains(&kind) {
+            sampled.insert(id);
+        }
+
+        total += cost;
+        let group_id = node
+            .get("group_id")
+            .and_then(Value::as_str)
+            .filter(|id| !id.chars().all(|c| c == '0' || c == '-'));
+        let group = groups.entry(group_id).or_insert_with(|| GroupCost {
+            title: group_id.map(|id| group_title(doc, id)),
+            nodes: 0,
+            cost: ShaderCost::default(),
+        });
+        group.nodes += 1;
+        group.cost += cost;
+    }
+
+    CostEstimate {
+        total,
+        groups: groups.into_values().collect(),
+        live_values: live_values(&order, &nodes),
+    }
+}
+
+/// The nodes connected to a node's inputs.
+fn sources(node: &Value) -> impl Iterator<Item = &str> {
+    node["node"]
+        .as_object()
+        .into_iter()
+        .flatten()
+        .filter(|(_, field)| is_input(field))
+        .filter_map(|(_, field)| input_source(field).map(|(src, _)| src))
+}
+
+/// Depth-first post order from the output.
+fn schedule<'a>(
+    id: &'a str,
+    nodes: &HashMap<&'a str, &'a Value>,
+    visited: &mut HashSet<&'a str>,
+    order: &mut Vec<&'a str>,
+) {
+    let Some((id, node)) = nodes.get_key_value(id) else {
+        return;
+    };
+    if !visited.insert(id) {
+        return;
+    }
+    for src in sources(node) {
+        schedule(src, nodes, visited, order);
+    }
+    order.push(id);
+}
+
+/// Max. number of node outputs that are computed but still needed by a later node.
+fn live_values(order: &[&str], nodes: &HashMap<&str, &Value>) -> u32 {
+    let position: HashMap<&str, usize> = order.iter().enumerate().map(|(i, id)| (*id, i)).collect();
+    // Position of the last consumer of each node.
+    let mut last_use: HashMap<&str, usize> = HashMap::new();
+    for (idx, &id) in order.iter().enumerate() {
+        for src in sources(nodes[id]) {
+            last_use.insert(src, idx);
+        }
+    }
+    let mut max = 0;
+    for idx in 0..order.len() {
+        let live = last_use
+            .iter()
+            .filter(|(src, last)| position.get(*src).is_some_and(|p| *p <= idx) && **last > idx)
+            .count();
+        max = max.max(live);
+    }
+    max as u32
+}
+
+fn group_title(doc: &Value, id: &str) -> String {
+    doc.get("groups")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .find(|group| node_id(group) == id)
+        .and_then(|group| group.get("title"))
+        .and_then(Value::as_str)
+        .unwrap_or(id)
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::math::Vec2;
+    use serde_json::json;
+
+    use super::*;
+    use crate::document::doc_nodes_mut;
+    use crate::import::GraphBuilder;
+
+    fn estimate(doc: &Value) -> CostEstimate {
+        estimate_document_cost(doc, &mut NodeTemplates::default())
+    }
+
+    #[test]
+    fn weights_are_registry_nodes() {
+        let mut templates = NodeTemplates::default();
+        for (name, _) in NODE_WEIGHTS {
+            assert!(templates.get(name).is_some(), "no `{name}` node");
+        }
+    }
+
+    #[test]
+    fn texture_samples() {
+        let mut builder = GraphBuilder::default();
+        let output = builder.add_output("Pbr Fragment", Vec2::ZERO).unwrap();
+        let noise = builder.add_node("Texture Sample", Vec2::ZERO).unwrap();
+        let albedo = builder.add_node("Texture Sample", Vec2::ZERO).unwrap();
+        // Not connected to the output.
+        builder.add_node("Texture Sample", Vec2::ZERO).unwrap();
+        builder.connect(&noise, 0, &albedo, "uv").unwrap();
+        builder.connect(&albedo, 0, &output, "color").unwrap();
+
+        let cost = estimate(&builder.document());
+        assert_eq!(cost.total.texture_samples, 2);
+        assert_eq!(cost.total.dependent_reads, 1);
+        assert_eq!(cost.total.alu, 2);
+
+        let over = cost.over_budget(&CostBudget::mobile());
+        assert_eq!(over.len(), 1);
+        assert!(over[0].starts_with("Dependent texture reads: 1"));
+        assert!(cost.over_budget(&CostBudget::desktop()).is_empty());
+    }
+
+    #[test]
+    fn groups_and_live_values() {
+        let mut builder = GraphBuilder::default();
+        let output = builder.add_output("Pbr Fragment", Vec2::ZERO).unwrap();
+        let uv = builder.add_node("Uv", Vec2::ZERO).unwrap();
+        let add = builder.add_node("Add", Vec2::ZERO).unwrap();
+        let divide = builder.add_node("Divide", Vec2::ZERO).unwrap();
+        builder.connect(&uv, 0, &add, "a").unwrap();
+        builder.connect(&add, 0, &divide, "a").unwrap();
+        // `uv` is still needed after `add`.
+        builder.connect(&uv, 0, &divide, "b").unwrap();
+        builder.connect(&divide, 0, &output, "roughness").unwrap();
+
+        let mut doc = builder.document();
+        doc["groups"] = json!([{ "id": "detail", "title": "Detail" }]);
+        for node in doc_nodes_mut(&mut doc) {
+            if node_id(node) == divide {
+                node["group_id"] = "detail".into();
+            }
+        }
+
+        let cost = estimate(&doc);
+        assert_eq!(cost.total.alu, 5);
+        assert_eq!(cost.live_values, 2);
+        assert_eq!(cost.groups.len(), 2);
+        let ungrouped = &cost.groups[0];
+        assert_eq!(ungrouped.title, None);
+        assert_eq!((ungrouped.nodes, ungrouped.cost.alu), (3, 1));
+        let detail = &cost.groups[1];
+        assert_eq!(detail.title.as_deref(), Some("Detail"));
+        assert_eq!((detail.nodes, detail.cost.alu), (1, 4));
+    }
+
+    #[test]
+    fn no_output() {
+        let mut builder = GraphBuilder::default();
+        builder.add_node("Divide", Vec2::ZERO).unwrap();
+        let cost = estimate(&builder.document());
+        assert_eq!(cost.total, ShaderCost::default());
+        assert!(cost.groups.is_empty());
+    }
+}
@@ -0,0 +1,86 @@
+use crate::attributes::GraphAttributeRequirements;
+use crate::extended_material::{
+    ExtendedMaterial, MaterialExtension, MaterialExtensionKey, MaterialExtensionPipeline,
+};
+use crate::material::{ShaderGraphShadersPlugin, ShaderGraphTarget, StandardShaderGraphMaterial};
+use crate::nodes::MasterMode;
+use bevy::{
+    mesh::MeshVertexBufferLayoutRef,
+    pbr::decal::ForwardDecalMaterialExt,
+    prelude::*,
+    render::render_resource::{
+        CompareFunction, RenderPipelineDescriptor, SpecializedMeshPipelineError,
+    },
+};
+
+/// A shader graph projected by a `ForwardDecal`, the graph's output node is `Decal Fragment`.
+///
+/// ```ignore
+/// let decal = decals.add(ShaderGraphDecalMaterial {
+///     base: StandardShaderGraphMaterial::default(),
+///     extension: ForwardDecalMaterialExt {
+///         depth_fade_factor: 8.0,
+///     },
+/// });
+/// commands.spawn((ForwardDecal, MeshMaterial3d(decal), Transform::from_scale(Vec3::splat(2.0))));
+/// ```
+///
+/// Uses this crate's `ExtendedMaterial`, Bevy's `ForwardDecalMaterial` needs `Copy` bind group data.
+pub type ShaderGraphDecalMaterial =
+    ExtendedMaterial<StandardShaderGraphMaterial, ForwardDecalMaterialExt>;
+
+/// Same specialization as Bevy's `MaterialExtension` impl for `ForwardDecalMaterialExt`.
+impl MaterialExtension for ForwardDecalMaterialExt {
+    fn alpha_mode() -> Option<AlphaMode> {
+        Some(AlphaMode::Blend)
+    }
+
+    fn specialize(
+        _pipeline: &MaterialExtensionPipeline,
+        descriptor: &mut RenderPipelineDescriptor,
+        _layout: &MeshVertexBufferLayoutRef,
+        _key: MaterialExtensionKey<Self>,
+    ) -> Result<(), SpecializedMeshPipelineError> {
+        if let Some(depth_stencil) = &mut descriptor.depth_stencil {
+            depth_stencil.depth_compare = CompareFunction::Always;
+        }
+
+        descriptor.vertex.shader_defs.push("FORWARD_DECAL".into());
+        if let Some(fragment) = &mut descriptor.fragment {
+            fragment.shader_defs.push("FORWARD_DECAL".into());
+        }
+
+        if let Some(label) = &mut descriptor.label {
+            *label = format!("forward_decal_{label}").into();
+        }
+
+        Ok(())
+    }
+}
+
+impl ShaderGraphTarget for ShaderGraphDecalMaterial {
+    const MODES: &'static [MasterMode] = &[MasterMode::Decal];
+
+    fn fragment(&self) -> Option<&Handle<Shader>> {
+        self.base.extension.graph.fragment.as_ref()
+    }
+
+    fn set_fragment(&mut self, fragment: Handle<Shader>, attributes: GraphAttributeRequirements) {
+        self.base.extension.graph.fragment = Some(fragment);
+        self.base.extension.graph.attributes = attributes;
+    }
+}
+
+/// Registers `ShaderGraphDecalMaterial`.  Cameras need a `DepthPrepass` to show decals.
+#[derive(Default, Clone, Debug)]
+pub struct ShaderGraphDecalPlugin;
+
+impl Plugin for ShaderGraphDecalPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<ShaderGraphShadersPlugin>() {
+            app.add_plugins(ShaderGraphShadersPlugin);
+        }
+
+        app.add_plugins(MaterialPlugin::<ShaderGraphDecalMaterial>::default());
+    }
+}
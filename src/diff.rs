@@ -0,0 +1,645 @@
+use std::fmt;
+
+use serde_json::{json, Map, Value};
+
+use crate::document::is_input;
+
+/// What a flattened graph entry describes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GraphItem {
+    Node,
+    /// Constant value of a node input.
+    Value,
+    /// Node parameter (non-input field).
+    Param,
+    Connection,
+    Group,
+    Property,
+    Output,
+    /// Node/group position or the editor view state.
+    Layout,
+    Other,
+}
+
+impl GraphItem {
+    fn from_key(key: &str) -> Self {
+        let parts: Vec<&str> = key.split('/').collect();
+        match parts.as_slice() {
+            ["node", _] => Self::Node,
+            ["node", _, "field", _] => Self::Value,
+            ["node", _, "param", _] => Self::Param,
+            ["node", _, "area"] | ["group", _, "area"] | ["editor"] => Self::Layout,
+            ["conn", ..] => Self::Connection,
+            ["group", _] => Self::Group,
+            ["prop", _] => Self::Property,
+            ["output"] => Self::Output,
+            _ => Self::Other,
+        }
+    }
+}
+
+/// Flatten a graph document into keyed entries, so that changes to different
+/// parts of the graph can be compared and merged independently.
+///
+/// Keys:
+/// * `node/<id>`: node header (name, type, group).
+/// * `node/<id>/field/<input>`: constant value of an input.
+/// * `node/<id>/param/<name>`: node parameter.
+/// * `node/<id>/area`: node position.
+/// * `conn/<id>/<input>`: connection into an input, `{ "idx": <input index>, "connected": .. }`.
+/// * `group/<id>`, `group/<id>/area`: node groups.
+/// * `prop/<id>`: graph properties.
+/// * `output`, `editor` and `top/<key>` for the rest of the document.
+pub fn flatten_graph(doc: &Value) -> Map<String, Value> {
+    let mut flat = Map::new();
+    let Some(obj) = doc.as_object() else {
+        return flat;
+    };
+    for (key, value) in obj {
+        match key.as_str() {
+            "nodes" => {
+                for node in value.as_array().into_iter().flatten() {
+                    flatten_node(node, &mut flat);
+                }
+            }
+            "groups" => {
+                for group in value.as_array().into_iter().flatten() {
+                    let id = item_id(group);
+                    let mut header = group.clone();
+                    let area = header.as_object_mut().and_then(|g| g.remove("area"));
+                    flat.insert(format!("group/{id}"), header);
+                    if let Some(area) = area {
+                        flat.insert(format!("group/{id}/area"), area);
+                    }
+                }
+            }
+            "properties" => {
+                for (idx, prop) in value.as_array().into_iter().flatten().enumerate() {
+                    let id = prop
+                        .get("id")
+                        .or_else(|| prop.get("name"))
+                        .and_then(Value::as_str)
+                        .map(str::to_string)
+                        .unwrap_or_else(|| idx.to_string());
+                    flat.insert(format!("prop/{id}"), prop.clone());
+                }
+            }
+            // Rebuilt from the node inputs.
+            "connections" => {}
+            "output" | "editor" => {
+                flat.insert(key.clone(), value.clone());
+            }
+            _ => {
+                flat.insert(format!("top/{key}"), value.clone());
+            }
+        }
+    }
+    flat
+}
+
+fn flatten_node(node: &Value, flat: &mut Map<String, Value>) {
+    let id = item_id(node);
+    let mut header = node.clone();
+    let (fields, area) = match header.as_object_mut() {
+        Some(obj) => (obj.remove("node"), obj.remove("area")),
+        None => (None, None),
+    };
+    flat.insert(format!("node/{id}"), header);
+    let mut input_idx = 0;
+    for (name, field) in fields
+        .as_ref()
+        .and_then(Value::as_object)
+        .into_iter()
+        .flatten()
+    {
+        if is_input(field) {
+            flat.insert(format!("node/{id}/field/{name}"), field["value"].clone());
+            if !field["connected"].is_null() {
+                let conn = json!({ "idx": input_idx, "connected": field["connected"] });
+                flat.insert(format!("conn/{id}/{name}"), conn);
+            }
+            input_idx += 1;
+        } else {
+            flat.insert(format!("node/{id}/param/{name}"), field.clone());
+        }
+    }
+    if let Some(area) = area {
+        flat.insert(format!("node/{id}/area"), area);
+    }
+}
+
+/// Rebuild a graph document from flattened entries.
+pub fn unflatten_graph(flat: &Map<String, Value>) -> Value {
+    let mut doc = Map::new();
+    let mut nodes: Vec<Value> = Vec::new();
+    let mut groups: Vec<Value> = Vec::new();
+    let mut props: Vec<Value> = Vec::new();
+
+    let find = |items: &mut Vec<Value>, id: &str| {
+        items
+            .iter_mut()
+            .position(|item| item.get("id").and_then(Value::as_str) == Some(id))
+    };
+
+    for (key, value) in flat {
+        let parts: Vec<&str> = key.split('/').collect();
+        match parts.as_slice() {
+            ["node", _] => {
+                let mut node = value.clone();
+                node["node"] = json!({});
+                nodes.push(node);
+            }
+            ["node", id, kind, name] => {
+                let Some(idx) = find(&mut nodes, id) else {
+                    continue;
+                };
+                let field = if *kind == "field" {
+                    let connected = flat
+                        .get(&format!("conn/{id}/{name}"))
+                        .map(|conn| conn["connected"].clone())
+                        .unwrap_or(Value::Null);
+                    json!({ "value": value, "connected": connected })
+                } else {
+                    value.clone()
+                };
+                nodes[idx]["node"][*name] = field;
+            }
+            ["node", id, "area"] => {
+                if let Some(idx) = find(&mut nodes, id) {
+                    nodes[idx]["area"] = value.clone();
+                }
+            }
+            ["group", _] => groups.push(value.clone()),
+            ["group", id, "area"] => {
+                if let Some(idx) = find(&mut groups, id) {
+                    groups[idx]["area"] = value.clone();
+                }
+            }
+            ["prop", _] => props.push(value.clone()),
+            ["top", name] => {
+                doc.insert(name.to_string(), value.clone());
+            }
+            ["output"] | ["editor"] => {
+                doc.insert(key.clone(), value.clone());
+            }
+            _ => {}
+        }
+    }
+
+    // Rebuild the connection list, the field order of a merged node can differ
+    // from the input order.
+    let mut connections = Vec::new();
+    for (key, conn) in flat {
+        let Some(id) = key.strip_prefix("conn/").and_then(|k| k.split('/').next()) else {
+            continue;
+        };
+        if find(&mut nodes, id).is_none() {
+            continue;
+        }
+        if let Some(output) = conn["connected"].get(0) {
+            connections.push(json!({
+                "input": { "node": id, "idx": conn["idx"] },
+                "output": output,
+            }));
+        }
+    }
+
+    doc.insert("properties".into(), props.into());
+    doc.insert("nodes".into(), nodes.into());
+    doc.insert("groups".into(), groups.into());
+    doc.insert("connections".into(), connections.into());
+    Value::Object(doc)
+}
+
+fn item_id(item: &Value) -> &str {
+    item.get("id").and_then(Value::as_str).unwrap_or_default()
+}
+
+/// A single semantic change between two graphs.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GraphChange {
+    pub key: String,
+    pub item: GraphItem,
+    pub old: Option<Value>,
+    pub new: Option<Value>,
+}
+
+impl GraphChange {
+    /// Layout changes don't affect the compiled shader.
+    pub fn is_layout(&self) -> bool {
+        self.item == GraphItem::Layout
+    }
+}
+
+/// Semantic diff between two graph documents.
+#[derive(Clone, Debug, Default)]
+pub struct GraphDiff {
+    pub changes: Vec<GraphChange>,
+    /// Node names by id, for display.
+    names: Map<String, Value>,
+}
+
+impl GraphDiff {
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Changes that affect the compiled shader.
+    pub fn semantic_changes(&self) -> impl Iterator<Item = &GraphChange> {
+        self.changes.iter().filter(|c| !c.is_layout())
+    }
+
+    fn node_label(&self, id: &str) -> String {
+        match self.names.get(id).and_then(Value::as_str) {
+            Some(name) => format!("{name} ({id})"),
+            None => id.to_string(),
+        }
+    }
+}
+
+/// Compare two graph documents.
+pub fn diff_graphs(old: &Value, new: &Value) -> GraphDiff {
+    let old = flatten_graph(old);
+    let new = flatten_graph(new);
+    let mut diff = GraphDiff::default();
+
+    for (key, value) in old.iter().chain(new.iter()) {
+        if let Some(id) = key.strip_prefix("node/").filter(|id| !id.contains('/')) {
+            if let Some(name) = value.get("name") {
+                diff.names.insert(id.to_string(), name.clone());
+            }
+        }
+    }
+
+    // Changes inside added/removed nodes are implied by the node change.
+    let node_of = |key: &str| -> Option<String> {
+        let mut parts = key.split('/');
+        match (parts.next(), parts.next()) {
+            (Some("node" | "conn"), Some(id)) => Some(id.to_string()),
+            _ => None,
+        }
+    };
+    let is_new_or_removed = |key: &str| {
+        node_of(key).is_some_and(|id| {
+            let header = format!("node/{id}");
+            key != header && old.contains_key(&header) != new.contains_key(&header)
+        })
+    };
+
+    for (key, old_value) in &old {
+        if is_new_or_removed(key) {
+            continue;
+        }
+        match new.get(key) {
+            Some(new_value) if new_value == old_value => {}
+            new_value => diff.changes.push(GraphChange {
+                key: key.clone(),
+                item: GraphItem::from_key(key),
+                old: Some(old_value.clone()),
+                new: new_value.cloned(),
+            }),
+        }
+    }
+    for (key, new_value) in &new {
+        if old.contains_key(key) || is_new_or_removed(key) {
+            continue;
+        }
+        diff.changes.push(GraphChange {
+            key: key.clone(),
+            item: GraphItem::from_key(key),
+            old: None,
+            new: Some(new_value.clone()),
+        });
+    }
+    diff
+}
+
+impl fmt::Display for GraphDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for change in &self.changes {
+            let parts: Vec<&str> = change.key.split('/').collect();
+            let sign = match (&change.old, &change.new) {
+                (None, _) => '+',
+                (_, None) => '-',
+                _ => '~',
+            };
+            let show = |value: &Option<Value>| {
+                value
+                    .as_ref()
+                    .map(Value::to_string)
+                    .unwrap_or_else(|| "none".to_string())
+            };
+            match (change.item, parts.as_slice()) {
+                (GraphItem::Node, [_, id]) => {
+                    writeln!(f, "{sign} node {}", self.node_label(id))?;
+                }
+                (GraphItem::Value | GraphItem::Param, [_, id, _, name]) => {
+                    writeln!(
+                        f,
+                        "{sign} {}.{name}: {} -> {}",
+                        self.node_label(id),
+                        show(&change.old),
+                        show(&change.new)
+                    )?;
+                }
+                (GraphItem::Connection, [_, id, input]) => {
+                    let source = |value: &Option<Value>| {
+                        value
+                            .as_ref()
+                            .and_then(|v| v["connected"].get(0))
+                            .map(|out| {
+                                format!(
+                                    "{}[{}]",
+                                    self.node_label(out["node"].as_str().unwrap_or_default()),
+                                    out["idx"]
+                                )
+                            })
+                            .unwrap_or_else(|| "none".to_string())
+                    };
+                    writeln!(
+                        f,
+                        "{sign} connection {}.{input}: {} -> {}",
+                        self.node_label(id),
+                        source(&change.old),
+                        source(&change.new)
+                    )?;
+                }
+                (GraphItem::Layout, _) => {
+                    writeln!(f, "{sign} layout {}", change.key)?;
+                }
+                _ => {
+                    writeln!(
+                        f,
+                        "{sign} {}: {} -> {}",
+                        change.key,
+                        show(&change.old),
+                        show(&change.new)
+                    )?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Result of a three-way merge.
+#[derive(Clone, Debug)]
+pub struct GraphMerge {
+    pub merged: Value,
+    /// Keys changed differently on both sides, `ours` was kept.
+    pub conflicts: Vec<String>,
+}
+
+impl GraphMerge {
+    pub fn has_conflicts(&self) -> bool {
+        !self.conflicts.is_empty()
+    }
+}
+
+/// Three-way merge of graph documents.
+///
+/// Entries changed on only one side are taken from that side.  Layout-only
+/// conflicts (node positions, editor view) are resolved with `ours`, other
+/// conflicts keep `ours` and are reported.
+pub fn merge_graphs(base: &Value, ours: &Value, theirs: &Value) -> GraphMerge {
+    let base = flatten_graph(base);
+    let ours = flatten_graph(ours);
+    let theirs = flatten_graph(theirs);
+
+    let mut merged = Map::new();
+    let mut conflicts = Vec::new();
+    let keys = ours
+        .keys()
+        .chain(theirs.keys().filter(|k| !ours.contains_key(*k)))
+        .chain(
+            base.keys()
+                .filter(|k| !ours.contains_key(*k) && !theirs.contains_key(*k)),
+        );
+    for key in keys {
+        let (b, o, t) = (base.get(key), ours.get(key), theirs.get(key));
+        let value = if o == t || t == b {
+            o
+        } else if o == b {
+            t
+        } else {
+            if GraphItem::from_key(key) != GraphItem::Layout {
+                conflicts.push(key.clone());
+            }
+            o
+        };
+        if let Some(value) = value {
+            merged.insert(key.clone(), value.clone());
+        }
+    }
+
+    // Drop the entries of removed nodes, it is a conflict if the other side changed them.
+    let removed: Vec<String> = merged
+        .keys()
+        .filter_map(|key| {
+            let mut parts = key.split('/');
+            match (parts.next(), parts.next(), parts.next()) {
+                (Some("node" | "conn"), Some(id), Some(_)) => Some(id.to_string()),
+                _ => None,
+            }
+        })
+        .filter(|id| !merged.contains_key(&format!("node/{id}")))
+        .collect();
+    for id in removed {
+        let node_keys: Vec<String> = merged
+            .keys()
+            .filter(|key| {
+                key.starts_with(&format!("node/{id}/")) || key.starts_with(&format!("conn/{id}/"))
+            })
+            .cloned()
+            .collect();
+        for key in node_keys {
+            let changed = base.get(&key) != merged.get(&key);
+            if changed && GraphItem::from_key(&key) != GraphItem::Layout {
+                conflicts.push(key.clone());
+            }
+            merged.remove(&key);
+        }
+    }
+    // Drop connections from removed nodes.
+    let dangling: Vec<String> = merged
+        .iter()
+        .filter(|(key, value)| {
+            key.starts_with("conn/")
+                && value["connected"]
+                    .get(0)
+                    .and_then(|out| out["node"].as_str())
+                    .is_some_and(|src| !merged.contains_key(&format!("node/{src}")))
+        })
+        .map(|(key, _)| key.clone())
+        .collect();
+    for key in dangling {
+        conflicts.push(key.clone());
+        merged.remove(&key);
+    }
+
+    conflicts.sort();
+    conflicts.dedup();
+    GraphMerge {
+        merged: unflatten_graph(&merged),
+        conflicts,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input(value: f32, from: Option<&str>) -> Value {
+        let connected = from.map(|node| json!([{ "node": node, "idx": 0 }, "F32"]));
+        json!({ "value": value, "connected": connected })
+    }
+
+    fn node(id: &str, x: f32, a: Value, b: Value) -> Value {
+        json!({
+            "id": id,
+            "name": "Add",
+            "node": { "a": a, "op": "add", "b": b },
+            "area": { "min": { "x": x, "y": 0.0 }, "max": { "x": x + 100.0, "y": 50.0 } },
+        })
+    }
+
+    /// `n1 -> n2.b`, with `n2` as the output.
+    fn base() -> Value {
+        json!({
+            "id": "graph",
+            "nodes": [
+                node("n1", 0.0, input(1.0, None), input(2.0, None)),
+                node("n2", 200.0, input(3.0, None), input(0.0, Some("n1"))),
+            ],
+            "output": "n2",
+        })
+    }
+
+    fn set(doc: &mut Value, node: usize, path: &[&str], value: Value) {
+        let mut target = &mut doc["nodes"][node];
+        for key in path {
+            target = &mut target[*key];
+        }
+        *target = value;
+    }
+
+    fn connections(doc: &Value) -> Vec<(String, u64, String)> {
+        let mut conns: Vec<_> = doc["connections"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|conn| {
+                (
+                    conn["input"]["node"].as_str().unwrap().to_string(),
+                    conn["input"]["idx"].as_u64().unwrap(),
+                    conn["output"]["node"].as_str().unwrap().to_string(),
+                )
+            })
+            .collect();
+        conns.sort();
+        conns
+    }
+
+    #[test]
+    fn unflatten_keeps_input_index() {
+        let flat = flatten_graph(&base());
+        assert_eq!(flat["conn/n2/b"]["idx"], 1);
+        // Node fields end up in a different order than the inputs.
+        let mut entries: Vec<_> = flat.into_iter().collect();
+        entries.sort_by_key(|(key, _)| key.contains("/field/a"));
+        let doc = unflatten_graph(&entries.into_iter().collect());
+        assert_eq!(connections(&doc), [("n2".into(), 1, "n1".into())]);
+        assert_eq!(doc["nodes"][1]["node"]["b"], input(0.0, Some("n1")));
+        assert_eq!(doc["output"], "n2");
+    }
+
+    #[test]
+    fn merge_both_sides() {
+        let base = base();
+        let mut ours = base.clone();
+        set(&mut ours, 0, &["node", "a", "value"], json!(5.0));
+        let mut theirs = base.clone();
+        set(&mut theirs, 1, &["node", "a"], input(0.0, Some("n1")));
+        set(&mut theirs, 1, &["area", "min", "x"], json!(300.0));
+
+        let merge = merge_graphs(&base, &ours, &theirs);
+        assert!(!merge.has_conflicts(), "{:?}", merge.conflicts);
+        let merged = &merge.merged;
+        assert_eq!(merged["nodes"][0]["node"]["a"]["value"], 5.0);
+        assert_eq!(merged["nodes"][1]["area"]["min"]["x"], 300.0);
+        assert_eq!(
+            connections(merged),
+            [("n2".into(), 0, "n1".into()), ("n2".into(), 1, "n1".into())]
+        );
+    }
+
+    #[test]
+    fn merge_layout_conflict_keeps_ours() {
+        let base = base();
+        let mut ours = base.clone();
+        set(&mut ours, 0, &["area", "min", "x"], json!(-50.0));
+        let mut theirs = base.clone();
+        set(&mut theirs, 0, &["area", "min", "x"], json!(50.0));
+
+        let merge = merge_graphs(&base, &ours, &theirs);
+        assert!(!merge.has_conflicts());
+        assert_eq!(merge.merged["nodes"][0]["area"]["min"]["x"], -50.0);
+    }
+
+    #[test]
+    fn merge_value_conflict() {
+        let base = base();
+        let mut ours = base.clone();
+        set(&mut ours, 1, &["node", "a", "value"], json!(4.0));
+        let mut theirs = base.clone();
+        set(&mut theirs, 1, &["node", "a", "value"], json!(6.0));
+        set(&mut theirs, 1, &["node", "op"], json!("sub"));
+
+        let merge = merge_graphs(&base, &ours, &theirs);
+        assert_eq!(merge.conflicts, ["node/n2/field/a"]);
+        assert_eq!(merge.merged["nodes"][1]["node"]["a"]["value"], 4.0);
+        assert_eq!(merge.merged["nodes"][1]["node"]["op"], "sub");
+    }
+
+    #[test]
+    fn merge_removed_node() {
+        let base = base();
+        let mut ours = base.clone();
+        ours["nodes"].as_array_mut().unwrap().remove(0);
+        set(&mut ours, 0, &["node", "b"], input(0.0, None));
+        let mut theirs = base.clone();
+        set(&mut theirs, 0, &["node", "a", "value"], json!(7.0));
+
+        // `n1` was removed by ours and changed by theirs.
+        let merge = merge_graphs(&base, &ours, &theirs);
+        assert_eq!(merge.conflicts, ["node/n1/field/a"]);
+        let nodes = merge.merged["nodes"].as_array().unwrap();
+        assert_eq!(nodes.len(), 1);
+        assert!(connections(&merge.merged).is_empty());
+
+        // A connection from a removed node is dropped.
+        let mut theirs = base.clone();
+        set(&mut theirs, 1, &["node", "a"], input(0.0, Some("n1")));
+        let mut ours = base.clone();
+        ours["nodes"].as_array_mut().unwrap().remove(0);
+        set(&mut ours, 0, &["node", "b"], input(0.0, None));
+        let merge = merge_graphs(&base, &ours, &theirs);
+        assert_eq!(merge.conflicts, ["conn/n2/a"]);
+        assert!(connections(&merge.merged).is_empty());
+    }
+
+    #[test]
+    fn diff_changes() {
+        let old = base();
+        let mut new = old.clone();
+        set(&mut new, 1, &["node", "b"], input(0.0, None));
+        set(&mut new, 0, &["area", "min", "x"], json!(10.0));
+        let diff = diff_graphs(&old, &new);
+        let keys: Vec<_> = diff.semantic_changes().map(|c| c.key.as_str()).collect();
+        assert_eq!(keys, ["conn/n2/b"]);
+        assert_eq!(diff.changes.len(), 2);
+        assert!(diff
+            .to_string()
+            .contains("connection Add (n2).b: Add (n1)[0] -> none"));
+    }
+}
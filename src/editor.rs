@@ -1,12 +1,13 @@
-use std::fs::File;
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, PoisonError};
 
 use anyhow::Result;
 
 use bevy::prelude::{Result as BevyResult, *};
+use bevy::tasks::AsyncComputeTaskPool;
 use bevy_egui::{egui, EguiContexts};
 
-use node_engine::{NodeGraph, NodeGraphCompile, NodeRegistry};
+use node_engine::NodeGraph;
 
 use crate::*;
 
@@ -15,33 +16,72 @@ pub struct ShaderGraphEditor {
     pub title: String,
     pub size: egui::Vec2,
     pub graph: NodeGraph,
+    pub save_options: SaveOptions,
+    /// Optimize the graph before compiling.
+    pub optimize: bool,
+    /// Cost limits for the target platform.
+    pub cost_budget: CostBudget,
+    pub bake_settings: BakeSettings,
+    /// Inputs (properties, textures) used when baking.
+    pub bake_inputs: EvalInputs,
+    /// Result of the running bake task, taken by `shader_editor`.
+    bake_result: Arc<Mutex<Option<Result<Image>>>>,
+    baking: bool,
+    /// Nodes that can be baked, `(id, name)`.
+    bake_nodes: Vec<(String, String)>,
+    baked_image: Option<Handle<Image>>,
+    shader: Option<Handle<Shader>>,
+    /// Mode of the graph `shader` was compiled from.
+    shader_mode: Option<MasterMode>,
     open: bool,
     open_preview: bool,
     file: PathBuf,
     code: String,
+    attributes: GraphAttributeRequirements,
+    mode: Option<MasterMode>,
     last_change_counter: usize,
     last_error_msg: Option<String>,
+    /// Node templates for linting and the cost estimate, built once.
+    templates: Arc<Mutex<NodeTemplates>>,
+    lints: Vec<GraphLint>,
+    cost: CostEstimate,
+    optimize_report: OptimizeReport,
+    unoptimized_lines: usize,
     changed: bool,
 }
 
 impl Default for ShaderGraphEditor {
     fn default() -> Self {
-        let registry = NodeRegistry::build();
-        let mut graph = NodeGraph::new();
-        let root = registry.new_by_name("Fragment");
-        if let Some(root) = root.ok() {
-            let output_id = graph.add(root);
-            graph.set_output(Some(output_id));
-        }
+        let graph = MasterMode::default()
+            .new_graph()
+            .unwrap_or_else(|_| NodeGraph::new());
 
         Self {
             title: "Graph editor".to_string(),
             size: (1000., 300.).into(),
             graph,
+            save_options: Default::default(),
+            optimize: true,
+            cost_budget: Default::default(),
+            bake_settings: Default::default(),
+            bake_inputs: Default::default(),
+            bake_result: Default::default(),
+            baking: false,
+            bake_nodes: Vec::new(),
+            baked_image: None,
+            shader: None,
+            shader_mode: None,
             file: "shader_graph.json".into(),
             code: "".to_string(),
+            attributes: Default::default(),
+            mode: None,
             last_change_counter: 0,
             last_error_msg: None,
+            templates: Default::default(),
+            lints: Vec::new(),
+            cost: Default::default(),
+            optimize_report: Default::default(),
+            unoptimized_lines: 0,
             open: true,
             open_preview: true,
             changed: true,
@@ -56,20 +96,109 @@ impl ShaderGraphEditor {
 
     pub fn load<P: AsRef<Path>>(&mut self, path: P) -> Result<()> {
         let path = path.as_ref();
-        let file = File::open(path)?;
+        let (graph, report) = load_graph(path)?;
         self.file = path.into();
-        self.graph = serde_json::from_reader(file)?;
+        self.graph = graph;
+        self.changed = true;
+        self.last_change_counter = 0;
+        if report.migrated() {
+            log::warn!(
+                "Upgraded graph {path:?} from format v{} to v{}:",
+                report.from_version,
+                report.to_version
+            );
+            for change in &report.changes {
+                log::warn!("  {change}");
+            }
+            self.last_error_msg = Some(format!(
+                "Upgraded graph from format v{} to v{} ({} changes), save to keep.",
+                report.from_version,
+                report.to_version,
+                report.changes.len()
+            ));
+        }
+        Ok(())
+    }
+
+    /// Start a new graph with the output node for a lighting mode.
+    pub fn new_graph(&mut self, mode: MasterMode) -> Result<()> {
+        self.graph = mode.new_graph()?;
         self.changed = true;
         self.last_change_counter = 0;
+        self.code.clear();
         Ok(())
     }
 
     pub fn save(&self) -> Result<()> {
-        let file = File::create(&self.file)?;
-        serde_json::to_writer_pretty(file, &self.graph)?;
+        save_graph_with(&self.file, &self.graph, &self.save_options)
+    }
+
+    /// Export the graph as a standalone material next to the graph file.
+    pub fn export(&self) -> Result<()> {
+        let name = self
+            .file
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("shader_graph");
+        let dir = self.file.parent().unwrap_or(Path::new("."));
+        ShaderGraphExport::new(&format!("{name}_material")).write(&self.graph, dir)
+    }
+
+    /// Bake the graph into a texture next to the graph file.
+    ///
+    /// The bake runs on the `AsyncComputeTaskPool`, `shader_editor` picks up
+    /// the result.
+    pub fn bake(&mut self) -> Result<()> {
+        if self.baking {
+            return Err(anyhow::anyhow!("A bake is already running"));
+        }
+        let name = self
+            .file
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("shader_graph");
+        let path = self.file.with_file_name(format!(
+            "{name}_baked.{}",
+            self.bake_settings.format.extension()
+        ));
+        let graph = self.graph.clone();
+        let settings = self.bake_settings.clone();
+        let inputs = self.bake_inputs.clone();
+        let result = self.bake_result.clone();
+        self.baking = true;
+        AsyncComputeTaskPool::get()
+            .spawn(async move {
+                let res = bake_graph_to_file(&graph, &settings, &inputs, &path);
+                if res.is_ok() {
+                    log::info!("Baked shader graph to {path:?}");
+                }
+                *result.lock().unwrap_or_else(PoisonError::into_inner) = Some(res);
+            })
+            .detach();
         Ok(())
     }
 
+    /// Take the result of a finished bake.
+    fn finish_bake(&mut self) -> Option<Image> {
+        let res = self
+            .bake_result
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take()?;
+        self.baking = false;
+        self.handle_result("Failed to bake", res).ok()
+    }
+
+    /// Is a bake running?
+    pub fn is_baking(&self) -> bool {
+        self.baking
+    }
+
+    /// The last baked texture.
+    pub fn baked_image(&self) -> Option<&Handle<Image>> {
+        self.baked_image.as_ref()
+    }
+
     fn handle_result<V>(&mut self, context: &str, res: Result<V>) -> Result<V> {
         if let Err(err) = &res {
             let msg = format!("{context}: {err:?}");
@@ -106,10 +235,65 @@ impl ShaderGraphEditor {
         egui::TopBottomPanel::top("graph_top_panel").show_inside(ui, |ui| {
             egui::MenuBar::new().ui(ui, |ui| {
                 ui.menu_button("File", |ui| {
+                    ui.menu_button("New", |ui| {
+                        for mode in MasterMode::ALL {
+                            if ui.button(mode.label()).clicked() {
+                                let res = self.new_graph(mode);
+                                let _ = self.handle_result("Failed to create graph", res);
+                                ui.close_kind(egui::UiKind::Menu);
+                            }
+                        }
+                    });
                     if ui.button("Save").clicked() {
                         let _ = self.handle_result("Failed to save", self.save());
                         ui.close_kind(egui::UiKind::Menu);
                     }
+                    ui.checkbox(
+                        &mut self.save_options.view_sidecar,
+                        "Save view state to .view",
+                    );
+                    if ui.button("Export material").clicked() {
+                        let _ = self.handle_result("Failed to export", self.export());
+                        ui.close_kind(egui::UiKind::Menu);
+                    }
+                    ui.menu_button("Bake", |ui| {
+                        let settings = &mut self.bake_settings;
+                        ui.horizontal(|ui| {
+                            ui.label("Size");
+                            ui.add(egui::DragValue::new(&mut settings.size.x).range(1..=8192));
+                            ui.add(egui::DragValue::new(&mut settings.size.y).range(1..=8192));
+                        });
+                        ui.horizontal(|ui| {
+                            ui.radio_value(&mut settings.format, BakeFormat::Png, "PNG");
+                            ui.radio_value(&mut settings.format, BakeFormat::Exr, "EXR");
+                        });
+                        let selected = settings
+                            .node
+                            .as_ref()
+                            .and_then(|id| self.bake_nodes.iter().find(|(n, _)| n == id))
+                            .map_or("Graph output", |(_, name)| name.as_str());
+                        egui::ComboBox::from_label("Node")
+                            .selected_text(selected)
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(&mut settings.node, None, "Graph output");
+                                for (id, name) in &self.bake_nodes {
+                                    ui.selectable_value(&mut settings.node, Some(id.clone()), name);
+                                }
+                            });
+                        let label = if self.baking {
+                            "Baking..."
+                        } else {
+                            "Bake texture"
+                        };
+                        if ui
+                            .add_enabled(!self.baking, egui::Button::new(label))
+                            .clicked()
+                        {
+                            let res = self.bake();
+                            let _ = self.handle_result("Failed to bake", res);
+                            ui.close_kind(egui::UiKind::Menu);
+                        }
+                    });
                 });
             });
         });
@@ -118,6 +302,24 @@ impl ShaderGraphEditor {
             if let Some(err_msg) = self.last_error_msg.as_ref() {
                 ui.label(err_msg);
             }
+            if !self.lints.is_empty() {
+                egui::CollapsingHeader::new(format!("Warnings ({})", self.lints.len()))
+                    .id_salt("graph_lints")
+                    .show(ui, |ui| {
+                        egui::ScrollArea::vertical()
+                            .max_height(100.)
+                            .show(ui, |ui| {
+                                for lint in &self.lints {
+                                    let color = match lint.level {
+                                        LintLevel::Warning => ui.visuals().warn_fg_color,
+                                        LintLevel::Info => ui.visuals().text_color(),
+                                    };
+                                    ui.colored_label(color, &lint.message);
+                                }
+                            });
+                    });
+            }
+            self.cost_ui(ui);
         });
         self.graph.show_details(ui);
         self.graph.show_graph(ui);
@@ -144,6 +346,21 @@ impl ShaderGraphEditor {
     }
 
     fn preview_ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            // Switch between optimized and unoptimized code to compare them.
+            if ui.checkbox(&mut self.optimize, "Optimized").changed() {
+                self.generate_code();
+            }
+            if self.optimize {
+                ui.label(format!(
+                    "{}, {} -> {} lines",
+                    self.optimize_report,
+                    self.unoptimized_lines,
+                    self.code.lines().count()
+                ));
+            }
+        });
+
         let Self { code, .. } = self;
 
         let theme = egui_extras::syntax_highlighting::CodeTheme::from_memory(ui.ctx(), ui.style());
@@ -176,15 +393,100 @@ impl ShaderGraphEditor {
 
     fn generate_code(&mut self) {
         self.changed = true;
-        let mut compiler = NodeGraphCompile::new();
-        compiler.define_block("imports");
-        compiler.define_block("bindings");
-
-        if let Err(err) = compiler.compile_graph(&self.graph) {
-            log::error!("Failed to compile shader graph: {err:?}");
+        let compiled = if self.optimize {
+            self.unoptimized_lines = compile_graph(&self.graph).code.lines().count();
+            let (compiled, report) =
+                compile_graph_optimized(&self.graph, &OptimizeOptions::default());
+            self.optimize_report = report;
+            compiled
+        } else {
+            compile_graph(&self.graph)
+        };
+        if let Some(err) = &compiled.error {
+            log::error!("Failed to compile shader graph: {err}");
         }
 
-        self.code = compiler.dump();
+        self.code = compiled.code;
+        self.attributes = compiled.attributes;
+        self.mode = compiled.mode;
+        let mut templates = self
+            .templates
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        self.lints = lint_graph_with(&self.graph, &mut templates).unwrap_or_else(|err| {
+            log::error!("Failed to lint shader graph: {err}");
+            Vec::new()
+        });
+        self.cost = estimate_cost_with(&self.graph, &mut templates).unwrap_or_else(|err| {
+            log::error!("Failed to estimate shader graph cost: {err}");
+            CostEstimate::default()
+        });
+        self.bake_nodes = match graph_to_value(&self.graph) {
+            Ok(doc) => {
+                let output = doc.get("output").and_then(|id| id.as_str());
+                doc_nodes(&doc)
+                    .filter(|node| Some(node_id(node)) != output)
+                    .map(|node| {
+                        let name = node.get("name").and_then(|n| n.as_str());
+                        (
+                            node_id(node).to_string(),
+                            name.unwrap_or_default().to_string(),
+                        )
+                    })
+                    .collect()
+            }
+            Err(err) => {
+                log::error!("Failed to list the shader graph nodes: {err}");
+                Vec::new()
+            }
+        };
+    }
+
+    /// Estimated cost of the graph.
+    pub fn cost(&self) -> &CostEstimate {
+        &self.cost
+    }
+
+    fn cost_ui(&self, ui: &mut egui::Ui) {
+        let over_budget = self.cost.over_budget(&self.cost_budget);
+        let title = format!("Cost: {}", self.cost.total);
+        let title = if over_budget.is_empty() {
+            egui::RichText::new(title)
+        } else {
+            egui::RichText::new(title).color(ui.visuals().warn_fg_color)
+        };
+        egui::CollapsingHeader::new(title)
+            .id_salt("graph_cost")
+            .show(ui, |ui| {
+                for over in &over_budget {
+                    ui.colored_label(ui.visuals().warn_fg_color, format!("Over budget: {over}"));
+                }
+                ui.label(format!(
+                    "Max. live values: {} (register pressure hint)",
+                    self.cost.live_values
+                ));
+                egui::Grid::new("graph_cost_groups")
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for header in ["Group", "Nodes", "~ALU", "Samples", "Dependent"] {
+                            ui.strong(header);
+                        }
+                        ui.end_row();
+                        for group in &self.cost.groups {
+                            ui.label(group.title.as_deref().unwrap_or("Ungrouped"));
+                            ui.label(group.nodes.to_string());
+                            ui.label(group.cost.alu.to_string());
+                            ui.label(group.cost.texture_samples.to_string());
+                            ui.label(group.cost.dependent_reads.to_string());
+                            ui.end_row();
+                        }
+                    });
+            });
+    }
+
+    /// Problems found in the graph by the linter.
+    pub fn lints(&self) -> &[GraphLint] {
+        &self.lints
     }
 
     pub fn recompile(&mut self) -> Option<Shader> {
@@ -194,6 +496,30 @@ impl ShaderGraphEditor {
         self.changed = false;
         Some(Shader::from_wgsl(self.code.clone(), ""))
     }
+
+    /// Mesh vertex attributes read by the graph's nodes.
+    pub fn attributes(&self) -> GraphAttributeRequirements {
+        self.attributes
+    }
+
+    /// The last compiled shader, updated by `shader_editor`.
+    pub fn shader(&self) -> Option<&Handle<Shader>> {
+        self.shader.as_ref()
+    }
+
+    /// Mode of the graph the last shader was compiled from.
+    pub fn shader_mode(&self) -> Option<MasterMode> {
+        self.shader_mode
+    }
+
+    /// The last compiled shader, if target `T` supports its mode.
+    pub fn shader_for<T: ShaderGraphTarget>(&self) -> Option<&Handle<Shader>> {
+        if T::MODES.contains(&self.shader_mode?) {
+            self.shader.as_ref()
+        } else {
+            None
+        }
+    }
 }
 
 pub fn shader_editor(
@@ -201,7 +527,7 @@ pub fn shader_editor(
     mut editor: ResMut<ShaderGraphEditor>,
     mut contexts: EguiContexts,
     mut shaders: ResMut<Assets<Shader>>,
-    mut materials: ResMut<Assets<StandardShaderGraphMaterial>>,
+    mut images: ResMut<Assets<Image>>,
 ) -> BevyResult {
     let ctx = contexts.ctx_mut()?;
     if !ctx.wants_keyboard_input() && input.just_pressed(KeyCode::KeyS) {
@@ -213,13 +539,55 @@ pub fn shader_editor(
     editor.show(ctx);
     editor.show_preview(ctx);
 
+    if let Some(image) = editor.finish_bake() {
+        editor.baked_image = Some(images.add(image));
+    }
+
     if let Some(shader) = editor.recompile() {
         eprintln!("Updating shader");
-        let shader = shaders.add(shader);
-        for (_, mat) in materials.iter_mut() {
-            mat.extension.graph.fragment = Some(shader.clone());
-        }
+        editor.shader = Some(shaders.add(shader));
+        editor.shader_mode = editor.mode;
     }
 
     Ok(())
 }
+
+/// Apply the editor's shader to all graph materials of type `M`, when the
+/// graph's mode is supported by `M`.
+///
+/// Add one per material type:
+/// `update_graph_materials::<StandardShaderGraphMaterial>`.
+pub fn update_graph_materials<M: Asset + ShaderGraphTarget>(
+    editor: Res<ShaderGraphEditor>,
+    mut materials: ResMut<Assets<M>>,
+) {
+    let Some(shader) = editor.shader_for::<M>() else {
+        return;
+    };
+    // Only touch stale materials, `get_mut` marks a material as changed.
+    let stale: Vec<_> = materials
+        .iter()
+        .filter(|(_, mat)| mat.fragment() != Some(shader))
+        .map(|(id, _)| id)
+        .collect();
+    for id in stale {
+        if let Some(mat) = materials.get_mut(id) {
+            mat.set_fragment(shader.clone(), editor.attributes());
+        }
+    }
+}
+
+/// Apply the editor's `Post Process` shader to all `ShaderGraphPostProcess` cameras.
+pub fn update_graph_post_process(
+    editor: Res<ShaderGraphEditor>,
+    mut cameras: Query<&mut ShaderGraphPostProcess>,
+) {
+    let Some(shader) = editor.shader_for::<ShaderGraphPostProcess>() else {
+        return;
+    };
+    for mut post_process in &mut cameras {
+        if post_process.fragment() != Some(shader) {
+            post_process.set_fragment(shader.clone(), editor.attributes());
+        }
+    }
+}
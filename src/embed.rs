@@ -0,0 +1,98 @@
+use std::path::{Path, PathBuf};
+
+use bevy::{asset::io::embedded::EmbeddedAssetRegistry, prelude::*};
+
+use crate::attributes::GraphAttributeRequirements;
+
+#[cfg(feature = "macros")]
+pub use bevy_shader_graph_macros::shader_graph;
+
+/// A shader graph compiled at build time by the `shader_graph!` macro.
+#[derive(Clone, Debug)]
+pub struct EmbeddedShaderGraph {
+    /// Graph file, relative to the crate's `Cargo.toml`.
+    pub path: &'static str,
+    pub code: &'static str,
+    /// Mesh vertex attributes read by the graph's nodes.
+    pub attributes: &'static [&'static str],
+}
+
+impl EmbeddedShaderGraph {
+    /// Path of the compiled shader in the `bevy_shader_graph` embedded assets.
+    pub fn embedded_path(&self) -> PathBuf {
+        Path::new("bevy_shader_graph")
+            .join(self.path)
+            .with_extension("wgsl")
+    }
+
+    /// Asset path of the compiled shader, `embedded://bevy_shader_graph/<path>.wgsl`.
+    pub fn asset_path(&self) -> String {
+        format!("embedded://{}", self.embedded_path().display())
+    }
+
+    /// Load the compiled shader, the graph must be registered with `App::embed_shader_graph`.
+    pub fn load(&self, asset_server: &AssetServer) -> Handle<Shader> {
+        asset_server.load(self.asset_path())
+    }
+
+    /// A `ShaderGraph` using the compiled shader as its `fragment`.
+    pub fn shader_graph(&self, asset_server: &AssetServer) -> crate::ShaderGraph {
+        crate::ShaderGraph {
+            fragment: Some(self.load(asset_server)),
+            attributes: GraphAttributeRequirements::from_names(self.attributes.iter().copied()),
+            ..default()
+        }
+    }
+}
+
+pub trait EmbedShaderGraphAppExt {
+    /// Add the shader of an embedded graph to the embedded assets.
+    fn embed_shader_graph(&mut self, graph: &EmbeddedShaderGraph) -> &mut Self;
+}
+
+impl EmbedShaderGraphAppExt for App {
+    fn embed_shader_graph(&mut self, graph: &EmbeddedShaderGraph) -> &mut Self {
+        let registry = self.world().resource::<EmbeddedAssetRegistry>();
+        // There is no `.wgsl` file on disk to watch, the macro rebuilds the code
+        // when the graph file changes.
+        registry.insert_asset(
+            PathBuf::from(graph.path).with_extension("wgsl"),
+            &graph.embedded_path(),
+            graph.code.as_bytes(),
+        );
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GRAPH: EmbeddedShaderGraph = EmbeddedShaderGraph {
+        path: "assets/graphs/wall.json",
+        code: "// wall",
+        attributes: &["uv0"],
+    };
+
+    #[test]
+    fn asset_path_of_the_compiled_shader() {
+        assert_eq!(
+            GRAPH.embedded_path(),
+            Path::new("bevy_shader_graph/assets/graphs/wall.wgsl")
+        );
+        assert_eq!(
+            GRAPH.asset_path(),
+            "embedded://bevy_shader_graph/assets/graphs/wall.wgsl"
+        );
+    }
+
+    #[test]
+    fn embed_adds_the_code_to_the_embedded_assets() {
+        let mut app = App::new();
+        app.add_plugins(AssetPlugin::default());
+        app.embed_shader_graph(&GRAPH);
+
+        let registry = app.world().resource::<EmbeddedAssetRegistry>();
+        assert!(registry.remove_asset(&GRAPH.embedded_path()).is_some());
+    }
+}
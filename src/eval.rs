@@ -0,0 +1,783 @@
+use std::collections::{BTreeMap, HashMap};
+
+use anyhow::{anyhow, Result};
+use bevy::image::Image;
+use bevy::math::{Vec2, Vec3, Vec4, Vec4Swizzles};
+use serde_json::Value;
+
+use node_engine::NodeGraph;
+
+use crate::document::{
+    doc_nodes, graph_to_value, input_source, is_input, node_id, node_kind, value_floats,
+    NodeTemplates,
+};
+
+/// Nodes the evaluator supports.
+const EVAL_NODES: &[&str] = &[
+    "Add",
+    "Subtract",
+    "Multiply",
+    "Divide",
+    "Min",
+    "Max",
+    "Absolute",
+    "Fraction",
+    "Clamp",
+    "Lerp",
+    "Split",
+    "Combine",
+    "Swizzle",
+    "Uv",
+    "Sprite Uv",
+    "Ui Node Uv",
+    "Screen Uv",
+    "Decal Uv",
+    "View Direction",
+    "Tiling And Offset",
+    "Texture Sample",
+    "Fresnel Effect",
+];
+
+/// A value with 1 to 4 components.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct EvalValue {
+    pub value: Vec4,
+    pub size: usize,
+}
+
+impl EvalValue {
+    pub fn scalar(v: f32) -> Self {
+        Self {
+            value: Vec4::new(v, 0.0, 0.0, 0.0),
+            size: 1,
+        }
+    }
+
+    pub fn new(values: &[f32]) -> Self {
+        let mut value = Vec4::ZERO;
+        for (idx, v) in values.iter().take(4).enumerate() {
+            value[idx] = *v;
+        }
+        Self {
+            value,
+            size: values.len().clamp(1, 4),
+        }
+    }
+
+    /// Component `idx`, scalars are splatted like in WGSL.
+    fn get(&self, idx: usize) -> f32 {
+        if self.size == 1 {
+            self.value.x
+        } else {
+            self.value[idx]
+        }
+    }
+
+    fn truncate(mut self, size: usize) -> Self {
+        self.size = self.size.min(size);
+        self
+    }
+
+    /// The value as a `vec4`, scalars are splatted.
+    pub fn to_vec4(&self) -> Vec4 {
+        match self.size {
+            1 => Vec4::splat(self.value.x),
+            _ => self.value,
+        }
+    }
+}
+
+/// Per-sample inputs.
+#[derive(Clone, Copy, Debug)]
+pub struct EvalSample {
+    pub uv: Vec2,
+    /// View direction, in the space the graph's `View direction` nodes use.
+    pub view_dir: Vec3,
+    /// Surface normal, used by nodes with an unconnected normal input.
+    pub normal: Vec3,
+}
+
+impl Default for EvalSample {
+    fn default() -> Self {
+        Self {
+            uv: Vec2::ZERO,
+            view_dir: Vec3::Z,
+            normal: Vec3::Z,
+        }
+    }
+}
+
+impl EvalSample {
+    pub fn from_uv(uv: Vec2) -> Self {
+        Self {
+            uv,
+            ..Default::default()
+        }
+    }
+}
+
+/// Inputs shared by all samples.
+#[derive(Clone, Debug, Default)]
+pub struct EvalInputs {
+    /// Property values, by property name or the name of a constant node
+    /// (e.g. `Far fraction`).
+    pub properties: HashMap<String, Vec4>,
+    /// Textures, by the id of the `Texture Sample` node or of the node
+    /// connected to its `tex` input.
+    pub textures: HashMap<String, Image>,
+}
+
+impl EvalInputs {
+    pub fn with_property(mut self, name: &str, value: Vec4) -> Self {
+        self.properties.insert(name.to_string(), value);
+        self
+    }
+
+    pub fn with_texture(mut self, node: &str, image: Image) -> Self {
+        self.textures.insert(node.to_string(), image);
+        self
+    }
+}
+
+/// The evaluated inputs of the graph's output node.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FragmentOutput {
+    pub values: BTreeMap<String, Vec4>,
+}
+
+impl FragmentOutput {
+    pub fn get(&self, name: &str) -> Option<Vec4> {
+        self.values.get(name).copied()
+    }
+
+    /// The `color` output.
+    pub fn color(&self) -> Vec4 {
+        self.get("color").unwrap_or_default()
+    }
+}
+
+#[derive(Clone, Debug)]
+enum EvalInput {
+    Connected(usize, usize),
+    Value(EvalValue),
+    Empty,
+}
+
+#[derive(Clone, Debug)]
+struct EvalNode {
+    id: String,
+    name: String,
+    kind: String,
+    inputs: Vec<(String, EvalInput)>,
+    params: serde_json::Map<String, Value>,
+}
+
+impl EvalNode {
+    fn input(&self, name: &str) -> Option<&EvalInput> {
+        self.inputs.iter().find(|(n, _)| n == name).map(|(_, i)| i)
+    }
+}
+
+/// Evaluates a shader graph on the CPU.
+///
+/// Supports the math, vector, UV, view direction and texture sample nodes.
+/// Other nodes fail with an error naming the node.
+#[derive(Clone, Debug)]
+pub struct GraphEvaluator {
+    nodes: Vec<EvalNode>,
+    output: usize,
+    /// Graph property defaults.
+    properties: HashMap<String, Vec4>,
+}
+
+impl GraphEvaluator {
+    pub fn new(graph: &NodeGraph) -> Result<Self> {
+        let doc = graph_to_value(graph)?;
+        let mut templates = NodeTemplates::default();
+        Self::from_document(&doc, &mut templates)
+    }
+
+    pub fn from_document(doc: &Value, templates: &mut NodeTemplates) -> Result<Self> {
+        templates.preload(EVAL_NODES);
+        let ids: HashMap<&str, usize> = doc_nodes(doc)
+            .enumerate()
+            .map(|(idx, node)| (node_id(node), idx))
+            .collect();
+        let output = doc
+            .get("output")
+            .and_then(Value::as_str)
+            .and_then(|id| ids.get(id).copied())
+            .ok_or_else(|| anyhow!("The graph has no output node"))?;
+
+        let mut nodes = Vec::new();
+        for node in doc_nodes(doc) {
+            let fields = node["node"].as_object().cloned().unwrap_or_default();
+            let mut inputs = Vec::new();
+            let mut params = serde_json::Map::new();
+            for (name, field) in fields {
+                if !is_input(&field) {
+                    params.insert(name, field);
+                    continue;
+                }
+                let input = match input_source(&field) {
+                    Some((src, idx)) => {
+                        let src = ids.get(src).ok_or_else(|| {
+                            anyhow!("Input `{name}` connected to unknown node {src}")
+                        })?;
+                        EvalInput::Connected(*src, idx as usize)
+                    }
+                    None => match value_floats(&field["value"]) {
+                        Some(values) if !values.is_empty() => {
+                            EvalInput::Value(EvalValue::new(&values))
+                        }
+                        _ => EvalInput::Empty,
+                    },
+                };
+                inputs.push((name, input));
+            }
+            nodes.push(EvalNode {
+                id: node_id(node).to_string(),
+                name: node
+                    .get("name")
+                    .and_then(Value::as_str)
+                    .unwrap_or_default()
+                    .to_string(),
+                kind: node_kind(templates, node).to_string(),
+                inputs,
+                params,
+            });
+        }
+
+        let properties = doc
+            .get("properties")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(|prop| {
+                let name = prop.get("name")?.as_str()?;
+                let value = property_floats(prop.get("value")?)?;
+                Some((name.to_string(), EvalValue::new(&value).value))
+            })
+            .collect();
+
+        Ok(Self {
+            nodes,
+            output,
+            properties,
+        })
+    }
+
+    /// Evaluate the output node's inputs for one sample.
+    pub fn evaluate(&self, sample: &EvalSample, inputs: &EvalInputs) -> Result<FragmentOutput> {
+        let mut eval = Evaluation::new(self, sample, inputs);
+        let output = &self.nodes[self.output];
+        let mut values = BTreeMap::new();
+        for (name, input) in &output.inputs {
+            if let Some(value) = eval.input(input, 0)? {
+                values.insert(name.clone(), value.to_vec4());
+            }
+        }
+        Ok(FragmentOutput { values })
+    }
+
+    /// Evaluate output `idx` of a node for one sample.
+    pub fn evaluate_node(
+        &self,
+        node: &str,
+        idx: usize,
+        sample: &EvalSample,
+        inputs: &EvalInputs,
+    ) -> Result<EvalValue> {
+        let node = self
+            .nodes
+            .iter()
+            .position(|n| n.id == node)
+            .ok_or_else(|| anyhow!("Unknown node: {node}"))?;
+        Evaluation::new(self, sample, inputs).node(node, idx, 0)
+    }
+}
+
+/// State of evaluating one sample.
+struct Evaluation<'a> {
+    graph: &'a GraphEvaluator,
+    sample: &'a EvalSample,
+    inputs: &'a EvalInputs,
+    cache: HashMap<(usize, usize), EvalValue>,
+}
+
+impl<'a> Evaluation<'a> {
+    fn new(graph: &'a GraphEvaluator, sample: &'a EvalSample, inputs: &'a EvalInputs) -> Self {
+        Self {
+            graph,
+            sample,
+            inputs,
+            cache: HashMap::new(),
+        }
+    }
+
+    fn input(&mut self, input: &EvalInput, depth: usize) -> Result<Option<EvalValue>> {
+        match input {
+            EvalInput::Connected(node, idx) => self.node(*node, *idx, depth + 1).map(Some),
+            EvalInput::Value(value) => Ok(Some(*value)),
+            EvalInput::Empty => Ok(None),
+        }
+    }
+
+    /// Evaluate a named input, failing if it has no value.
+    fn arg(&mut self, node: &EvalNode, name: &str, depth: usize) -> Result<EvalValue> {
+        let input = node
+            .input(name)
+            .ok_or_else(|| anyhow!("`{}` has no input `{name}`", node.name))?;
+        self.input(input, depth)?
+            .ok_or_else(|| anyhow!("`{}`: input `{name}` is not connected", node.name))
+    }
+
+    fn node(&mut self, idx: usize, output: usize, depth: usize) -> Result<EvalValue> {
+        if let Some(value) = self.cache.get(&(idx, output)) {
+            return Ok(*value);
+        }
+        if depth > self.graph.nodes.len() {
+            return Err(anyhow!("The graph has a cycle"));
+        }
+        let graph = self.graph;
+        let node = &graph.nodes[idx];
+        let value = self.eval_node(node, output, depth)?;
+        self.cache.insert((idx, output), value);
+        Ok(value)
+    }
+
+    fn eval_node(&mut self, node: &EvalNode, output: usize, depth: usize) -> Result<EvalValue> {
+        let binary = |f: fn(f32, f32) -> f32| {
+            move |eval: &mut Self| -> Result<EvalValue> {
+                let a = eval.arg(node, "a", depth)?;
+                let b = eval.arg(node, "b", depth)?;
+                Ok(map(&[a, b], |v| f(v[0], v[1])))
+            }
+        };
+        match node.kind.as_str() {
+            "Add" => binary(|a, b| a + b)(self),
+            "Subtract" => binary(|a, b| a - b)(self),
+            "Multiply" => binary(|a, b| a * b)(self),
+            "Divide" => binary(|a, b| a / b)(self),
+            "Min" => binary(f32::min)(self),
+            "Max" => binary(f32::max)(self),
+            "Absolute" => {
+                let a = self.arg(node, "a", depth)?;
+                Ok(map(&[a], |v| v[0].abs()))
+            }
+            "Fraction" => {
+                let a = self.arg(node, "a", depth)?;
+                Ok(map(&[a], |v| v[0] - v[0].floor()))
+            }
+            "Clamp" => {
+                let input = self.arg(node, "input", depth)?;
+                let min = self.arg(node, "min", depth)?;
+                let max = self.arg(node, "max", depth)?;
+                Ok(map(&[input, min, max], |v| v[0].max(v[1]).min(v[2])))
+            }
+            "Lerp" => {
+                let a = self.arg(node, "a", depth)?;
+                let b = self.arg(node, "b", depth)?;
+                let t = self.arg(node, "t", depth)?;
+                Ok(map(&[a, b, t], |v| v[0] + (v[1] - v[0]) * v[2]))
+            }
+            "Split" => {
+                let input = self.arg(node, "input", depth)?;
+                Ok(EvalValue::scalar(input.get(output.min(3))))
+            }
+            "Combine" => {
+                let mut values = [0.0; 4];
+                for (value, name) in values.iter_mut().zip(["r", "g", "b", "a"]) {
+                    *value = self.arg(node, name, depth)?.get(0);
+                }
+                // Outputs: vec4, vec3, vec2.
+                let size = 4 - output.min(2);
+                Ok(EvalValue::new(&values[..size]))
+            }
+            "Swizzle" => {
+                let input = self.arg(node, "input", depth)?;
+                let mask = node
+                    .params
+                    .get("swizzle")
+                    .and_then(Value::as_str)
+                    .unwrap_or("xyzw");
+                let values = mask
+                    .chars()
+                    .map(|c| match c {
+                        'x' | 'r' => Ok(input.get(0)),
+                        'y' | 'g' => Ok(input.get(1)),
+                        'z' | 'b' => Ok(input.get(2)),
+                        'w' | 'a' => Ok(input.get(3)),
+                        _ => Err(anyhow!("Invalid swizzle `{mask}`")),
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                Ok(EvalValue::new(&values))
+            }
+            "Uv" | "Sprite Uv" | "Ui Node Uv" | "Screen Uv" | "Decal Uv" => {
+                Ok(EvalValue::new(&self.sample.uv.to_array()))
+            }
+            "View Direction" => Ok(EvalValue::new(&self.sample.view_dir.to_array())),
+            "Tiling And Offset" => {
+                let uv = self.uv_arg(node, "uv", depth)?;
+                let tiling = self.arg(node, "tiling", depth)?;
+                let offset = self.arg(node, "offset", depth)?;
+                Ok(map(&[uv, tiling, offset], |v| v[0] * v[1] + v[2]).truncate(2))
+            }
+            "Texture Sample" => {
+                let uv = self.uv_arg(node, "uv", depth)?;
+                let image = self.texture(node)?;
+                let color = sample_image(image, Vec2::new(uv.get(0), uv.get(1)))?;
+                Ok(EvalValue::new(&color.to_array()))
+            }
+            "Fresnel Effect" => {
+                // Unconnected, the node uses the mesh normal and the direction to
+                // the camera, the opposite of `View direction`.
+                let normal = self
+                    .connected_arg(node, "normal", depth)?
+                    .map_or(self.sample.normal, |v| v.value.xyz());
+                let view_dir = self
+                    .connected_arg(node, "view_dir", depth)?
+                    .map_or(-self.sample.view_dir, |v| v.value.xyz());
+                let power = self.arg(node, "power", depth)?.get(0);
+                let n_dot_v = normal
+                    .normalize_or_zero()
+                    .dot(view_dir.normalize_or_zero())
+                    .clamp(0.0, 1.0);
+                Ok(EvalValue::scalar((1.0 - n_dot_v).powf(power)))
+            }
+            _ if node.inputs.is_empty() => self.constant(node),
+            kind => Err(anyhow!(
+                "CPU evaluation of `{kind}` (node `{}`) is not supported",
+                node.name
+            )),
+        }
+    }
+
+    /// A connected input, `None` if it isn't connected.
+    fn connected_arg(
+        &mut self,
+        node: &EvalNode,
+        name: &str,
+        depth: usize,
+    ) -> Result<Option<EvalValue>> {
+        match node.input(name) {
+            Some(EvalInput::Connected(..)) => self.arg(node, name, depth).map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    /// A UV input, defaults to the sample UV.
+    fn uv_arg(&mut self, node: &EvalNode, name: &str, depth: usize) -> Result<EvalValue> {
+        match node.input(name) {
+            Some(EvalInput::Connected(..)) => self.arg(node, name, depth),
+            _ => Ok(EvalValue::new(&self.sample.uv.to_array())),
+        }
+    }
+
+    fn texture(&self, node: &EvalNode) -> Result<&'a Image> {
+        let source = match node.input("tex") {
+            Some(EvalInput::Connected(src, _)) => Some(self.graph.nodes[*src].id.as_str()),
+            _ => None,
+        };
+        let textures = &self.inputs.textures;
+        textures
+            .get(&node.id)
+            .or_else(|| source.and_then(|src| textures.get(src)))
+            .ok_or_else(|| anyhow!("No texture for `{}` ({})", node.name, node.id))
+    }
+
+    /// Constant nodes: a property override or the node's numeric parameters.
+    fn constant(&self, node: &EvalNode) -> Result<EvalValue> {
+        let properties = &self.inputs.properties;
+        if let Some(value) = properties
+            .get(&node.name)
+            .or_else(|| self.graph.properties.get(&node.name))
+        {
+            return Ok(EvalValue::new(&value.to_array()));
+        }
+        let values: Option<Vec<f32>> = node
+            .params
+            .values()
+            .map(property_floats)
+            .collect::<Option<Vec<_>>>()
+            .map(|values| values.concat());
+        match values {
+            Some(values) if !values.is_empty() => Ok(EvalValue::new(&values)),
+            _ => Err(anyhow!(
+                "CPU evaluation of `{}` (node `{}`) is not supported",
+                node.kind,
+                node.name
+            )),
+        }
+    }
+}
+
+/// Apply `f` per component, the result has the size of the largest argument.
+fn map(args: &[EvalValue], f: impl Fn(&[f32]) -> f32) -> EvalValue {
+    let size = args.iter().map(|arg| arg.size).max().unwrap_or(1);
+    let mut values = [0.0; 4];
+    let mut v = Vec::with_capacity(args.len());
+    for (idx, value) in values.iter_mut().enumerate().take(size) {
+        v.clear();
+        v.extend(args.iter().map(|arg| arg.get(idx)));
+        *value = f(&v);
+    }
+    EvalValue::new(&values[..size])
+}
+
+/// Floats of a property or parameter value (`1.0`, `[1.0, 2.0]`, `{ "Vec2": [..] }`).
+fn property_floats(value: &Value) -> Option<Vec<f32>> {
+    match value {
+        Value::Object(map) if map.len() == 1 && !map.contains_key("data") => {
+            property_floats(map.values().next()?)
+        }
+        _ => value_floats(value),
+    }
+}
+
+/// Bilinear sample with repeat addressing, returns linear RGBA.
+fn sample_image(image: &Image, uv: Vec2) -> Result<Vec4> {
+    let size = image.size();
+    if size.x == 0 || size.y == 0 {
+        return Err(anyhow!("Empty texture"));
+    }
+    let pos = uv * size.as_vec2() - 0.5;
+    let base = pos.floor();
+    let frac = pos - base;
+    let texel = |dx: i32, dy: i32| -> Result<Vec4> {
+        let x = (base.x as i32 + dx).rem_euclid(size.x as i32) as u32;
+        let y = (base.y as i32 + dy).rem_euclid(size.y as i32) as u32;
+        let color = image
+            .get_color_at(x, y)
+            .map_err(|err| anyhow!("Failed to read texture: {err:?}"))?;
+        let color = color.to_linear();
+        Ok(Vec4::new(color.red, color.green, color.blue, color.alpha))
+    };
+    let top = texel(0, 0)?.lerp(texel(1, 0)?, frac.x);
+    let bottom = texel(0, 1)?.lerp(texel(1, 1)?, frac.x);
+    Ok(top.lerp(bottom, frac.y))
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::asset::RenderAssetUsages;
+    use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+
+    use super::*;
+    use crate::import::GraphBuilder;
+
+    fn assert_near(value: EvalValue, expected: &[f32]) {
+        assert_eq!(value.size, expected.len(), "{value:?}");
+        for (idx, expected) in expected.iter().enumerate() {
+            assert!(
+                (value.get(idx) - expected).abs() < 1e-4,
+                "{value:?} != {expected:?} at {idx}"
+            );
+        }
+    }
+
+    fn evaluator(builder: &GraphBuilder) -> GraphEvaluator {
+        let mut templates = NodeTemplates::default();
+        GraphEvaluator::from_document(&builder.document(), &mut templates).unwrap()
+    }
+
+    /// A texture with one row of RGBA texels.
+    fn texture_row(texels: &[[f32; 4]]) -> Image {
+        let data = texels
+            .iter()
+            .flatten()
+            .flat_map(|v| v.to_le_bytes())
+            .collect();
+        Image::new(
+            Extent3d {
+                width: texels.len() as u32,
+                height: 1,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            data,
+            TextureFormat::Rgba32Float,
+            RenderAssetUsages::default(),
+        )
+    }
+
+    #[test]
+    fn interior_mapping() {
+        let json = include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/interior_mapping.json"
+        ));
+        let doc: Value = serde_json::from_str(json).unwrap();
+        let find = |prefix: &str| {
+            doc_nodes(&doc)
+                .map(node_id)
+                .find(|id| id.starts_with(prefix))
+                .unwrap()
+                .to_string()
+        };
+        let room_uv = find("c23c29f6");
+        let depth = find("9d01ca36");
+        let texture = find("63e02ba7");
+        let eval = GraphEvaluator::from_document(&doc, &mut NodeTemplates::default()).unwrap();
+
+        let sample = EvalSample {
+            uv: Vec2::new(0.3, 0.6),
+            view_dir: Vec3::new(0.3, -0.4, -0.8),
+            normal: Vec3::Z,
+        };
+        let inputs = EvalInputs::default();
+        let uv = eval.evaluate_node(&room_uv, 0, &sample, &inputs).unwrap();
+        assert_near(uv.truncate(2), &[0.7375, 0.4]);
+        let d = eval.evaluate_node(&depth, 0, &sample, &inputs).unwrap();
+        assert_near(d.truncate(1), &[1.0]);
+
+        // A nearer back wall moves the hit point.
+        let inputs = EvalInputs::default().with_property("Far fraction", Vec4::splat(0.25));
+        let uv = eval.evaluate_node(&room_uv, 0, &sample, &inputs).unwrap();
+        assert_near(uv.truncate(2), &[0.741935, 0.387097]);
+        let d = eval.evaluate_node(&depth, 0, &sample, &inputs).unwrap();
+        assert_near(d.truncate(1), &[0.355556]);
+
+        let sample = EvalSample {
+            view_dir: Vec3::new(-0.2, 0.5, -0.6),
+            ..sample
+        };
+        let inputs = EvalInputs::default();
+        let uv = eval.evaluate_node(&room_uv, 0, &sample, &inputs).unwrap();
+        assert_near(uv.truncate(2), &[0.516129, 0.903226]);
+        let d = eval.evaluate_node(&depth, 0, &sample, &inputs).unwrap();
+        assert_near(d.truncate(1), &[0.24]);
+
+        // The room texture is the output color.
+        let texel = [0.25, 0.5, 0.75, 1.0];
+        let inputs = EvalInputs::default().with_texture(&texture, texture_row(&[texel]));
+        let output = eval.evaluate(&sample, &inputs).unwrap();
+        assert!(output.color().abs_diff_eq(Vec4::from_array(texel), 1e-5));
+    }
+
+    #[test]
+    fn math_graph() {
+        let mut builder = GraphBuilder::default();
+        let output = builder.add_output("Unlit Fragment", Vec2::ZERO).unwrap();
+        let uv = builder.add_node("Uv", Vec2::ZERO).unwrap();
+        let multiply = builder.add_node("Multiply", Vec2::ZERO).unwrap();
+        let fraction = builder.add_node("Fraction", Vec2::ZERO).unwrap();
+        let clamp = builder.add_node("Clamp", Vec2::ZERO).unwrap();
+        let split = builder.add_node("Split", Vec2::ZERO).unwrap();
+        let lerp = builder.add_node("Lerp", Vec2::ZERO).unwrap();
+        let combine = builder.add_node("Combine", Vec2::ZERO).unwrap();
+        builder.connect(&uv, 0, &multiply, "a").unwrap();
+        builder.set_input(&multiply, "b", &[3.0]);
+        builder.connect(&multiply, 0, &fraction, "a").unwrap();
+        builder.connect(&fraction, 0, &clamp, "input").unwrap();
+        builder.set_input(&clamp, "min", &[0.0]);
+        builder.set_input(&clamp, "max", &[0.85]);
+        builder.connect(&clamp, 0, &split, "input").unwrap();
+        builder.set_input(&lerp, "a", &[0.0]);
+        builder.set_input(&lerp, "b", &[2.0]);
+        builder.connect(&split, 1, &lerp, "t").unwrap();
+        builder.connect(&split, 0, &combine, "r").unwrap();
+        builder.connect(&lerp, 0, &combine, "g").unwrap();
+        builder.set_input(&combine, "b", &[0.5]);
+        builder.set_input(&combine, "a", &[1.0]);
+        builder.connect(&combine, 0, &output, "color").unwrap();
+        let eval = evaluator(&builder);
+
+        let sample = EvalSample::from_uv(Vec2::new(0.3, 0.6));
+        let inputs = EvalInputs::default();
+        let node = |id: &str, idx| eval.evaluate_node(id, idx, &sample, &inputs).unwrap();
+        assert_near(node(&multiply, 0), &[0.9, 1.8]);
+        assert_near(node(&fraction, 0), &[0.9, 0.8]);
+        assert_near(node(&clamp, 0), &[0.85, 0.8]);
+        assert_near(node(&split, 1), &[0.8]);
+        assert_near(node(&lerp, 0), &[1.6]);
+        assert_near(node(&combine, 1), &[0.85, 1.6, 0.5]);
+
+        let output = eval.evaluate(&sample, &inputs).unwrap();
+        assert!(output
+            .color()
+            .abs_diff_eq(Vec4::new(0.85, 1.6, 0.5, 1.0), 1e-5));
+    }
+
+    #[test]
+    fn texture_graph() {
+        let mut builder = GraphBuilder::default();
+        let output = builder.add_output("Unlit Fragment", Vec2::ZERO).unwrap();
+        let tiling = builder.add_node("Tiling And Offset", Vec2::ZERO).unwrap();
+        let sample_node = builder.add_node("Texture Sample", Vec2::ZERO).unwrap();
+        let uv = builder.add_node("Uv", Vec2::ZERO).unwrap();
+        builder.connect(&uv, 0, &tiling, "uv").unwrap();
+        builder.set_input(&tiling, "tiling", &[1.0, 1.0]);
+        builder.set_input(&tiling, "offset", &[0.25, 0.0]);
+        builder.connect(&tiling, 0, &sample_node, "uv").unwrap();
+        builder.connect(&sample_node, 0, &output, "color").unwrap();
+        let eval = evaluator(&builder);
+
+        let black = [0.0, 0.0, 0.0, 1.0];
+        let white = [1.0, 1.0, 1.0, 1.0];
+        let inputs = EvalInputs::default().with_texture(&sample_node, texture_row(&[black, white]));
+        let color = |u: f32| {
+            let sample = EvalSample::from_uv(Vec2::new(u, 0.5));
+            eval.evaluate(&sample, &inputs).unwrap().color()
+        };
+        // Texel centers are at u = 0.25 and 0.75, before the offset.
+        assert!(color(0.0).abs_diff_eq(Vec4::from_array(black), 1e-5));
+        assert!(color(0.25).abs_diff_eq(Vec4::new(0.5, 0.5, 0.5, 1.0), 1e-5));
+        assert!(color(0.5).abs_diff_eq(Vec4::from_array(white), 1e-5));
+        // Repeat addressing.
+        assert!(color(1.0).abs_diff_eq(Vec4::from_array(black), 1e-5));
+
+        let sample = EvalSample::from_uv(Vec2::new(0.5, 0.5));
+        let err = eval.evaluate(&sample, &EvalInputs::default()).unwrap_err();
+        assert!(err.to_string().starts_with("No texture for"), "{err}");
+    }
+
+    #[test]
+    fn supported_nodes_exist() {
+        let mut templates = NodeTemplates::default();
+        for name in EVAL_NODES {
+            assert!(templates.get(name).is_some(), "no `{name}` node");
+        }
+    }
+
+    #[test]
+    fn fresnel_effect() {
+        let mut builder = GraphBuilder::default();
+        let output = builder.add_output("Unlit Fragment", Vec2::ZERO).unwrap();
+        let node = builder.add_node("Fresnel Effect", Vec2::ZERO).unwrap();
+        builder.set_input(&node, "power", &[2.0]);
+        builder.connect(&node, 0, &output, "color").unwrap();
+        let eval = evaluator(&builder);
+
+        let inputs = EvalInputs::default();
+        let fresnel = |view_dir: Vec3| {
+            let sample = EvalSample {
+                view_dir,
+                ..Default::default()
+            };
+            eval.evaluate_node(&node, 0, &sample, &inputs).unwrap()
+        };
+        // Looking straight at the surface, at 60 degrees and at a grazing angle.
+        assert_near(fresnel(Vec3::NEG_Z), &[0.0]);
+        assert_near(fresnel(Vec3::new(0.0, 3f32.sqrt(), -1.0)), &[0.25]);
+        assert_near(fresnel(Vec3::X), &[1.0]);
+    }
+
+    #[test]
+    fn unsupported_nodes() {
+        let mut builder = GraphBuilder::default();
+        let output = builder.add_output("Unlit Fragment", Vec2::ZERO).unwrap();
+        let time = builder.add_node("Time", Vec2::ZERO).unwrap();
+        builder.connect(&time, 0, &output, "color").unwrap();
+        let eval = evaluator(&builder);
+        let err = eval
+            .evaluate(&EvalSample::default(), &EvalInputs::default())
+            .unwrap_err();
+        assert!(err.to_string().contains("`Time`"), "{err}");
+
+        let mut builder = GraphBuilder::default();
+        builder.add_node("Uv", Vec2::ZERO).unwrap();
+        let doc = builder.document();
+        assert!(GraphEvaluator::from_document(&doc, &mut NodeTemplates::default()).is_err());
+    }
+}
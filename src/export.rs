@@ -0,0 +1,601 @@
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use serde::Deserialize;
+use serde_json::Value;
+
+use node_engine::NodeGraph;
+
+use crate::compile_graph;
+
+/// Type of a graph property in the exported material.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PropertyType {
+    F32,
+    Vec2,
+    Vec3,
+    Vec4,
+}
+
+impl PropertyType {
+    fn rust_type(&self) -> &'static str {
+        match self {
+            Self::F32 => "f32",
+            Self::Vec2 => "Vec2",
+            Self::Vec3 => "Vec3",
+            Self::Vec4 => "Vec4",
+        }
+    }
+
+    fn wgsl_type(&self) -> &'static str {
+        match self {
+            Self::F32 => "f32",
+            Self::Vec2 => "vec2<f32>",
+            Self::Vec3 => "vec3<f32>",
+            Self::Vec4 => "vec4<f32>",
+        }
+    }
+
+    fn from_len(len: usize) -> Option<Self> {
+        match len {
+            1 => Some(Self::F32),
+            2 => Some(Self::Vec2),
+            3 => Some(Self::Vec3),
+            4 => Some(Self::Vec4),
+            _ => None,
+        }
+    }
+}
+
+/// A property in the graph's `properties` list: `{ "name": "Tint", "value": { "Vec3": [1.0, 0.5, 0.5] } }`.
+#[derive(Deserialize)]
+struct PropertyDef {
+    name: String,
+    value: PropertyValue,
+}
+
+/// A property value, tagged like `node_engine` saves it (`{ "F32": 1.0 }`) or plain
+/// floats (`1.0`, `[1.0, 0.5]`) in hand written documents.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum PropertyValue {
+    Tagged(node_engine::Value),
+    Scalar(f32),
+    Vector(Vec<f32>),
+}
+
+/// A graph property, exported as a named material field.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExportProperty {
+    pub name: String,
+    pub field: String,
+    pub ty: PropertyType,
+    pub default: [f32; 4],
+}
+
+impl ExportProperty {
+    pub fn new(name: &str, ty: PropertyType, default: [f32; 4]) -> Self {
+        Self {
+            name: name.to_string(),
+            field: to_snake_case(name),
+            ty,
+            default,
+        }
+    }
+
+    fn from_def(def: PropertyDef) -> Result<Self> {
+        let values = match def.value {
+            PropertyValue::Tagged(value) => match value {
+                node_engine::Value::I32(v) => vec![v as f32],
+                node_engine::Value::U32(v) => vec![v as f32],
+                node_engine::Value::F32(v) => vec![v],
+                node_engine::Value::Vec2(v) => v.to_array().to_vec(),
+                node_engine::Value::Vec3(v) => v.to_array().to_vec(),
+                node_engine::Value::Vec4(v) => v.to_array().to_vec(),
+                value => {
+                    return Err(anyhow!(
+                        "Property `{}` has an unsupported type: {value:?}",
+                        def.name
+                    ))
+                }
+            },
+            PropertyValue::Scalar(v) => vec![v],
+            PropertyValue::Vector(values) => values,
+        };
+        let ty = PropertyType::from_len(values.len()).ok_or_else(|| {
+            anyhow!(
+                "Property `{}` has {} components, expected 1 to 4",
+                def.name,
+                values.len()
+            )
+        })?;
+        let mut default = [0.0; 4];
+        default[..values.len()].copy_from_slice(&values);
+        Ok(Self::new(&def.name, ty, default))
+    }
+
+    fn rust_default(&self) -> String {
+        let [x, y, z, w] = self.default;
+        match self.ty {
+            PropertyType::F32 => format!("{x:?}"),
+            PropertyType::Vec2 => format!("Vec2::new({x:?}, {y:?})"),
+            PropertyType::Vec3 => format!("Vec3::new({x:?}, {y:?}, {z:?})"),
+            PropertyType::Vec4 => format!("Vec4::new({x:?}, {y:?}, {z:?}, {w:?})"),
+        }
+    }
+}
+
+/// Read the `properties` list of a graph document.
+pub fn document_properties(doc: &Value) -> Result<Vec<ExportProperty>> {
+    let props = match doc.get("properties") {
+        None | Some(Value::Null) => return Ok(Vec::new()),
+        Some(props) => props,
+    };
+    let defs: Vec<PropertyDef> = serde_json::from_value(props.clone())
+        .map_err(|err| anyhow!("Invalid graph properties: {err}"))?;
+    defs.into_iter().map(ExportProperty::from_def).collect()
+}
+
+fn to_snake_case(name: &str) -> String {
+    let mut out = String::new();
+    let mut prev_lower = false;
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() {
+            if c.is_ascii_uppercase() && prev_lower {
+                out.push('_');
+            }
+            prev_lower = c.is_ascii_lowercase() || c.is_ascii_digit();
+            out.push(c.to_ascii_lowercase());
+        } else if !out.is_empty() && !out.ends_with('_') {
+            out.push('_');
+            prev_lower = false;
+        }
+    }
+    let out = out.trim_end_matches('_').to_string();
+    match out.chars().next() {
+        None => "prop".to_string(),
+        Some(c) if c.is_ascii_digit() => format!("prop_{out}"),
+        Some(_) => out,
+    }
+}
+
+/// Shader modules of this crate, by import path.  Exported shaders get them
+/// inlined, the exported material doesn't register them.
+const SHADER_MODULES: &[(&str, &str)] = &[
+    (
+        "bevy_shader_graph::pbr_input",
+        include_str!("shaders/pbr_input.wgsl"),
+    ),
+    (
+        "bevy_shader_graph::lighting",
+        include_str!("shaders/lighting.wgsl"),
+    ),
+];
+
+/// Replace the `#import bevy_shader_graph::*` lines with the imported modules.
+fn inline_shader_modules(code: &str) -> Result<String> {
+    let mut modules = Vec::new();
+    let mut out = String::new();
+    for line in code.split_inclusive('\n') {
+        let Some(path) = line.trim().strip_prefix("#import bevy_shader_graph::") else {
+            out.push_str(line);
+            continue;
+        };
+        let name = path.split("::").next().unwrap_or_default();
+        let import_path = format!("bevy_shader_graph::{name}");
+        let (_, source) = SHADER_MODULES
+            .iter()
+            .find(|(module, _)| *module == import_path)
+            .ok_or_else(|| anyhow!("`{import_path}` can't be used in an exported material"))?;
+        if !modules.contains(source) {
+            modules.push(*source);
+        }
+    }
+    let mut inlined = String::new();
+    for source in modules {
+        inlined.push('\n');
+        for line in source.lines() {
+            if !line.starts_with("#define_import_path") {
+                inlined.push_str(line);
+                inlined.push('\n');
+            }
+        }
+    }
+    out.insert_str(import_block_end(&out), &inlined);
+    Ok(out)
+}
+
+/// Byte offset after the leading `#import` lines (and their `{ ... }` lists).
+fn import_block_end(code: &str) -> usize {
+    let mut end = 0;
+    let mut offset = 0;
+    let mut in_list = false;
+    for line in code.split_inclusive('\n') {
+        offset += line.len();
+        let trimmed = line.trim();
+        if in_list {
+            in_list = !trimmed.starts_with('}');
+            end = offset;
+        } else if trimmed.starts_with("#import") {
+            in_list = trimmed.ends_with('{');
+            end = offset;
+        } else if !trimmed.is_empty() && !trimmed.starts_with('#') {
+            break;
+        }
+    }
+    end
+}
+
+fn to_pascal_case(name: &str) -> String {
+    to_snake_case(name)
+        .split('_')
+        .map(|part| {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(c) => c.to_ascii_uppercase().to_string() + chars.as_str(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+/// The exported WGSL shader and Rust material module.
+#[derive(Clone, Debug)]
+pub struct ExportedMaterial {
+    pub wgsl: String,
+    pub rust: String,
+}
+
+/// Export a shader graph as a standalone material.
+///
+/// The generated Rust module only depends on Bevy and the shader modules of this
+/// crate are inlined into the WGSL, so production code doesn't need `node_engine`
+/// (or this crate) at runtime.
+#[derive(Clone, Debug)]
+pub struct ShaderGraphExport {
+    /// Material type name, e.g. `WallMaterial`.
+    pub name: String,
+    /// Asset path of the exported `.wgsl` file.
+    pub shader_path: String,
+    /// Uniform binding used for the material properties.
+    pub binding: u32,
+}
+
+impl ShaderGraphExport {
+    pub fn new(name: &str) -> Self {
+        let name = to_pascal_case(name);
+        let shader_path = format!("shaders/{}.wgsl", to_snake_case(&name));
+        Self {
+            name,
+            shader_path,
+            binding: 100,
+        }
+    }
+
+    pub fn with_shader_path(mut self, path: &str) -> Self {
+        self.shader_path = path.to_string();
+        self
+    }
+
+    pub fn properties(&self, graph: &NodeGraph) -> Result<Vec<ExportProperty>> {
+        document_properties(&serde_json::to_value(graph)?)
+    }
+
+    pub fn export(&self, graph: &NodeGraph) -> Result<ExportedMaterial> {
+        let code = compile_graph(graph).into_result()?;
+        let props = self.properties(graph)?;
+        self.export_code(&code, &props)
+    }
+
+    /// Build the exported files from compiled code and the graph's properties.
+    pub fn export_code(&self, code: &str, props: &[ExportProperty]) -> Result<ExportedMaterial> {
+        Ok(ExportedMaterial {
+            wgsl: inline_shader_modules(&self.generate_wgsl(code, props))?,
+            rust: self.generate_rust(props),
+        })
+    }
+
+    /// Export the graph and write `<dir>/<shader_path>` and `<dir>/<module>.rs`.
+    pub fn write<P: AsRef<Path>>(&self, graph: &NodeGraph, dir: P) -> Result<()> {
+        let dir = dir.as_ref();
+        let exported = self.export(graph)?;
+        let shader_file = dir.join(&self.shader_path);
+        if let Some(parent) = shader_file.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(shader_file, exported.wgsl)?;
+        fs::write(
+            dir.join(format!("{}.rs", to_snake_case(&self.name))),
+            exported.rust,
+        )?;
+        Ok(())
+    }
+
+    /// Name of the WGSL uniform with the properties.
+    fn uniform_var(&self) -> String {
+        format!("{}_properties", to_snake_case(&self.name))
+    }
+
+    /// WGSL declaration of the properties uniform, with the same fields (in the
+    /// same order) as the generated Rust uniform struct.
+    pub fn uniform_wgsl(&self, props: &[ExportProperty]) -> String {
+        if props.is_empty() {
+            return String::new();
+        }
+        let name = &self.name;
+        let mut out = String::new();
+        let _ = writeln!(out, "struct {name}Uniform {{");
+        for prop in props {
+            let _ = writeln!(out, "    {}: {},", prop.field, prop.ty.wgsl_type());
+        }
+        let _ = writeln!(out, "}};");
+        let _ = writeln!(
+            out,
+            "@group(#{{MATERIAL_BIND_GROUP}}) @binding({}) var<uniform> {}: {name}Uniform;",
+            self.binding,
+            self.uniform_var()
+        );
+        out
+    }
+
+    /// Replace the graph's own declarations at the properties binding (e.g. `prop4`)
+    /// with the generated uniform.
+    fn generate_wgsl(&self, code: &str, props: &[ExportProperty]) -> String {
+        let binding = format!("@binding({})", self.binding);
+        let mut out = String::new();
+        let mut uniform = Some(self.uniform_wgsl(props));
+        for line in code.lines() {
+            if line.contains(&binding) {
+                out.push_str(&uniform.take().unwrap_or_default());
+                continue;
+            }
+            out.push_str(line);
+            out.push('\n');
+        }
+        if let Some(uniform) = uniform.filter(|uniform| !uniform.is_empty()) {
+            // Nothing to replace, declare it after the imports.
+            let imports_end = import_block_end(&out);
+            out.insert_str(imports_end, &format!("\n{uniform}"));
+        }
+        out
+    }
+
+    fn generate_rust(&self, props: &[ExportProperty]) -> String {
+        let name = &self.name;
+        let uniform = format!("{name}Uniform");
+        let mut out = String::new();
+        // `write!` to a `String` can't fail.
+        let _ = writeln!(
+            out,
+            "//! Generated by `bevy_shader_graph` from the `{name}` graph.  Do not edit.\n"
+        );
+        let render = if props.is_empty() {
+            "render::render_resource::*"
+        } else {
+            "render::{render_asset::RenderAssets, render_resource::*, texture::GpuImage}"
+        };
+        let _ = writeln!(
+            out,
+            "use bevy::{{
+    pbr::{{ExtendedMaterial, MaterialExtension, MaterialPlugin}},
+    prelude::*,
+    {render},
+    shader::ShaderRef,
+}};
+"
+        );
+        let _ = writeln!(
+            out,
+            "pub type Standard{name} = ExtendedMaterial<StandardMaterial, {name}>;\n"
+        );
+
+        if !props.is_empty() {
+            let _ = writeln!(out, "#[derive(Clone, Default, ShaderType)]");
+            let _ = writeln!(out, "pub struct {uniform} {{");
+            for prop in props {
+                let _ = writeln!(out, "    pub {}: {},", prop.field, prop.ty.rust_type());
+            }
+            let _ = writeln!(out, "}}\n");
+        }
+
+        let _ = writeln!(out, "#[derive(Asset, AsBindGroup, Reflect, Debug, Clone)]");
+        if !props.is_empty() {
+            let _ = writeln!(out, "#[uniform({}, {uniform})]", self.binding);
+        }
+        let _ = writeln!(out, "#[reflect(Default, Debug)]");
+        let _ = writeln!(out, "pub struct {name} {{");
+        for prop in props {
+            let _ = writeln!(out, "    /// Graph property `{}`.", prop.name);
+            let _ = writeln!(out, "    pub {}: {},", prop.field, prop.ty.rust_type());
+        }
+        let _ = writeln!(out, "}}\n");
+
+        let _ = writeln!(out, "impl Default for {name} {{");
+        let _ = writeln!(out, "    fn default() -> Self {{");
+        let _ = writeln!(out, "        Self {{");
+        for prop in props {
+            let _ = writeln!(out, "            {}: {},", prop.field, prop.rust_default());
+        }
+        let _ = writeln!(out, "        }}");
+        let _ = writeln!(out, "    }}");
+        let _ = writeln!(out, "}}\n");
+
+        if !props.is_empty() {
+            let _ = writeln!(out, "impl AsBindGroupShaderType<{uniform}> for {name} {{");
+            let _ = writeln!(
+                out,
+                "    fn as_bind_group_shader_type(&self, _images: &RenderAssets<GpuImage>) -> {uniform} {{"
+            );
+            let _ = writeln!(out, "        {uniform} {{");
+            for prop in props {
+                let _ = writeln!(out, "            {0}: self.{0},", prop.field);
+            }
+            let _ = writeln!(out, "        }}");
+            let _ = writeln!(out, "    }}");
+            let _ = writeln!(out, "}}\n");
+        }
+
+        let _ = writeln!(out, "impl MaterialExtension for {name} {{");
+        let _ = writeln!(out, "    fn fragment_shader() -> ShaderRef {{");
+        let _ = writeln!(out, "        \"{}\".into()", self.shader_path);
+        let _ = writeln!(out, "    }}");
+        let _ = writeln!(out, "}}\n");
+
+        let _ = writeln!(out, "#[derive(Default, Clone, Debug)]");
+        let _ = writeln!(out, "pub struct {name}Plugin;\n");
+        let _ = writeln!(out, "impl Plugin for {name}Plugin {{");
+        let _ = writeln!(out, "    fn build(&self, app: &mut App) {{");
+        let _ = writeln!(
+            out,
+            "        app.add_plugins(MaterialPlugin::<Standard{name}>::default());"
+        );
+        let _ = writeln!(out, "    }}");
+        let _ = writeln!(out, "}}");
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{graph_from_value, graph_to_value, MasterMode};
+
+    /// `(field, type)` pairs of `struct <name> { ... }` in generated code.
+    fn struct_fields(code: &str, name: &str) -> Vec<(String, String)> {
+        let start = code
+            .find(&format!("struct {name} {{"))
+            .unwrap_or_else(|| panic!("missing struct {name} in:\n{code}"));
+        let body = &code[start..];
+        let body = &body[body.find('{').unwrap() + 1..body.find('}').unwrap()];
+        body.lines()
+            .filter_map(|line| {
+                let line = line.trim().trim_end_matches(',');
+                let line = line.strip_prefix("pub ").unwrap_or(line);
+                let (field, ty) = line.split_once(':')?;
+                Some((field.trim().to_string(), ty.trim().to_string()))
+            })
+            .collect()
+    }
+
+    fn wgsl_to_rust(ty: &str) -> &str {
+        match ty {
+            "vec2<f32>" => "Vec2",
+            "vec3<f32>" => "Vec3",
+            "vec4<f32>" => "Vec4",
+            ty => ty,
+        }
+    }
+
+    #[test]
+    fn uniform_layout_round_trip() {
+        let doc = serde_json::json!({
+            "properties": [
+                { "name": "Tint Color", "value": [1.0, 0.5, 0.25, 1.0] },
+                { "name": "Speed", "value": 2.0 },
+                { "name": "Offset", "value": [0.0, 1.0] },
+            ],
+        });
+        let props = document_properties(&doc).unwrap();
+        assert_eq!(
+            props.iter().map(|p| p.ty).collect::<Vec<_>>(),
+            [PropertyType::Vec4, PropertyType::F32, PropertyType::Vec2]
+        );
+
+        let export = ShaderGraphExport::new("wall");
+        let code = "#import bevy_pbr::forward_io::VertexOutput\n\
+            @group(2) @binding(100) var<uniform> prop4: vec4<f32>;\n\
+            @fragment\nfn fragment(in: VertexOutput) -> @location(0) vec4<f32> {\n}\n";
+        let exported = export.export_code(code, &props).unwrap();
+
+        assert!(!exported.wgsl.contains("prop4"));
+        assert_eq!(exported.wgsl.matches("@binding(100)").count(), 1);
+        let wgsl = struct_fields(&exported.wgsl, "WallUniform");
+        let rust = struct_fields(&exported.rust, "WallUniform");
+        assert_eq!(wgsl.len(), 3);
+        assert_eq!(
+            wgsl.iter()
+                .map(|(f, ty)| (f.as_str(), wgsl_to_rust(ty)))
+                .collect::<Vec<_>>(),
+            rust.iter()
+                .map(|(f, ty)| (f.as_str(), ty.as_str()))
+                .collect::<Vec<_>>()
+        );
+        assert!(exported.rust.contains("#[uniform(100, WallUniform)]"));
+    }
+
+    #[test]
+    fn uniform_declared_after_imports() {
+        let export = ShaderGraphExport::new("wall");
+        let props = [ExportProperty::new("Speed", PropertyType::F32, [1.0; 4])];
+        let code =
+            "#import bevy_pbr::{\n\tforward_io::VertexOutput,\n}\n@fragment\nfn fragment() {}\n";
+        let wgsl = export.export_code(code, &props).unwrap().wgsl;
+        let uniform = wgsl.find("struct WallUniform").unwrap();
+        assert!(uniform > wgsl.find("}\n").unwrap());
+        assert!(uniform < wgsl.find("@fragment").unwrap());
+    }
+
+    #[test]
+    fn no_properties() {
+        let export = ShaderGraphExport::new("wall");
+        let exported = export
+            .export_code("@fragment\nfn fragment() {}\n", &[])
+            .unwrap();
+        assert!(!exported.wgsl.contains("WallUniform"));
+        assert!(!exported.rust.contains("WallUniform"));
+        assert!(!exported.rust.contains("GpuImage"));
+    }
+
+    #[test]
+    fn graph_properties() {
+        let mut doc = graph_to_value(&MasterMode::Unlit.new_graph().unwrap()).unwrap();
+        doc["properties"] = serde_json::json!([
+            {
+                "id": "3f0e4c1a-8a51-4bde-9b3e-6c0f5a1d2e7b",
+                "name": "Tint Color",
+                "description": "",
+                "value": { "Vec4": [1.0, 0.5, 0.25, 1.0] },
+            },
+            {
+                "id": "9b1d7e42-0c6a-4f8e-a3d5-2e4b6c8f0a1d",
+                "name": "Speed",
+                "description": "",
+                "value": { "F32": 2.0 },
+            },
+        ]);
+        let graph = graph_from_value(doc).unwrap();
+
+        let props = ShaderGraphExport::new("wall").properties(&graph).unwrap();
+        assert_eq!(
+            props,
+            [
+                ExportProperty::new("Tint Color", PropertyType::Vec4, [1.0, 0.5, 0.25, 1.0]),
+                ExportProperty::new("Speed", PropertyType::F32, [2.0, 0.0, 0.0, 0.0]),
+            ]
+        );
+    }
+
+    #[test]
+    fn inlines_shader_modules() {
+        let export = ShaderGraphExport::new("wall");
+        let graph = MasterMode::CustomLighting.new_graph().unwrap();
+        let wgsl = export.export(&graph).unwrap().wgsl;
+        assert!(!wgsl.contains("#import bevy_shader_graph::"));
+        assert!(!wgsl.contains("#define_import_path"));
+        assert_eq!(wgsl.matches("fn graph_pbr_input(").count(), 1);
+        assert!(wgsl.find("fn graph_pbr_input(").unwrap() < wgsl.find("@fragment").unwrap());
+
+        let code = "#import bevy_shader_graph::post_process::screen_color\n";
+        assert!(export.export_code(code, &[]).is_err());
+    }
+
+    #[test]
+    fn invalid_properties() {
+        let doc = serde_json::json!({ "properties": [{ "name": "Big", "value": vec![0.0; 5] }] });
+        assert!(document_properties(&doc).is_err());
+        let doc = serde_json::json!({ "properties": { "Speed": 1.0 } });
+        assert!(document_properties(&doc).is_err());
+    }
+}
@@ -220,7 +220,7 @@ impl<B: Material, E: MaterialExtension> AsBindGroup for ExtendedMaterial<B, E> {
         let mut seen_bindings = HashSet::<_>::with_hasher(FixedHasher);
         for entry in B::bind_group_layout_entries(render_device, force_non_bindless)
             .into_iter()
-            .chain(E::bind_group_layout_entries(render_device, force_non_bindless).into_iter())
+            .chain(E::bind_group_layout_entries(render_device, force_non_bindless))
         {
             if seen_bindings.insert(entry.binding) {
                 entries.push(entry);
@@ -0,0 +1,686 @@
+//! Import Blender material node trees.
+//!
+//! Blender has no standard node tree export, this reads a JSON dump of
+//! `material.node_tree` with this layout:
+//!
+//! ```json
+//! {
+//!   "nodes": [{
+//!     "name": "Principled BSDF",
+//!     "type": "BSDF_PRINCIPLED",
+//!     "location": [10.0, 300.0],
+//!     "properties": { "operation": "MULTIPLY" },
+//!     "inputs": [{ "name": "Base Color", "identifier": "Base Color", "default_value": [0.8, 0.8, 0.8, 1.0] }],
+//!     "outputs": [{ "name": "BSDF", "identifier": "BSDF" }]
+//!   }],
+//!   "links": [{ "from_node": "Image Texture", "from_socket": "Color", "to_node": "Principled BSDF", "to_socket": "Base Color" }]
+//! }
+//! ```
+//!
+//! Sockets are matched by `identifier`, falling back to `name`.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use bevy::math::Vec2;
+use serde::Deserialize;
+use serde_json::Value;
+
+use node_engine::NodeGraph;
+
+use super::{GraphBuilder, ImportReport};
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct BlenderNodeTree {
+    pub nodes: Vec<BlenderNode>,
+    pub links: Vec<BlenderLink>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct BlenderNode {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub ty: String,
+    pub location: [f32; 2],
+    pub properties: HashMap<String, Value>,
+    pub inputs: Vec<BlenderSocket>,
+    pub outputs: Vec<BlenderSocket>,
+}
+
+impl BlenderNode {
+    fn property(&self, name: &str) -> Option<&str> {
+        self.properties.get(name).and_then(Value::as_str)
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct BlenderSocket {
+    pub name: String,
+    pub identifier: Option<String>,
+    pub default_value: Option<Value>,
+}
+
+impl BlenderSocket {
+    fn id(&self) -> &str {
+        self.identifier.as_deref().unwrap_or(&self.name)
+    }
+
+    fn values(&self) -> Vec<f32> {
+        match &self.default_value {
+            Some(Value::Number(num)) => vec![num.as_f64().unwrap_or_default() as f32],
+            Some(Value::Array(arr)) => arr
+                .iter()
+                .filter_map(Value::as_f64)
+                .map(|v| v as f32)
+                .collect(),
+            _ => vec![],
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct BlenderLink {
+    pub from_node: String,
+    pub from_socket: String,
+    pub to_node: String,
+    pub to_socket: String,
+}
+
+/// Principled BSDF inputs -> `PBR Fragment` inputs.
+const PRINCIPLED_INPUTS: &[(&str, &str)] = &[
+    ("Base Color", "color"),
+    ("Metallic", "metallic"),
+    ("Roughness", "roughness"),
+    ("Alpha", "alpha"),
+    ("Normal", "normal"),
+    // Blender 3.x.
+    ("Emission", "emission"),
+    ("Emission Color", "emission"),
+];
+
+/// Unsupported Principled BSDF inputs and the value at which they have no effect.
+/// Other unsupported inputs only matter when one of these is set.
+const PRINCIPLED_NEUTRAL: &[(&str, f32)] = &[
+    ("Subsurface Weight", 0.0),
+    ("Subsurface", 0.0),
+    ("Specular IOR Level", 0.5),
+    ("Specular", 0.5),
+    ("Anisotropic", 0.0),
+    ("Transmission Weight", 0.0),
+    ("Transmission", 0.0),
+    ("Coat Weight", 0.0),
+    ("Clearcoat", 0.0),
+    ("Sheen Weight", 0.0),
+    ("Sheen", 0.0),
+    ("Thin Film Thickness", 0.0),
+];
+
+/// Where the sockets of a Blender node ended up.
+#[derive(Default)]
+struct Imported {
+    /// Socket -> (node id, input name).
+    inputs: HashMap<String, (String, &'static str)>,
+    /// Socket -> (node id, output index).
+    outputs: HashMap<String, (String, u32)>,
+    /// Socket -> constant value, for inlined value nodes.
+    constants: HashMap<String, Vec<f32>>,
+}
+
+/// Import a JSON dump of a Blender material node tree.
+pub fn import_blender<P: AsRef<Path>>(path: P) -> Result<(NodeGraph, ImportReport)> {
+    let file = std::fs::File::open(path)?;
+    let tree: BlenderNodeTree = serde_json::from_reader(file)?;
+    import_blender_tree(&tree)
+}
+
+/// Import a Blender material node tree.
+pub fn import_blender_tree(tree: &BlenderNodeTree) -> Result<(NodeGraph, ImportReport)> {
+    let mut builder = GraphBuilder::default();
+    let mut imported: HashMap<&str, Imported> = HashMap::new();
+
+    let principled = tree
+        .nodes
+        .iter()
+        .find(|n| n.ty == "BSDF_PRINCIPLED")
+        .ok_or_else(|| anyhow!("No Principled BSDF node found"))?;
+    let node_types: HashMap<&str, &str> = tree
+        .nodes
+        .iter()
+        .map(|n| (n.name.as_str(), n.ty.as_str()))
+        .collect();
+
+    for node in &tree.nodes {
+        // Blender's Y axis points up.
+        let pos = Vec2::new(node.location[0], -node.location[1]);
+        let result = match node.ty.as_str() {
+            "BSDF_PRINCIPLED" => import_principled(&mut builder, node, pos, &tree.links),
+            // The BSDF is the output.
+            "OUTPUT_MATERIAL" => Ok(Some(Imported::default())),
+            "VALUE" | "RGB" => Ok(Some(Imported {
+                constants: node
+                    .outputs
+                    .iter()
+                    .map(|s| (s.id().to_string(), s.values()))
+                    .collect(),
+                ..Default::default()
+            })),
+            "MATH" | "VECT_MATH" => import_math(&mut builder, node, pos),
+            "MIX" | "MIX_RGB" => import_mix(&mut builder, node, pos),
+            "MAPPING" => import_mapping(&mut builder, node, pos),
+            "TEX_IMAGE" => import_image(&mut builder, node, pos),
+            "VALTORGB" => import_color_ramp(&mut builder, node, pos),
+            "NORMAL_MAP" => import_normal_map(&mut builder, node, pos),
+            "TEX_COORD" | "UVMAP" => {
+                let id = builder.add_node("Uv", pos)?;
+                let mut imported = Imported::default();
+                imported.outputs.insert("UV".into(), (id, 0));
+                Ok(Some(imported))
+            }
+            _ => Ok(None),
+        };
+        match result {
+            Ok(Some(node_imported)) => {
+                imported.insert(node.name.as_str(), node_imported);
+            }
+            Ok(None) => {
+                builder.add_placeholder(&format!("{} ({})", node.ty, node.name), pos);
+            }
+            Err(err) => {
+                builder.add_placeholder(&format!("{} ({}): {err}", node.ty, node.name), pos);
+            }
+        }
+    }
+
+    for link in &tree.links {
+        let (Some(from), Some(to)) = (
+            imported.get(link.from_node.as_str()),
+            imported.get(link.to_node.as_str()),
+        ) else {
+            builder.warn(format!(
+                "Dropped link {}.{} -> {}.{}",
+                link.from_node, link.from_socket, link.to_node, link.to_socket
+            ));
+            continue;
+        };
+        if link.to_node == principled.name
+            && !PRINCIPLED_INPUTS
+                .iter()
+                .any(|(socket, _)| *socket == link.to_socket)
+        {
+            builder.warn(format!(
+                "Principled BSDF input `{}` is not supported",
+                link.to_socket
+            ));
+            continue;
+        }
+        // The `normal` input is a tangent space normal.
+        if link.to_node == principled.name
+            && link.to_socket == "Normal"
+            && node_types.get(link.from_node.as_str()) != Some(&"NORMAL_MAP")
+        {
+            builder.warn(format!(
+                "Principled BSDF `Normal` from `{}` is not a tangent space normal map",
+                link.from_node
+            ));
+            continue;
+        }
+        let Some((to_id, input)) = to.inputs.get(&link.to_socket) else {
+            if !to.inputs.is_empty() || !to.outputs.is_empty() {
+                builder.warn(format!(
+                    "`{}` input `{}` is not supported",
+                    link.to_node, link.to_socket
+                ));
+            }
+            continue;
+        };
+        if let Some(value) = from.constants.get(&link.from_socket) {
+            builder.set_input(to_id, input, value);
+            continue;
+        }
+        let Some((from_id, idx)) = from.outputs.get(&link.from_socket) else {
+            builder.warn(format!(
+                "`{}` output `{}` is not supported",
+                link.from_node, link.from_socket
+            ));
+            continue;
+        };
+        if let Err(err) = builder.connect(from_id, *idx, to_id, input) {
+            builder.warn(format!("{err}"));
+        }
+    }
+
+    builder.finish()
+}
+
+/// Add a node and copy the default values of the mapped sockets.
+fn add_mapped(
+    builder: &mut GraphBuilder,
+    node: &BlenderNode,
+    name: &str,
+    pos: Vec2,
+    inputs: &[(&str, &'static str)],
+    outputs: &[(&str, u32)],
+) -> Result<Imported> {
+    let id = builder.add_node(name, pos)?;
+    let mut imported = Imported::default();
+    for socket in &node.inputs {
+        let Some((_, input)) = inputs.iter().find(|(s, _)| *s == socket.id()) else {
+            continue;
+        };
+        let values = socket.values();
+        if !values.is_empty() && builder.has_field(&id, input) {
+            builder.set_input(&id, input, &values);
+        }
+        imported
+            .inputs
+            .insert(socket.id().to_string(), (id.clone(), *input));
+    }
+    for (socket, idx) in outputs {
+        imported
+            .outputs
+            .insert(socket.to_string(), (id.clone(), *idx));
+    }
+    Ok(imported)
+}
+
+fn import_principled(
+    builder: &mut GraphBuilder,
+    node: &BlenderNode,
+    pos: Vec2,
+    links: &[BlenderLink],
+) -> Result<Option<Imported>> {
+    let id = builder.add_output("Pbr Fragment", pos)?;
+    let mut imported = Imported::default();
+    let is_linked = |socket: &str| {
+        links
+            .iter()
+            .any(|link| link.to_node == node.name && link.to_socket == socket)
+    };
+    // Blender's emission is `Emission Color * Emission Strength`.
+    let strength = node
+        .inputs
+        .iter()
+        .find(|s| s.id() == "Emission Strength")
+        .and_then(|s| s.values().first().copied())
+        .unwrap_or(1.0);
+    for socket in &node.inputs {
+        let Some((_, input)) = PRINCIPLED_INPUTS.iter().find(|(s, _)| *s == socket.id()) else {
+            continue;
+        };
+        let mut target = (id.clone(), *input);
+        if is_linked(socket.id()) {
+            if *input == "emission" && strength != 1.0 {
+                let scale = builder.add_node("Multiply", pos - Vec2::new(250.0, 0.0))?;
+                builder.set_input(&scale, "b", &[strength]);
+                builder.connect(&scale, 0, &id, "emission")?;
+                target = (scale, "a");
+            }
+        } else if *input == "emission" {
+            let values: Vec<f32> = socket.values().iter().map(|v| v * strength).collect();
+            builder.set_input(&id, input, &values);
+        } else if *input != "normal" {
+            // The unconnected `Normal` is the surface normal, the default.
+            let values = socket.values();
+            if !values.is_empty() {
+                builder.set_input(&id, input, &values);
+            }
+        }
+        imported.inputs.insert(socket.id().to_string(), target);
+    }
+    // Linked inputs are reported with the links.
+    for socket in &node.inputs {
+        let Some((_, neutral)) = PRINCIPLED_NEUTRAL.iter().find(|(s, _)| *s == socket.id()) else {
+            continue;
+        };
+        let value = socket.values().first().copied().unwrap_or(*neutral);
+        if value != *neutral && !is_linked(socket.id()) {
+            builder.warn(format!(
+                "Principled BSDF input `{}` ({value}) is not supported",
+                socket.id()
+            ));
+        }
+    }
+    Ok(Some(imported))
+}
+
+/// Tangent space normal maps decode the color with `color * 2 - 1`.
+fn import_normal_map(
+    builder: &mut GraphBuilder,
+    node: &BlenderNode,
+    pos: Vec2,
+) -> Result<Option<Imported>> {
+    if node.property("space").unwrap_or("TANGENT") != "TANGENT" {
+        return Ok(None);
+    }
+    let strength = node.inputs.iter().find(|s| s.id() == "Strength");
+    if strength.is_some_and(|s| s.values().first().is_some_and(|v| *v != 1.0)) {
+        builder.warn(format!(
+            "`{}`: normal map strength is not supported",
+            node.name
+        ));
+    }
+    let decode = builder.add_node("Lerp", pos)?;
+    builder.set_input(&decode, "a", &[-1.0, -1.0, -1.0]);
+    builder.set_input(&decode, "b", &[1.0, 1.0, 1.0]);
+    let mut imported = Imported::default();
+    imported
+        .inputs
+        .insert("Color".into(), (decode.clone(), "t"));
+    imported.outputs.insert("Normal".into(), (decode, 0));
+    Ok(Some(imported))
+}
+
+fn import_math(
+    builder: &mut GraphBuilder,
+    node: &BlenderNode,
+    pos: Vec2,
+) -> Result<Option<Imported>> {
+    let name = match node.property("operation").unwrap_or("ADD") {
+        "ADD" => "Add",
+        "SUBTRACT" => "Subtract",
+        "MULTIPLY" => "Multiply",
+        "DIVIDE" => "Divide",
+        "MINIMUM" => "Min",
+        "MAXIMUM" => "Max",
+        "ABSOLUTE" => "Absolute",
+        "FRACT" | "FRACTION" => "Fraction",
+        _ => return Ok(None),
+    };
+    let (a, b, out) = if node.ty == "MATH" {
+        ("Value", "Value_001", "Value")
+    } else {
+        ("Vector", "Vector_001", "Vector")
+    };
+    let inputs: &[(&str, &'static str)] = match name {
+        "Absolute" | "Fraction" => &[(a, "a")],
+        _ => &[(a, "a"), (b, "b")],
+    };
+    Ok(Some(add_mapped(
+        builder,
+        node,
+        name,
+        pos,
+        inputs,
+        &[(out, 0)],
+    )?))
+}
+
+fn import_mix(
+    builder: &mut GraphBuilder,
+    node: &BlenderNode,
+    pos: Vec2,
+) -> Result<Option<Imported>> {
+    if node.property("blend_type").unwrap_or("MIX") != "MIX" {
+        return Ok(None);
+    }
+    let mut inputs = Vec::new();
+    let mut outputs = Vec::new();
+    for socket in &node.inputs {
+        let id = socket.id();
+        let input = match id.split('_').next().unwrap_or_default() {
+            "Fac" | "Factor" => "t",
+            "A" | "Color1" => "a",
+            "B" | "Color2" => "b",
+            _ => continue,
+        };
+        inputs.push((id, input));
+    }
+    for socket in &node.outputs {
+        outputs.push((socket.id(), 0));
+    }
+    if outputs.is_empty() {
+        outputs.push(("Color", 0));
+    }
+    Ok(Some(add_mapped(
+        builder, node, "Lerp", pos, &inputs, &outputs,
+    )?))
+}
+
+fn import_mapping(
+    builder: &mut GraphBuilder,
+    node: &BlenderNode,
+    pos: Vec2,
+) -> Result<Option<Imported>> {
+    if let Some(rotation) = node.inputs.iter().find(|s| s.id() == "Rotation") {
+        if rotation.values().iter().any(|v| *v != 0.0) {
+            builder.warn(format!(
+                "`{}`: mapping rotation is not supported",
+                node.name
+            ));
+        }
+    }
+    let inputs = [
+        ("Vector", "uv"),
+        ("Location", "offset"),
+        ("Scale", "tiling"),
+    ];
+    Ok(Some(add_mapped(
+        builder,
+        node,
+        "Tiling And Offset",
+        pos,
+        &inputs,
+        &[("Vector", 0)],
+    )?))
+}
+
+fn import_image(
+    builder: &mut GraphBuilder,
+    node: &BlenderNode,
+    pos: Vec2,
+) -> Result<Option<Imported>> {
+    if let Some(image) = node.property("image") {
+        builder.warn(format!(
+            "`{}`: assign the texture `{image}` in the editor",
+            node.name
+        ));
+    }
+    Ok(Some(add_mapped(
+        builder,
+        node,
+        "Texture Sample",
+        pos,
+        &[("Vector", "uv")],
+        &[("Color", 0)],
+    )?))
+}
+
+/// Two-stop color ramps become a `Lerp`, with a remap of the factor when the
+/// stops aren't at 0 and 1.
+fn import_color_ramp(
+    builder: &mut GraphBuilder,
+    node: &BlenderNode,
+    pos: Vec2,
+) -> Result<Option<Imported>> {
+    let stops: Vec<(f32, Vec<f32>)> = node
+        .properties
+        .get("elements")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .map(|stop| {
+            let position = stop["position"].as_f64().unwrap_or_default() as f32;
+            let color = stop["color"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_f64)
+                .map(|v| v as f32)
+                .collect();
+            (position, color)
+        })
+        .collect();
+    let [(p0, c0), (p1, c1)] = stops.as_slice() else {
+        return Ok(None);
+    };
+    if node.property("interpolation").unwrap_or("LINEAR") != "LINEAR" {
+        builder.warn(format!(
+            "`{}`: only linear color ramps are supported",
+            node.name
+        ));
+    }
+
+    let lerp = builder.add_node("Lerp", pos)?;
+    builder.set_input(&lerp, "a", c0);
+    builder.set_input(&lerp, "b", c1);
+
+    let mut imported = Imported::default();
+    imported.outputs.insert("Color".into(), (lerp.clone(), 0));
+    if *p0 == 0.0 && *p1 == 1.0 {
+        imported.inputs.insert("Fac".into(), (lerp, "t"));
+        return Ok(Some(imported));
+    }
+
+    // t = clamp((fac - p0) / (p1 - p0), 0, 1)
+    let offset = Vec2::new(-250.0, 0.0);
+    let sub = builder.add_node("Subtract", pos + offset * 3.0)?;
+    builder.set_input(&sub, "b", &[*p0]);
+    let div = builder.add_node("Divide", pos + offset * 2.0)?;
+    builder.set_input(&div, "b", &[(p1 - p0).max(f32::EPSILON)]);
+    let clamp = builder.add_node("Clamp", pos + offset)?;
+    builder.set_input(&clamp, "min", &[0.0]);
+    builder.set_input(&clamp, "max", &[1.0]);
+    builder.connect(&sub, 0, &div, "a")?;
+    builder.connect(&div, 0, &clamp, "input")?;
+    builder.connect(&clamp, 0, &lerp, "t")?;
+    imported.inputs.insert("Fac".into(), (sub, "a"));
+    Ok(Some(imported))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::document::{graph_to_value, input_source, node_id, value_floats};
+
+    fn import(tree: Value) -> (Value, ImportReport) {
+        let tree: BlenderNodeTree = serde_json::from_value(tree).unwrap();
+        let (graph, report) = import_blender_tree(&tree).unwrap();
+        (graph_to_value(&graph).unwrap(), report)
+    }
+
+    fn find<'a>(doc: &'a Value, name: &str) -> &'a Value {
+        doc["nodes"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|node| node["name"] == name)
+            .unwrap_or_else(|| panic!("no `{name}` node"))
+    }
+
+    fn socket(name: &str, value: Value) -> Value {
+        json!({ "name": name, "default_value": value })
+    }
+
+    fn link(from: &str, from_socket: &str, to: &str, to_socket: &str) -> Value {
+        json!({ "from_node": from, "from_socket": from_socket, "to_node": to, "to_socket": to_socket })
+    }
+
+    fn principled(inputs: Vec<Value>) -> Value {
+        json!({ "name": "Principled BSDF", "type": "BSDF_PRINCIPLED", "inputs": inputs })
+    }
+
+    #[test]
+    fn principled_inputs() {
+        let (doc, report) = import(json!({
+            "nodes": [
+                principled(vec![
+                    socket("Base Color", json!([0.8, 0.8, 0.8, 1.0])),
+                    socket("Metallic", json!(0.25)),
+                    socket("Roughness", json!(0.75)),
+                    socket("Alpha", json!(0.5)),
+                    socket("Normal", json!([0.0, 0.0, 0.0])),
+                    socket("Emission Color", json!([1.0, 0.5, 0.0, 1.0])),
+                    socket("Emission Strength", json!(2.0)),
+                    socket("Coat Weight", json!(0.0)),
+                    socket("Sheen Weight", json!(0.5)),
+                ]),
+                { "name": "Normal Map", "type": "NORMAL_MAP", "inputs": [socket("Strength", json!(1.0))] },
+                { "name": "Normal Texture", "type": "TEX_IMAGE" },
+            ],
+            "links": [
+                link("Normal Texture", "Color", "Normal Map", "Color"),
+                link("Normal Map", "Normal", "Principled BSDF", "Normal"),
+            ],
+        }));
+
+        let output = find(&doc, "Pbr Fragment");
+        assert_eq!(doc["output"], node_id(output));
+        let floats = |input: &str| value_floats(&output["node"][input]["value"]).unwrap();
+        assert_eq!(floats("color"), [0.8, 0.8, 0.8]);
+        assert_eq!(floats("metallic"), [0.25]);
+        assert_eq!(floats("roughness"), [0.75]);
+        assert_eq!(floats("alpha"), [0.5]);
+        assert_eq!(floats("emission"), [2.0, 1.0, 0.0]);
+
+        // The normal map is decoded to a tangent space normal.
+        let decode = find(&doc, "Lerp");
+        assert_eq!(
+            input_source(&output["node"]["normal"]),
+            Some((node_id(decode), 0))
+        );
+        let texture = find(&doc, "Texture Sample");
+        assert_eq!(
+            input_source(&decode["node"]["t"]),
+            Some((node_id(texture), 0))
+        );
+
+        // Only the unsupported input that has an effect is reported.
+        assert_eq!(report.warnings.len(), 1, "{report}");
+        assert!(report.warnings[0].contains("Sheen Weight"));
+        assert!(report.unsupported.is_empty());
+    }
+
+    #[test]
+    fn linked_emission_strength() {
+        let (doc, report) = import(json!({
+            "nodes": [
+                principled(vec![
+                    socket("Emission Color", json!([1.0, 1.0, 1.0, 1.0])),
+                    socket("Emission Strength", json!(3.0)),
+                ]),
+                { "name": "Glow", "type": "TEX_IMAGE" },
+            ],
+            "links": [link("Glow", "Color", "Principled BSDF", "Emission Color")],
+        }));
+        let output = find(&doc, "Pbr Fragment");
+        let scale = find(&doc, "Multiply");
+        assert_eq!(
+            input_source(&output["node"]["emission"]),
+            Some((node_id(scale), 0))
+        );
+        assert_eq!(value_floats(&scale["node"]["b"]["value"]), Some(vec![3.0]));
+        let texture = find(&doc, "Texture Sample");
+        assert_eq!(
+            input_source(&scale["node"]["a"]),
+            Some((node_id(texture), 0))
+        );
+        assert!(report.is_clean(), "{report}");
+    }
+
+    #[test]
+    fn dropped_inputs_are_reported() {
+        let (doc, report) = import(json!({
+            "nodes": [
+                principled(vec![socket("Normal", json!([0.0, 0.0, 0.0]))]),
+                { "name": "Bump", "type": "VECT_MATH", "properties": { "operation": "ADD" } },
+                { "name": "Value", "type": "VALUE", "outputs": [socket("Value", json!(0.5))] },
+            ],
+            "links": [
+                link("Bump", "Vector", "Principled BSDF", "Normal"),
+                link("Value", "Value", "Principled BSDF", "Transmission Weight"),
+            ],
+        }));
+        let output = find(&doc, "Pbr Fragment");
+        assert!(output["node"]["normal"]["connected"].is_null());
+        assert_eq!(report.warnings.len(), 2, "{report}");
+        assert!(report.warnings[0].contains("Normal"));
+        assert!(report.warnings[1].contains("Transmission Weight"));
+    }
+}
@@ -0,0 +1,850 @@
+//! Import Godot VisualShader (`.tres`) resources.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use bevy::math::Vec2;
+
+use node_engine::NodeGraph;
+
+use super::{GraphBuilder, ImportReport};
+
+/// A value in a Godot text resource.
+#[derive(Clone, Debug, PartialEq)]
+pub enum GdValue {
+    Number(f64),
+    Str(String),
+    Bool(bool),
+    /// Constructor call, e.g. `Vector2(1, 2)` or `SubResource("id")`.
+    Call(String, Vec<GdValue>),
+    Array(Vec<GdValue>),
+    /// Anything else (dictionaries, `null`, ...).
+    Other(String),
+}
+
+impl GdValue {
+    fn as_f32(&self) -> Option<f32> {
+        match self {
+            Self::Number(num) => Some(*num as f32),
+            Self::Bool(b) => Some(if *b { 1.0 } else { 0.0 }),
+            _ => None,
+        }
+    }
+
+    fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::Str(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Numbers of a number, vector or color value.
+    fn floats(&self) -> Vec<f32> {
+        match self {
+            Self::Call(_, args) => args.iter().filter_map(GdValue::as_f32).collect(),
+            value => value.as_f32().into_iter().collect(),
+        }
+    }
+
+    /// Resource id of a `SubResource(..)` reference.
+    fn sub_resource(&self) -> Option<String> {
+        match self {
+            Self::Call(name, args) if name == "SubResource" => match args.first()? {
+                Self::Str(id) => Some(id.clone()),
+                Self::Number(id) => Some(id.to_string()),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    fn items(&self) -> &[GdValue] {
+        match self {
+            Self::Array(items) => items,
+            // `PackedInt32Array(..)` / `PoolIntArray(..)`.
+            Self::Call(_, items) => items,
+            _ => &[],
+        }
+    }
+}
+
+/// A `[section]` of a Godot text resource.
+#[derive(Clone, Debug, Default)]
+pub struct GdSection {
+    pub tag: String,
+    pub attrs: HashMap<String, GdValue>,
+    pub props: Vec<(String, GdValue)>,
+}
+
+impl GdSection {
+    pub fn attr_str(&self, name: &str) -> Option<String> {
+        match self.attrs.get(name)? {
+            GdValue::Str(s) => Some(s.clone()),
+            GdValue::Number(num) => Some(num.to_string()),
+            _ => None,
+        }
+    }
+
+    pub fn prop(&self, name: &str) -> Option<&GdValue> {
+        self.props
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value)
+    }
+}
+
+/// Parse a Godot text resource (`.tres`) into sections.
+pub fn parse_tres(data: &str) -> Result<Vec<GdSection>> {
+    let mut sections: Vec<GdSection> = Vec::new();
+    let mut lines = data.lines();
+    while let Some(line) = lines.next() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with(';') {
+            continue;
+        }
+        if let Some(header) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            let mut parser = GdParser::new(header);
+            let tag = parser.ident();
+            let mut section = GdSection {
+                tag,
+                ..Default::default()
+            };
+            while let Some((key, value)) = parser.assignment()? {
+                section.attrs.insert(key, value);
+            }
+            sections.push(section);
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        // Values can span multiple lines (strings, arrays).
+        let mut value = value.trim().to_string();
+        while !is_complete(&value) {
+            match lines.next() {
+                Some(next) => {
+                    value.push('\n');
+                    value.push_str(next);
+                }
+                None => return Err(anyhow!("Unterminated value for `{}`", key.trim())),
+            }
+        }
+        let value = GdParser::new(&value).value()?;
+        if let Some(section) = sections.last_mut() {
+            section.props.push((key.trim().to_string(), value));
+        }
+    }
+    Ok(sections)
+}
+
+/// Check if quotes and brackets of a value are balanced.
+fn is_complete(value: &str) -> bool {
+    let mut depth = 0i32;
+    let mut in_str = false;
+    let mut escaped = false;
+    for c in value.chars() {
+        if in_str {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_str = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => in_str = true,
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => depth -= 1,
+            _ => {}
+        }
+    }
+    !in_str && depth <= 0
+}
+
+struct GdParser<'a> {
+    src: &'a str,
+    pos: usize,
+}
+
+impl<'a> GdParser<'a> {
+    fn new(src: &'a str) -> Self {
+        Self { src, pos: 0 }
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.src[self.pos..]
+    }
+
+    fn skip_ws(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.skip_ws();
+        self.rest().chars().next()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += c.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn ident(&mut self) -> String {
+        self.skip_ws();
+        let rest = self.rest();
+        let len = rest
+            .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '/'))
+            .unwrap_or(rest.len());
+        self.pos += len;
+        rest[..len].to_string()
+    }
+
+    /// `key=value` in a section header.
+    fn assignment(&mut self) -> Result<Option<(String, GdValue)>> {
+        let key = self.ident();
+        if key.is_empty() {
+            return Ok(None);
+        }
+        if !self.eat('=') {
+            return Err(anyhow!("Expected `=` after `{key}`"));
+        }
+        Ok(Some((key, self.value()?)))
+    }
+
+    fn list(&mut self, end: char) -> Result<Vec<GdValue>> {
+        let mut items = Vec::new();
+        loop {
+            if self.eat(end) {
+                return Ok(items);
+            }
+            items.push(self.value()?);
+            if !self.eat(',') {
+                if self.eat(end) {
+                    return Ok(items);
+                }
+                return Err(anyhow!("Expected `,` or `{end}` at: {}", self.rest()));
+            }
+        }
+    }
+
+    fn value(&mut self) -> Result<GdValue> {
+        match self.peek() {
+            Some('"') => {
+                self.pos += 1;
+                let mut out = String::new();
+                let mut chars = self.rest().char_indices();
+                while let Some((idx, c)) = chars.next() {
+                    match c {
+                        '\\' => {
+                            if let Some((_, next)) = chars.next() {
+                                out.push(match next {
+                                    'n' => '\n',
+                                    't' => '\t',
+                                    c => c,
+                                });
+                            }
+                        }
+                        '"' => {
+                            self.pos += idx + 1;
+                            return Ok(GdValue::Str(out));
+                        }
+                        c => out.push(c),
+                    }
+                }
+                Err(anyhow!("Unterminated string"))
+            }
+            Some('[') => {
+                self.pos += 1;
+                Ok(GdValue::Array(self.list(']')?))
+            }
+            Some('{') => {
+                // Dictionaries aren't needed, keep the raw text.
+                let start = self.pos;
+                let mut depth = 0;
+                for (idx, c) in self.rest().char_indices() {
+                    match c {
+                        '{' => depth += 1,
+                        '}' => {
+                            depth -= 1;
+                            if depth == 0 {
+                                self.pos += idx + 1;
+                                break;
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                Ok(GdValue::Other(self.src[start..self.pos].to_string()))
+            }
+            Some(c) if c == '-' || c == '.' || c.is_ascii_digit() => {
+                let rest = self.rest();
+                let len = rest
+                    .find(|c: char| !(c.is_ascii_alphanumeric() || "-+.".contains(c)))
+                    .unwrap_or(rest.len());
+                self.pos += len;
+                let num = &rest[..len];
+                num.parse().map(GdValue::Number).or_else(|_| match num {
+                    "inf" => Ok(GdValue::Number(f64::INFINITY)),
+                    "-inf" => Ok(GdValue::Number(f64::NEG_INFINITY)),
+                    _ => Err(anyhow!("Invalid number: {num}")),
+                })
+            }
+            Some(_) => {
+                let name = self.ident();
+                if name.is_empty() {
+                    return Err(anyhow!("Unexpected input: {}", self.rest()));
+                }
+                if self.eat('(') {
+                    return Ok(GdValue::Call(name, self.list(')')?));
+                }
+                Ok(match name.as_str() {
+                    "true" => GdValue::Bool(true),
+                    "false" => GdValue::Bool(false),
+                    _ => GdValue::Other(name),
+                })
+            }
+            None => Err(anyhow!("Missing value")),
+        }
+    }
+}
+
+/// Godot spatial fragment output ports -> `PBR Fragment` inputs.
+const OUTPUT_PORTS: &[(i64, &str)] = &[
+    (0, "color"),
+    (1, "alpha"),
+    (2, "metallic"),
+    (3, "roughness"),
+    (5, "emission"),
+    (6, "occlusion"),
+    (NORMAL_MAP_PORT, "normal"),
+];
+
+/// `NORMAL_MAP` output, a tangent space normal encoded as a color.
+const NORMAL_MAP_PORT: i64 = 9;
+
+/// A converted Godot node.
+enum GdNode {
+    Node {
+        id: String,
+        /// Godot input port -> input name.
+        inputs: &'static [&'static str],
+    },
+    /// Constants are inlined into the inputs they connect to.
+    Constant(Vec<f32>),
+    Output(String),
+    Placeholder,
+}
+
+/// Map a Godot node type (and its operator) onto a registry node.
+fn map_node(ty: &str, section: &GdSection) -> Option<(&'static str, &'static [&'static str])> {
+    const UNARY: &[&str] = &["a"];
+    const BINARY: &[&str] = &["a", "b"];
+    const LERP: &[&str] = &["a", "b", "t"];
+    const CLAMP: &[&str] = &["input", "min", "max"];
+    const VECTOR: &[&str] = &["input"];
+    const COMBINE: &[&str] = &["r", "g", "b", "a"];
+    const TEXTURE: &[&str] = &["uv"];
+    const FRESNEL: &[&str] = &["normal", "view_dir", "invert", "power"];
+    const NONE: &[&str] = &[];
+    let op = |name| {
+        section
+            .prop(name)
+            .and_then(GdValue::as_f32)
+            .map(|op| op as i64)
+            .unwrap_or(0)
+    };
+    Some(match ty {
+        "VisualShaderNodeFloatOp" | "VisualShaderNodeVectorOp" => {
+            let name = match op("operator") {
+                0 => "Add",
+                1 => "Subtract",
+                2 => "Multiply",
+                3 => "Divide",
+                6 => "Max",
+                7 => "Min",
+                _ => return None,
+            };
+            (name, BINARY)
+        }
+        // No `OP_POW`, so min/max come earlier than in the float operators.
+        "VisualShaderNodeIntOp" => {
+            let name = match op("operator") {
+                0 => "Add",
+                1 => "Subtract",
+                2 => "Multiply",
+                3 => "Divide",
+                5 => "Max",
+                6 => "Min",
+                _ => return None,
+            };
+            (name, BINARY)
+        }
+        "VisualShaderNodeFloatFunc" => match op("function") {
+            12 => ("Absolute", UNARY),
+            17 => ("Fraction", UNARY),
+            _ => return None,
+        },
+        "VisualShaderNodeVectorFunc" => match op("function") {
+            4 => ("Absolute", UNARY),
+            18 => ("Fraction", UNARY),
+            _ => return None,
+        },
+        "VisualShaderNodeMix" => ("Lerp", LERP),
+        "VisualShaderNodeClamp" => ("Clamp", CLAMP),
+        "VisualShaderNodeVectorDecompose" => ("Split", VECTOR),
+        "VisualShaderNodeVectorCompose" => ("Combine", COMBINE),
+        "VisualShaderNodeTexture" => ("Texture Sample", TEXTURE),
+        "VisualShaderNodeFresnel" => ("Fresnel Effect", FRESNEL),
+        "VisualShaderNodeInput" => {
+            let name = match section.prop("input_name").and_then(GdValue::as_str) {
+                Some("uv") => "Uv",
+                Some("view") => "View Direction",
+                _ => return None,
+            };
+            (name, NONE)
+        }
+        _ => return None,
+    })
+}
+
+fn constant_value(ty: &str, section: &GdSection) -> Option<Vec<f32>> {
+    match ty {
+        "VisualShaderNodeFloatConstant"
+        | "VisualShaderNodeIntConstant"
+        | "VisualShaderNodeVec2Constant"
+        | "VisualShaderNodeVec3Constant"
+        | "VisualShaderNodeVec4Constant"
+        | "VisualShaderNodeColorConstant"
+        | "VisualShaderNodeBooleanConstant" => section.prop("constant").map(GdValue::floats),
+        _ => None,
+    }
+}
+
+/// Import a Godot VisualShader `.tres` resource (spatial shader, fragment stage).
+pub fn import_godot<P: AsRef<Path>>(path: P) -> Result<(NodeGraph, ImportReport)> {
+    let data = std::fs::read_to_string(path)?;
+    import_godot_str(&data)
+}
+
+/// Import the contents of a Godot VisualShader `.tres` resource.
+pub fn import_godot_str(data: &str) -> Result<(NodeGraph, ImportReport)> {
+    let sections = parse_tres(data)?;
+    match sections.first() {
+        Some(header)
+            if header.tag == "gd_resource"
+                && header.attr_str("type").as_deref() == Some("VisualShader") => {}
+        _ => return Err(anyhow!("Not a Godot VisualShader resource")),
+    }
+    let sub_resources: HashMap<String, &GdSection> = sections
+        .iter()
+        .filter(|s| s.tag == "sub_resource")
+        .filter_map(|s| Some((s.attr_str("id")?, s)))
+        .collect();
+    let resource = sections
+        .iter()
+        .find(|s| s.tag == "resource")
+        .ok_or_else(|| anyhow!("Missing [resource] section"))?;
+
+    if let Some(mode) = resource.prop("mode").and_then(GdValue::as_f32) {
+        if mode != 0.0 {
+            return Err(anyhow!("Only spatial VisualShaders are supported"));
+        }
+    }
+
+    // Collect the fragment stage nodes: `nodes/fragment/<id>/<prop>`.
+    let mut positions: HashMap<i64, Vec2> = HashMap::new();
+    let mut node_refs: Vec<(i64, String)> = Vec::new();
+    let mut connections: Vec<i64> = Vec::new();
+    for (key, value) in &resource.props {
+        let Some(key) = key.strip_prefix("nodes/fragment/") else {
+            continue;
+        };
+        if key == "connections" {
+            connections = value
+                .items()
+                .iter()
+                .filter_map(GdValue::as_f32)
+                .map(|v| v as i64)
+                .collect();
+            continue;
+        }
+        let Some((id, prop)) = key.split_once('/') else {
+            continue;
+        };
+        let Ok(id) = id.parse::<i64>() else {
+            continue;
+        };
+        match prop {
+            "position" => {
+                let pos = value.floats();
+                positions.insert(
+                    id,
+                    Vec2::new(
+                        pos.first().copied().unwrap_or_default(),
+                        pos.get(1).copied().unwrap_or_default(),
+                    ),
+                );
+            }
+            "node" => {
+                if let Some(sub) = value.sub_resource() {
+                    node_refs.push((id, sub));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut builder = GraphBuilder::default();
+    let mut nodes: HashMap<i64, GdNode> = HashMap::new();
+
+    // Node `0` is the shader output.
+    let output_pos = positions.get(&0).copied().unwrap_or_default();
+    let output = builder.add_output("Pbr Fragment", output_pos)?;
+    nodes.insert(0, GdNode::Output(output));
+
+    for (id, sub) in node_refs {
+        let pos = positions.get(&id).copied().unwrap_or_default();
+        let Some(section) = sub_resources.get(&sub) else {
+            builder.warn(format!("Node {id}: missing sub resource `{sub}`"));
+            continue;
+        };
+        let ty = section.attr_str("type").unwrap_or_default();
+        if let Some(value) = constant_value(&ty, section) {
+            nodes.insert(id, GdNode::Constant(value));
+            continue;
+        }
+        let Some((name, inputs)) = map_node(&ty, section) else {
+            builder.add_placeholder(&format!("{ty} ({id})"), pos);
+            nodes.insert(id, GdNode::Placeholder);
+            continue;
+        };
+        let Ok(node_id) = builder.add_node(name, pos) else {
+            builder.add_placeholder(&format!("{ty} ({id}), no `{name}` node"), pos);
+            nodes.insert(id, GdNode::Placeholder);
+            continue;
+        };
+        // `default_input_values = [port, value, port, value, ..]`
+        if let Some(defaults) = section.prop("default_input_values") {
+            for pair in defaults.items().chunks(2) {
+                let [port, value] = pair else {
+                    continue;
+                };
+                let Some(input) = port.as_f32().and_then(|port| inputs.get(port as usize)) else {
+                    continue;
+                };
+                if builder.has_field(&node_id, input) {
+                    builder.set_input(&node_id, input, &value.floats());
+                }
+            }
+        }
+        nodes.insert(
+            id,
+            GdNode::Node {
+                id: node_id,
+                inputs,
+            },
+        );
+    }
+
+    // `connections = [from_node, from_port, to_node, to_port, ..]`
+    for conn in connections.chunks(4) {
+        let &[from, from_port, to, to_port] = conn else {
+            continue;
+        };
+        let is_normal_map =
+            matches!(nodes.get(&to), Some(GdNode::Output(_))) && to_port == NORMAL_MAP_PORT;
+        let (to_id, input) = match nodes.get(&to) {
+            Some(GdNode::Node { id, inputs }) => match inputs.get(to_port as usize) {
+                Some(input) => (id.clone(), *input),
+                None => {
+                    builder.warn(format!("Node {to}: input port {to_port} is not supported"));
+                    continue;
+                }
+            },
+            Some(GdNode::Output(id)) => {
+                match OUTPUT_PORTS.iter().find(|(port, _)| *port == to_port) {
+                    Some((_, input)) => (id.clone(), *input),
+                    None => {
+                        builder.warn(format!("Output port {to_port} is not supported"));
+                        continue;
+                    }
+                }
+            }
+            _ => {
+                builder.warn(format!("Dropped connection {from} -> {to}"));
+                continue;
+            }
+        };
+        match nodes.get(&from) {
+            Some(GdNode::Node { id, .. }) if is_normal_map => {
+                let pos = output_pos - Vec2::new(150.0, 0.0);
+                if let Err(err) =
+                    connect_normal_map(&mut builder, id, from_port as u32, &to_id, pos)
+                {
+                    builder.warn(format!("{err}"));
+                }
+            }
+            Some(GdNode::Node { id, .. }) => {
+                if let Err(err) = builder.connect(id, from_port as u32, &to_id, input) {
+                    builder.warn(format!("{err}"));
+                }
+            }
+            Some(GdNode::Constant(value)) => {
+                let value: Vec<f32> = if is_normal_map {
+                    value.iter().map(|v| v * 2.0 - 1.0).collect()
+                } else {
+                    value.clone()
+                };
+                if builder.has_field(&to_id, input) {
+                    builder.set_input(&to_id, input, &value);
+                } else {
+                    builder.warn(format!("Dropped constant for missing input `{input}`"));
+                }
+            }
+            _ => {
+                builder.warn(format!("Dropped connection {from} -> {to}"));
+            }
+        }
+    }
+
+    builder.finish()
+}
+
+/// Connect a `NORMAL_MAP` color to the `normal` input, decoding it with `color * 2 - 1`.
+fn connect_normal_map(
+    builder: &mut GraphBuilder,
+    from: &str,
+    out_idx: u32,
+    output: &str,
+    pos: Vec2,
+) -> Result<()> {
+    let decode = builder.add_node("Lerp", pos)?;
+    builder.set_input(&decode, "a", &[-1.0, -1.0, -1.0]);
+    builder.set_input(&decode, "b", &[1.0, 1.0, 1.0]);
+    builder.connect(from, out_idx, &decode, "t")?;
+    builder.connect(&decode, 0, output, "normal")
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+
+    use super::*;
+    use crate::document::{graph_to_value, input_source, node_id, value_floats};
+
+    const TRES: &str = r#"[gd_resource type="VisualShader" load_steps=4 format=3 uid="uid://b1x2"]
+
+; Comments are skipped.
+[sub_resource type="VisualShaderNodeFloatConstant" id="VisualShaderNodeFloatConstant_1"]
+constant = 0.25
+
+[sub_resource type="VisualShaderNodeVectorOp" id="VisualShaderNodeVectorOp_2"]
+default_input_values = [0, Vector3(1, 0.5, 0), 1, Vector3(2, 2,
+2)]
+operator = 2
+
+[sub_resource type="VisualShaderNodeCurveTexture" id="VisualShaderNodeCurveTexture_3"]
+
+[resource]
+code = "shader_type spatial;
+// \"quoted\" [not an array
+void fragment() {
+	ALBEDO = vec3(1.0);
+}
+"
+nodes/fragment/0/position = Vector2(400, 150)
+nodes/fragment/2/node = SubResource("VisualShaderNodeFloatConstant_1")
+nodes/fragment/2/position = Vector2(-100, 300)
+nodes/fragment/3/node = SubResource("VisualShaderNodeVectorOp_2")
+nodes/fragment/3/position = Vector2(-120, 100)
+nodes/fragment/4/node = SubResource("VisualShaderNodeCurveTexture_3")
+nodes/fragment/4/position = Vector2(-300, 0)
+nodes/fragment/connections = PackedInt32Array(2, 0, 0, 3, 3, 0, 0, 0, 3, 0, 0, 9)
+"#;
+
+    fn num(v: f64) -> GdValue {
+        GdValue::Number(v)
+    }
+
+    fn find<'a>(doc: &'a Value, name: &str) -> &'a Value {
+        doc["nodes"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|node| node["name"] == name)
+            .unwrap_or_else(|| panic!("no `{name}` node"))
+    }
+
+    #[test]
+    fn parse_sections() {
+        let sections = parse_tres(TRES).unwrap();
+        let tags: Vec<_> = sections.iter().map(|s| s.tag.as_str()).collect();
+        assert_eq!(
+            tags,
+            [
+                "gd_resource",
+                "sub_resource",
+                "sub_resource",
+                "sub_resource",
+                "resource"
+            ]
+        );
+
+        let header = &sections[0];
+        assert_eq!(header.attr_str("type").as_deref(), Some("VisualShader"));
+        assert_eq!(header.attr_str("uid").as_deref(), Some("uid://b1x2"));
+        assert_eq!(header.attrs["load_steps"], num(4.0));
+
+        // Multi-line array.
+        let op = &sections[2];
+        assert_eq!(
+            op.prop("default_input_values"),
+            Some(&GdValue::Array(vec![
+                num(0.0),
+                GdValue::Call("Vector3".into(), vec![num(1.0), num(0.5), num(0.0)]),
+                num(1.0),
+                GdValue::Call("Vector3".into(), vec![num(2.0), num(2.0), num(2.0)]),
+            ]))
+        );
+        assert_eq!(op.prop("operator").and_then(GdValue::as_f32), Some(2.0));
+        assert!(sections[3].props.is_empty());
+
+        // Multi-line string with escapes and brackets.
+        let resource = &sections[4];
+        let code = resource.prop("code").and_then(GdValue::as_str).unwrap();
+        assert!(code.contains("// \"quoted\" [not an array\n"));
+        assert!(code.ends_with("\tALBEDO = vec3(1.0);\n}\n"));
+
+        let node = resource.prop("nodes/fragment/2/node").unwrap();
+        assert_eq!(
+            node.sub_resource().as_deref(),
+            Some("VisualShaderNodeFloatConstant_1")
+        );
+        let connections = resource.prop("nodes/fragment/connections").unwrap();
+        assert_eq!(connections.items().len(), 12);
+    }
+
+    #[test]
+    fn parse_values() {
+        let value = |src| GdParser::new(src).value().unwrap();
+        assert_eq!(value("-1.5e2"), num(-150.0));
+        assert_eq!(value("inf"), GdValue::Other("inf".into()));
+        assert_eq!(value("-inf"), num(f64::NEG_INFINITY));
+        assert_eq!(value("true"), GdValue::Bool(true));
+        assert_eq!(value(r#""a\tb\\c""#), GdValue::Str("a\tb\\c".into()));
+        assert_eq!(value("[]"), GdValue::Array(vec![]));
+        assert_eq!(value("Color(1, 0, 0, 1)").floats(), [1.0, 0.0, 0.0, 1.0]);
+        assert_eq!(
+            value("{ \"a\": { \"b\": 1 } }"),
+            GdValue::Other("{ \"a\": { \"b\": 1 } }".into())
+        );
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!(parse_tres("[resource]\nvalue = [1, 2\n").is_err());
+        assert!(parse_tres("[resource]\nvalue = \"open\n").is_err());
+        assert!(parse_tres("[sub_resource type]\n").is_err());
+        assert!(GdParser::new("[1 2]").value().is_err());
+    }
+
+    #[test]
+    fn import_fragment() {
+        let (graph, report) = import_godot_str(TRES).unwrap();
+        let doc = graph_to_value(&graph).unwrap();
+
+        let output = find(&doc, "Pbr Fragment");
+        assert_eq!(doc["output"], node_id(output));
+        // The float constant is inlined.
+        assert_eq!(output["node"]["roughness"]["value"], 0.25);
+        assert!(output["node"]["roughness"]["connected"].is_null());
+
+        let multiply = find(&doc, "Multiply");
+        assert_eq!(
+            input_source(&output["node"]["color"]),
+            Some((node_id(multiply), 0))
+        );
+        assert_eq!(
+            value_floats(&multiply["node"]["a"]["value"]),
+            Some(vec![1.0, 0.5, 0.0])
+        );
+        assert_eq!(multiply["area"]["min"]["x"], -120.0);
+        assert_eq!(multiply["area"]["min"]["y"], 100.0);
+
+        // The normal map is decoded to a tangent space normal.
+        let decode = find(&doc, "Lerp");
+        assert_eq!(
+            input_source(&output["node"]["normal"]),
+            Some((node_id(decode), 0))
+        );
+        assert_eq!(
+            input_source(&decode["node"]["t"]),
+            Some((node_id(multiply), 0))
+        );
+
+        assert_eq!(report.unsupported, ["VisualShaderNodeCurveTexture (4)"]);
+        find(&doc, "Placeholder");
+    }
+
+    #[test]
+    fn fresnel_node() {
+        let tres = r#"[gd_resource type="VisualShader" format=3]
+
+[sub_resource type="VisualShaderNodeFresnel" id="VisualShaderNodeFresnel_1"]
+default_input_values = [2, false, 3, 4.0]
+
+[resource]
+nodes/fragment/2/node = SubResource("VisualShaderNodeFresnel_1")
+nodes/fragment/connections = PackedInt32Array(2, 0, 0, 2)
+"#;
+        let (graph, report) = import_godot_str(tres).unwrap();
+        assert!(report.unsupported.is_empty());
+        let doc = graph_to_value(&graph).unwrap();
+        let fresnel = find(&doc, "Fresnel Effect");
+        assert_eq!(fresnel["node"]["power"]["value"], 4.0);
+        assert_eq!(
+            input_source(&find(&doc, "Pbr Fragment")["node"]["metallic"]),
+            Some((node_id(fresnel), 0))
+        );
+    }
+
+    #[test]
+    fn int_operators() {
+        let tres = r#"[gd_resource type="VisualShader" format=3]
+
+[sub_resource type="VisualShaderNodeIntOp" id="VisualShaderNodeIntOp_1"]
+operator = 5
+
+[sub_resource type="VisualShaderNodeIntOp" id="VisualShaderNodeIntOp_2"]
+operator = 6
+
+[sub_resource type="VisualShaderNodeIntOp" id="VisualShaderNodeIntOp_3"]
+operator = 7
+
+[resource]
+nodes/fragment/2/node = SubResource("VisualShaderNodeIntOp_1")
+nodes/fragment/3/node = SubResource("VisualShaderNodeIntOp_2")
+nodes/fragment/4/node = SubResource("VisualShaderNodeIntOp_3")
+"#;
+        let (graph, report) = import_godot_str(tres).unwrap();
+        let doc = graph_to_value(&graph).unwrap();
+        find(&doc, "Max");
+        find(&doc, "Min");
+        // `OP_BITWISE_AND`.
+        assert_eq!(report.unsupported, ["VisualShaderNodeIntOp (4)"]);
+    }
+
+    #[test]
+    fn rejects_other_resources() {
+        assert!(import_godot_str("[gd_resource type=\"Material\" format=3]\n").is_err());
+        let canvas = "[gd_resource type=\"VisualShader\" format=3]\n\n[resource]\nmode = 1\n";
+        assert!(import_godot_str(canvas).is_err());
+    }
+}
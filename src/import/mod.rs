@@ -0,0 +1,269 @@
+//! Importers for shader graphs from other tools.
+
+use std::fmt;
+
+use anyhow::{anyhow, Result};
+use bevy::math::Vec2;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use node_engine::NodeGraph;
+
+use crate::document::{
+    doc_nodes_mut, graph_from_value, graph_to_value, set_value_floats, NodeTemplates,
+};
+
+pub mod blender;
+pub use blender::*;
+pub mod godot;
+pub use godot::*;
+pub mod unity;
+pub use unity::*;
+
+/// Default size of a node in the editor.
+const NODE_SIZE: Vec2 = Vec2::new(212.0, 51.0);
+
+/// Space between a placeholder node and its group.
+const PLACEHOLDER_MARGIN: Vec2 = Vec2::new(10.0, 30.0);
+
+/// Nodes and values that couldn't be imported exactly.
+#[derive(Clone, Debug, Default)]
+pub struct ImportReport {
+    /// Source nodes replaced by placeholders.
+    pub unsupported: Vec<String>,
+    /// Other lossy conversions.
+    pub warnings: Vec<String>,
+}
+
+impl ImportReport {
+    pub fn is_clean(&self) -> bool {
+        self.unsupported.is_empty() && self.warnings.is_empty()
+    }
+}
+
+impl fmt::Display for ImportReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_clean() {
+            return writeln!(f, "All nodes imported.");
+        }
+        for node in &self.unsupported {
+            writeln!(f, "Unsupported node: {node}")?;
+        }
+        for warning in &self.warnings {
+            writeln!(f, "Warning: {warning}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Builds a graph document node by node.
+///
+/// Unsupported source nodes become `Placeholder` nodes, so they are visible in the
+/// editor without affecting the compiled shader.
+pub struct GraphBuilder {
+    templates: NodeTemplates,
+    doc: Value,
+    output: Option<String>,
+    pub report: ImportReport,
+}
+
+impl Default for GraphBuilder {
+    fn default() -> Self {
+        Self::new(NodeTemplates::default())
+    }
+}
+
+impl GraphBuilder {
+    pub fn new(templates: NodeTemplates) -> Self {
+        let doc = graph_to_value(&NodeGraph::new()).unwrap_or_else(|_| json!({}));
+        let mut builder = Self {
+            templates,
+            doc,
+            output: None,
+            report: ImportReport::default(),
+        };
+        for key in ["nodes", "groups", "connections"] {
+            if !builder.doc.get(key).is_some_and(Value::is_array) {
+                builder.doc[key] = json!([]);
+            }
+        }
+        builder
+    }
+
+    /// Add a registry node, returns the new node id.
+    pub fn add_node(&mut self, name: &str, pos: Vec2) -> Result<String> {
+        let template = self.templates.require(name)?;
+        let id = Uuid::new_v4().to_string();
+        let node = json!({
+            "id": id,
+            "group_id": Uuid::nil().to_string(),
+            "name": template.name,
+            "node_type": template.node_type,
+            "node": template.node,
+            "area": area(pos, NODE_SIZE),
+        });
+        self.nodes_mut().push(node);
+        Ok(id)
+    }
+
+    /// Add the graph's output node, returns the node id.
+    pub fn add_output(&mut self, name: &str, pos: Vec2) -> Result<String> {
+        let id = self.add_node(name, pos)?;
+        self.output = Some(id.clone());
+        Ok(id)
+    }
+
+    /// The output node, if one was added.
+    pub fn output(&self) -> Option<&str> {
+        self.output.as_deref()
+    }
+
+    /// Add a `Placeholder` node for an unsupported source node, in a group titled
+    /// with the source node.
+    pub fn add_placeholder(&mut self, label: &str, pos: Vec2) {
+        self.report.unsupported.push(label.to_string());
+        let group_id = Uuid::new_v4().to_string();
+        let group = json!({
+            "id": group_id,
+            "title": format!("Unsupported: {label}"),
+            "area": area(pos - PLACEHOLDER_MARGIN, NODE_SIZE + PLACEHOLDER_MARGIN * 2.0),
+        });
+        if let Some(groups) = self.doc["groups"].as_array_mut() {
+            groups.push(group);
+        }
+        match self.add_node("Placeholder", pos) {
+            Ok(id) => {
+                if let Some(node) = doc_nodes_mut(&mut self.doc).find(|n| n["id"] == id.as_str()) {
+                    node["group_id"] = group_id.into();
+                }
+            }
+            Err(err) => self.warn(format!("Failed to add a placeholder for {label}: {err}")),
+        }
+    }
+
+    pub fn warn(&mut self, msg: impl Into<String>) {
+        self.report.warnings.push(msg.into());
+    }
+
+    /// The registry name of a node.
+    pub fn node_name(&self, node: &str) -> Option<String> {
+        self.find_node(node)
+            .and_then(|n| n.get("name"))
+            .and_then(Value::as_str)
+            .map(str::to_string)
+    }
+
+    /// Check if a node has an input or parameter.
+    pub fn has_field(&self, node: &str, field: &str) -> bool {
+        self.find_node(node)
+            .and_then(|n| n.get("node"))
+            .is_some_and(|fields| fields.get(field).is_some())
+    }
+
+    /// Set a constant input value, converting to the input's value type.
+    pub fn set_input(&mut self, node: &str, input: &str, values: &[f32]) {
+        let Some(field) = self.field_mut(node, input) else {
+            self.warn(format!("Node {node} has no input `{input}`"));
+            return;
+        };
+        let Some(value) = field.get_mut("value") else {
+            return;
+        };
+        if !set_value_floats(value, values) {
+            self.warn(format!("Can't set the value of input `{input}`"));
+        }
+    }
+
+    /// Set a node parameter (a non-input field).
+    pub fn set_param(&mut self, node: &str, param: &str, value: Value) {
+        match self.field_mut(node, param) {
+            Some(field) => *field = value,
+            None => self.warn(format!("Node {node} has no parameter `{param}`")),
+        }
+    }
+
+    /// Connect output `out_idx` of node `from` to the named input of node `to`.
+    pub fn connect(&mut self, from: &str, out_idx: u32, to: &str, input: &str) -> Result<()> {
+        let name = self
+            .node_name(to)
+            .ok_or_else(|| anyhow!("Unknown node: {to}"))?;
+        let idx = self
+            .templates
+            .require(&name)?
+            .input_idx(input)
+            .ok_or_else(|| anyhow!("Node `{name}` has no input `{input}`"))?;
+        let field = self
+            .field_mut(to, input)
+            .ok_or_else(|| anyhow!("Node `{name}` has no input `{input}`"))?;
+        let data_type = input_data_type(&name, &field["value"]);
+        let output = json!({ "node": from, "idx": out_idx });
+        field["connected"] = json!([output, data_type]);
+        if let Some(connections) = self.doc["connections"].as_array_mut() {
+            connections.push(json!({
+                "input": { "node": to, "idx": idx },
+                "output": output,
+            }));
+        }
+        Ok(())
+    }
+
+    /// The graph document built so far.
+    pub fn document(&self) -> Value {
+        let mut doc = self.doc.clone();
+        doc["output"] = self.output.clone().map(Value::from).unwrap_or(Value::Null);
+        doc
+    }
+
+    pub fn finish(self) -> Result<(NodeGraph, ImportReport)> {
+        let graph = graph_from_value(self.document())?;
+        Ok((graph, self.report))
+    }
+
+    fn nodes_mut(&mut self) -> &mut Vec<Value> {
+        self.doc["nodes"]
+            .as_array_mut()
+            .expect("nodes is always an array")
+    }
+
+    fn find_node(&self, id: &str) -> Option<&Value> {
+        self.doc["nodes"]
+            .as_array()?
+            .iter()
+            .find(|n| n.get("id").and_then(Value::as_str) == Some(id))
+    }
+
+    fn field_mut(&mut self, node: &str, field: &str) -> Option<&mut Value> {
+        doc_nodes_mut(&mut self.doc)
+            .find(|n| n.get("id").and_then(Value::as_str) == Some(node))?
+            .get_mut("node")?
+            .get_mut(field)
+    }
+}
+
+fn area(pos: Vec2, size: Vec2) -> Value {
+    let max = pos + size;
+    json!({
+        "min": { "x": pos.x, "y": pos.y },
+        "max": { "x": max.x, "y": max.y },
+    })
+}
+
+/// The data type recorded for a connection into an input.
+fn input_data_type(node_name: &str, value: &Value) -> &'static str {
+    match value {
+        // `Multiply` also accepts matrices.
+        Value::Object(_) if node_name == "Multiply" => "Dynamic",
+        Value::Object(_) => "DynamicVector",
+        Value::Number(_) => "F32",
+        Value::Array(arr) => match arr.first() {
+            // UV inputs: `[[u, v], "UV0"]`.
+            Some(Value::Array(_)) => "Vec2",
+            _ => match arr.len() {
+                2 => "Vec2",
+                3 => "Vec3",
+                _ => "Vec4",
+            },
+        },
+        _ => "Dynamic",
+    }
+}
@@ -0,0 +1,513 @@
+//! Import Unity Shader Graph (`.shadergraph`) files.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use bevy::math::Vec2;
+use serde_json::Value;
+
+use node_engine::NodeGraph;
+
+use super::{GraphBuilder, ImportReport};
+
+/// How a Unity node maps onto a registry node.
+struct UnityNode {
+    /// Unity node class, without the `UnityEditor.ShaderGraph.` namespace.
+    unity: &'static str,
+    /// Registry node name.
+    name: &'static str,
+    /// Unity input slot id -> input name.
+    inputs: &'static [(i64, &'static str)],
+    /// Unity output slot id -> output index.
+    outputs: &'static [(i64, u32)],
+}
+
+const BINARY_INPUTS: &[(i64, &str)] = &[(0, "a"), (1, "b")];
+const BINARY_OUTPUTS: &[(i64, u32)] = &[(2, 0)];
+
+const UNITY_NODES: &[UnityNode] = &[
+    UnityNode {
+        unity: "AddNode",
+        name: "Add",
+        inputs: BINARY_INPUTS,
+        outputs: BINARY_OUTPUTS,
+    },
+    UnityNode {
+        unity: "SubtractNode",
+        name: "Subtract",
+        inputs: BINARY_INPUTS,
+        outputs: BINARY_OUTPUTS,
+    },
+    UnityNode {
+        unity: "MultiplyNode",
+        name: "Multiply",
+        inputs: BINARY_INPUTS,
+        outputs: BINARY_OUTPUTS,
+    },
+    UnityNode {
+        unity: "DivideNode",
+        name: "Divide",
+        inputs: BINARY_INPUTS,
+        outputs: BINARY_OUTPUTS,
+    },
+    UnityNode {
+        unity: "MinimumNode",
+        name: "Min",
+        inputs: BINARY_INPUTS,
+        outputs: BINARY_OUTPUTS,
+    },
+    UnityNode {
+        unity: "MaximumNode",
+        name: "Max",
+        inputs: BINARY_INPUTS,
+        outputs: BINARY_OUTPUTS,
+    },
+    UnityNode {
+        unity: "FractionNode",
+        name: "Fraction",
+        inputs: &[(0, "a")],
+        outputs: &[(1, 0)],
+    },
+    UnityNode {
+        unity: "AbsoluteNode",
+        name: "Absolute",
+        inputs: &[(0, "a")],
+        outputs: &[(1, 0)],
+    },
+    UnityNode {
+        unity: "ClampNode",
+        name: "Clamp",
+        inputs: &[(0, "input"), (1, "min"), (2, "max")],
+        outputs: &[(3, 0)],
+    },
+    UnityNode {
+        unity: "LerpNode",
+        name: "Lerp",
+        inputs: &[(0, "a"), (1, "b"), (2, "t")],
+        outputs: &[(3, 0)],
+    },
+    UnityNode {
+        unity: "SplitNode",
+        name: "Split",
+        inputs: &[(0, "input")],
+        outputs: &[(1, 0), (2, 1), (3, 2), (4, 3)],
+    },
+    UnityNode {
+        unity: "CombineNode",
+        name: "Combine",
+        inputs: &[(0, "r"), (1, "g"), (2, "b"), (3, "a")],
+        outputs: &[(4, 0), (5, 1), (6, 2)],
+    },
+    UnityNode {
+        unity: "SwizzleNode",
+        name: "Swizzle",
+        inputs: &[(0, "input")],
+        outputs: &[(1, 0)],
+    },
+    UnityNode {
+        unity: "UVNode",
+        name: "Uv",
+        inputs: &[],
+        outputs: &[(0, 0)],
+    },
+    UnityNode {
+        unity: "ViewDirectionNode",
+        name: "View Direction",
+        inputs: &[],
+        outputs: &[(0, 0)],
+    },
+    UnityNode {
+        unity: "TilingAndOffsetNode",
+        name: "Tiling And Offset",
+        inputs: &[(0, "uv"), (1, "tiling"), (2, "offset")],
+        outputs: &[(3, 0)],
+    },
+    UnityNode {
+        unity: "SampleTexture2DNode",
+        name: "Texture Sample",
+        inputs: &[(1, "tex"), (2, "uv")],
+        outputs: &[(0, 0)],
+    },
+    UnityNode {
+        unity: "FresnelNode",
+        name: "Fresnel Effect",
+        inputs: &[(0, "normal"), (1, "view_dir"), (2, "power")],
+        outputs: &[(3, 0)],
+    },
+];
+
+/// Unity master stack blocks -> `PBR Fragment` inputs.
+const UNITY_BLOCKS: &[(&str, &str)] = &[
+    ("SurfaceDescription.BaseColor", "color"),
+    ("SurfaceDescription.Alpha", "alpha"),
+    ("SurfaceDescription.Metallic", "metallic"),
+    ("SurfaceDescription.Smoothness", SMOOTHNESS),
+    ("SurfaceDescription.NormalTS", "normal"),
+    ("SurfaceDescription.Emission", "emission"),
+    ("SurfaceDescription.Occlusion", "occlusion"),
+];
+
+/// Unity's smoothness block, converted to `roughness = 1 - smoothness`.
+const SMOOTHNESS: &str = "smoothness";
+
+const UNITY_NAMESPACE: &str = "UnityEditor.ShaderGraph.";
+
+/// Where an imported Unity node ended up.
+enum Imported {
+    Node {
+        id: String,
+        mapping: &'static UnityNode,
+    },
+    Block {
+        input: &'static str,
+    },
+    Placeholder,
+}
+
+/// Import a Unity `.shadergraph` file.
+pub fn import_unity<P: AsRef<Path>>(path: P) -> Result<(NodeGraph, ImportReport)> {
+    let data = std::fs::read_to_string(path)?;
+    import_unity_str(&data)
+}
+
+/// Import the contents of a Unity `.shadergraph` file.
+///
+/// Supports the multi-object JSON format used since Shader Graph 10.
+pub fn import_unity_str(data: &str) -> Result<(NodeGraph, ImportReport)> {
+    let objects = serde_json::Deserializer::from_str(data)
+        .into_iter::<Value>()
+        .collect::<Result<Vec<_>, _>>()?;
+    let by_id: HashMap<&str, &Value> = objects
+        .iter()
+        .filter_map(|obj| Some((obj.get("m_ObjectId")?.as_str()?, obj)))
+        .collect();
+    let graph = objects
+        .iter()
+        .find(|obj| unity_type(obj) == Some("GraphData"))
+        .ok_or_else(|| anyhow!("Not a Unity Shader Graph file (no GraphData object)"))?;
+
+    let mut builder = GraphBuilder::default();
+    let mut imported = HashMap::new();
+    let mut output_pos = Vec2::ZERO;
+    let mut has_blocks = false;
+    // Constant values of the master stack blocks.
+    let mut block_values = Vec::new();
+
+    for node_ref in graph["m_Nodes"].as_array().into_iter().flatten() {
+        let Some(node) = node_ref["m_Id"].as_str().and_then(|id| by_id.get(id)) else {
+            continue;
+        };
+        let id = node["m_ObjectId"].as_str().unwrap_or_default();
+        let ty = unity_type(node).unwrap_or_default();
+        let pos = node_position(node);
+
+        if ty == "BlockNode" {
+            has_blocks = true;
+            output_pos = output_pos.max(pos);
+            let descriptor = node["m_SerializedDescriptor"].as_str().unwrap_or_default();
+            match UNITY_BLOCKS.iter().find(|(block, _)| *block == descriptor) {
+                Some((_, input)) => {
+                    let values = node["m_Slots"]
+                        .get(0)
+                        .and_then(|slot| slot["m_Id"].as_str())
+                        .and_then(|id| by_id.get(id))
+                        .map(|slot| slot_values(&slot["m_Value"]))
+                        .unwrap_or_default();
+                    block_values.push((*input, values));
+                    imported.insert(id.to_string(), Imported::Block { input });
+                }
+                None => {
+                    builder.warn(format!(
+                        "Master stack block `{descriptor}` is not supported"
+                    ));
+                    imported.insert(id.to_string(), Imported::Placeholder);
+                }
+            }
+            continue;
+        }
+
+        let Some(mapping) = UNITY_NODES.iter().find(|m| m.unity == ty) else {
+            builder.add_placeholder(&format!("{ty} ({id})"), pos);
+            imported.insert(id.to_string(), Imported::Placeholder);
+            continue;
+        };
+        let node_id = match builder.add_node(mapping.name, pos) {
+            Ok(node_id) => node_id,
+            Err(_) => {
+                builder.add_placeholder(&format!("{ty} ({id}), no `{}` node", mapping.name), pos);
+                imported.insert(id.to_string(), Imported::Placeholder);
+                continue;
+            }
+        };
+        import_slot_values(&mut builder, &by_id, node, &node_id, mapping);
+        if ty == "SwizzleNode" {
+            if let Some(mask) = node["_maskInput"].as_str() {
+                builder.set_param(&node_id, "swizzle", mask.to_lowercase().into());
+            }
+        }
+        imported.insert(
+            id.to_string(),
+            Imported::Node {
+                id: node_id,
+                mapping,
+            },
+        );
+    }
+
+    let output_pos = output_pos + Vec2::new(300.0, 0.0);
+    let output = if has_blocks {
+        Some(builder.add_output("Pbr Fragment", output_pos)?)
+    } else {
+        builder.warn("No master stack found, the graph has no output");
+        None
+    };
+    if let Some(output) = &output {
+        for (input, values) in block_values {
+            match (input, values.as_slice()) {
+                (_, []) => {}
+                (SMOOTHNESS, [smoothness, ..]) => {
+                    builder.set_input(output, "roughness", &[1.0 - smoothness]);
+                }
+                (input, values) => builder.set_input(output, input, values),
+            }
+        }
+    }
+
+    for edge in graph["m_Edges"].as_array().into_iter().flatten() {
+        let (from, from_slot) = edge_slot(&edge["m_OutputSlot"]);
+        let (to, to_slot) = edge_slot(&edge["m_InputSlot"]);
+        let (Some(from), Some(to)) = (imported.get(from), imported.get(to)) else {
+            continue;
+        };
+        let Imported::Node {
+            id: from_id,
+            mapping: from_map,
+        } = from
+        else {
+            builder.warn(format!(
+                "Dropped connection from unsupported node slot {from_slot}"
+            ));
+            continue;
+        };
+        let Some(&(_, out_idx)) = from_map.outputs.iter().find(|(slot, _)| *slot == from_slot)
+        else {
+            builder.warn(format!(
+                "`{}` output slot {from_slot} is not supported",
+                from_map.name
+            ));
+            continue;
+        };
+        let (to_id, input) = match to {
+            Imported::Node { id, mapping } => {
+                match mapping.inputs.iter().find(|(slot, _)| *slot == to_slot) {
+                    Some((_, input)) => (id.clone(), *input),
+                    None => {
+                        builder.warn(format!(
+                            "`{}` input slot {to_slot} is not supported",
+                            mapping.name
+                        ));
+                        continue;
+                    }
+                }
+            }
+            Imported::Block { input } => match &output {
+                Some(output) if *input == SMOOTHNESS => {
+                    let pos = output_pos - Vec2::new(150.0, 0.0);
+                    if let Err(err) =
+                        connect_smoothness(&mut builder, from_id, out_idx, output, pos)
+                    {
+                        builder.warn(format!("{err}"));
+                    }
+                    continue;
+                }
+                Some(output) => (output.clone(), *input),
+                None => continue,
+            },
+            Imported::Placeholder => {
+                builder.warn(format!(
+                    "Dropped connection into unsupported node slot {to_slot}"
+                ));
+                continue;
+            }
+        };
+        if let Err(err) = builder.connect(from_id, out_idx, &to_id, input) {
+            builder.warn(format!("{err}"));
+        }
+    }
+
+    builder.finish()
+}
+
+/// Connect a smoothness output to the `roughness` input through `1 - smoothness`.
+fn connect_smoothness(
+    builder: &mut GraphBuilder,
+    from: &str,
+    out_idx: u32,
+    output: &str,
+    pos: Vec2,
+) -> Result<()> {
+    let invert = builder.add_node("Subtract", pos)?;
+    builder.set_input(&invert, "a", &[1.0]);
+    builder.connect(from, out_idx, &invert, "b")?;
+    builder.connect(&invert, 0, output, "roughness")
+}
+
+fn unity_type(obj: &Value) -> Option<&str> {
+    obj["m_Type"]
+        .as_str()
+        .map(|ty| ty.strip_prefix(UNITY_NAMESPACE).unwrap_or(ty))
+}
+
+fn node_position(node: &Value) -> Vec2 {
+    let pos = &node["m_DrawState"]["m_Position"];
+    Vec2::new(
+        pos["x"].as_f64().unwrap_or_default() as f32,
+        pos["y"].as_f64().unwrap_or_default() as f32,
+    )
+}
+
+fn edge_slot(slot: &Value) -> (&str, i64) {
+    (
+        slot["m_Node"]["m_Id"].as_str().unwrap_or_default(),
+        slot["m_SlotId"].as_i64().unwrap_or(-1),
+    )
+}
+
+/// Copy the constant values of the node's input slots.
+fn import_slot_values(
+    builder: &mut GraphBuilder,
+    by_id: &HashMap<&str, &Value>,
+    node: &Value,
+    node_id: &str,
+    mapping: &UnityNode,
+) {
+    for slot_ref in node["m_Slots"].as_array().into_iter().flatten() {
+        let Some(slot) = slot_ref["m_Id"].as_str().and_then(|id| by_id.get(id)) else {
+            continue;
+        };
+        let slot_id = slot["m_Id"].as_i64().unwrap_or(-1);
+        let Some((_, input)) = mapping.inputs.iter().find(|(id, _)| *id == slot_id) else {
+            continue;
+        };
+        let values = slot_values(&slot["m_Value"]);
+        if !values.is_empty() && builder.has_field(node_id, input) {
+            builder.set_input(node_id, input, &values);
+        }
+    }
+}
+
+/// Unity slot values are numbers or `{ "x": .., "y": .. }` vectors (`e00`.. for matrices).
+fn slot_values(value: &Value) -> Vec<f32> {
+    match value {
+        Value::Number(num) => vec![num.as_f64().unwrap_or_default() as f32],
+        Value::Object(obj) => ["x", "y", "z", "w"]
+            .iter()
+            .map_while(|key| obj.get(*key).and_then(Value::as_f64))
+            .map(|v| v as f32)
+            .collect(),
+        _ => vec![],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::{graph_to_value, input_source, node_id};
+
+    const GRAPH: &str = r#"
+{"m_Type": "UnityEditor.ShaderGraph.GraphData", "m_ObjectId": "graph",
+ "m_Nodes": [{"m_Id": "smooth"}, {"m_Id": "metal"}, {"m_Id": "frac"}, {"m_Id": "voronoi"}],
+ "m_Edges": [{"m_OutputSlot": {"m_Node": {"m_Id": "frac"}, "m_SlotId": 1},
+              "m_InputSlot": {"m_Node": {"m_Id": "smooth"}, "m_SlotId": 0}}]}
+{"m_Type": "UnityEditor.ShaderGraph.BlockNode", "m_ObjectId": "smooth",
+ "m_SerializedDescriptor": "SurfaceDescription.Smoothness", "m_Slots": [{"m_Id": "smooth_slot"}]}
+{"m_Type": "UnityEditor.ShaderGraph.Vector1MaterialSlot", "m_ObjectId": "smooth_slot",
+ "m_Id": 0, "m_Value": 0.75}
+{"m_Type": "UnityEditor.ShaderGraph.BlockNode", "m_ObjectId": "metal",
+ "m_SerializedDescriptor": "SurfaceDescription.Metallic", "m_Slots": [{"m_Id": "metal_slot"}]}
+{"m_Type": "UnityEditor.ShaderGraph.Vector1MaterialSlot", "m_ObjectId": "metal_slot",
+ "m_Id": 0, "m_Value": 0.25}
+{"m_Type": "UnityEditor.ShaderGraph.FractionNode", "m_ObjectId": "frac", "m_Slots": []}
+{"m_Type": "UnityEditor.ShaderGraph.VoronoiNode", "m_ObjectId": "voronoi", "m_Slots": []}
+"#;
+
+    fn find<'a>(doc: &'a Value, name: &str) -> &'a Value {
+        doc["nodes"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|node| node["name"] == name)
+            .unwrap_or_else(|| panic!("no `{name}` node"))
+    }
+
+    #[test]
+    fn master_stack() {
+        let (graph, report) = import_unity_str(GRAPH).unwrap();
+        let doc = graph_to_value(&graph).unwrap();
+
+        let output = find(&doc, "Pbr Fragment");
+        assert_eq!(doc["output"], node_id(output));
+        assert_eq!(output["node"]["metallic"]["value"], 0.25);
+
+        // Smoothness is connected through `1 - smoothness`.
+        let invert = find(&doc, "Subtract");
+        let frac = find(&doc, "Fraction");
+        assert_eq!(
+            input_source(&output["node"]["roughness"]),
+            Some((node_id(invert), 0))
+        );
+        assert_eq!(input_source(&invert["node"]["b"]), Some((node_id(frac), 0)));
+
+        assert_eq!(report.unsupported, ["VoronoiNode (voronoi)"]);
+    }
+
+    #[test]
+    fn smoothness_constant() {
+        let graph = GRAPH.replace(r#""m_Edges": [{"#, r#""m_Unused": [{"#);
+        let (graph, _) = import_unity_str(&graph).unwrap();
+        let doc = graph_to_value(&graph).unwrap();
+        let roughness = &find(&doc, "Pbr Fragment")["node"]["roughness"];
+        assert!(roughness["connected"].is_null());
+        assert_eq!(roughness["value"], 0.25);
+    }
+
+    #[test]
+    fn fresnel_node() {
+        let graph = r#"
+{"m_Type": "UnityEditor.ShaderGraph.GraphData", "m_ObjectId": "graph",
+ "m_Nodes": [{"m_Id": "metal"}, {"m_Id": "fresnel"}],
+ "m_Edges": [{"m_OutputSlot": {"m_Node": {"m_Id": "fresnel"}, "m_SlotId": 3},
+              "m_InputSlot": {"m_Node": {"m_Id": "metal"}, "m_SlotId": 0}}]}
+{"m_Type": "UnityEditor.ShaderGraph.BlockNode", "m_ObjectId": "metal",
+ "m_SerializedDescriptor": "SurfaceDescription.Metallic", "m_Slots": []}
+{"m_Type": "UnityEditor.ShaderGraph.FresnelNode", "m_ObjectId": "fresnel",
+ "m_Slots": [{"m_Id": "power_slot"}]}
+{"m_Type": "UnityEditor.ShaderGraph.Vector1MaterialSlot", "m_ObjectId": "power_slot",
+ "m_Id": 2, "m_Value": 3.0}
+"#;
+        let (graph, report) = import_unity_str(graph).unwrap();
+        assert!(report.unsupported.is_empty());
+        let doc = graph_to_value(&graph).unwrap();
+        let fresnel = find(&doc, "Fresnel Effect");
+        assert_eq!(fresnel["node"]["power"]["value"], 3.0);
+        assert_eq!(
+            input_source(&find(&doc, "Pbr Fragment")["node"]["metallic"]),
+            Some((node_id(fresnel), 0))
+        );
+    }
+
+    #[test]
+    fn placeholder_node() {
+        let (graph, _) = import_unity_str(GRAPH).unwrap();
+        let doc = graph_to_value(&graph).unwrap();
+        let placeholder = find(&doc, "Placeholder");
+        let group = doc["groups"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|group| group["id"] == placeholder["group_id"])
+            .unwrap();
+        assert_eq!(group["title"], "Unsupported: VoronoiNode (voronoi)");
+    }
+}
@@ -2,9 +2,50 @@
 
 extern crate alloc;
 
+pub mod attributes;
+pub use attributes::*;
+pub mod bake;
+pub use bake::*;
+pub use bevy_shader_graph_document::canonical;
+pub use canonical::*;
+pub mod compile;
+pub use compile::*;
+pub mod cost;
+pub use cost::*;
+pub mod decal;
+pub use decal::*;
+pub mod diff;
+pub use diff::*;
+pub use bevy_shader_graph_document::document;
+pub use document::*;
+pub mod embed;
+pub use embed::*;
+pub mod eval;
+pub use eval::*;
+pub mod export;
+pub use export::*;
 pub mod extended_material;
+pub mod import;
+pub mod lint;
+pub use lint::*;
 pub mod material;
 pub use material::*;
+#[cfg(feature = "sprite")]
+pub mod material2d;
+#[cfg(feature = "sprite")]
+pub use material2d::*;
+pub use bevy_shader_graph_document::migrate;
+pub use migrate::*;
+pub use bevy_shader_graph_nodes as nodes;
+pub use nodes::*;
+pub use bevy_shader_graph_document::optimize;
+pub use optimize::*;
+pub mod post_process;
+pub use post_process::*;
+#[cfg(feature = "ui")]
+pub mod ui_material;
+#[cfg(feature = "ui")]
+pub use ui_material::*;
 #[cfg(feature = "egui")]
 pub mod editor;
 #[cfg(feature = "egui")]
@@ -0,0 +1,411 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use anyhow::Result;
+use serde_json::Value;
+
+use node_engine::NodeGraph;
+
+use crate::document::{
+    doc_nodes, graph_to_value, input_source, is_input, node_id, node_kind, value_floats,
+    NodeTemplates,
+};
+
+/// Nodes the linter knows about.
+const LINT_NODES: &[&str] = &["Divide", "Split", "Combine", "Swizzle"];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LintLevel {
+    Info,
+    Warning,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum LintKind {
+    /// The node's outputs aren't used.
+    UnusedNode,
+    /// The node is used, but doesn't contribute to the graph's output.
+    UnreachableNode,
+    /// An input without a default value is not connected.
+    UnconnectedInput,
+    /// A conversion that does nothing (e.g. `Split` -> `Combine`).
+    RedundantConversion,
+    /// A subgraph that only depends on constants.
+    ConstantSubgraph,
+    /// A value that is probably wrong (e.g. divide by zero).
+    SuspiciousValue,
+}
+
+/// A problem found in a graph.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GraphLint {
+    pub level: LintLevel,
+    pub kind: LintKind,
+    /// The node with the problem.
+    pub node: Option<String>,
+    pub message: String,
+}
+
+impl fmt::Display for GraphLint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let level = match self.level {
+            LintLevel::Info => "info",
+            LintLevel::Warning => "warning",
+        };
+        match &self.node {
+            Some(node) => write!(f, "{level}: {} [{node}]", self.message),
+            None => write!(f, "{level}: {}", self.message),
+        }
+    }
+}
+
+/// Lint a shader graph.
+pub fn lint_graph(graph: &NodeGraph) -> Result<Vec<GraphLint>> {
+    lint_graph_with(graph, &mut NodeTemplates::default())
+}
+
+/// Lint a shader graph, reusing the node templates (e.g. the editor's).
+pub fn lint_graph_with(graph: &NodeGraph, templates: &mut NodeTemplates) -> Result<Vec<GraphLint>> {
+    let doc = graph_to_value(graph)?;
+    Ok(lint_document(&doc, templates))
+}
+
+/// Lint a graph document.
+pub fn lint_document(doc: &Value, templates: &mut NodeTemplates) -> Vec<GraphLint> {
+    templates.preload(LINT_NODES);
+    let nodes: HashMap<&str, &Value> = doc_nodes(doc).map(|n| (node_id(n), n)).collect();
+    let kinds: HashMap<&str, &str> = nodes
+        .iter()
+        .map(|(id, node)| (*id, node_kind(templates, node)))
+        .collect();
+    let output = doc.get("output").and_then(Value::as_str);
+
+    let mut lints = Vec::new();
+    let mut lint = |level, kind, node: &str, message: String| {
+        let name = kinds.get(node).copied().unwrap_or_default();
+        lints.push(GraphLint {
+            level,
+            kind,
+            node: Some(node.to_string()),
+            message: format!("{name}: {message}"),
+        });
+    };
+
+    let Some(output) = output else {
+        return vec![GraphLint {
+            level: LintLevel::Warning,
+            kind: LintKind::UnreachableNode,
+            node: None,
+            message: "The graph has no output node".to_string(),
+        }];
+    };
+
+    // Nodes used by other nodes.
+    let mut used = HashSet::new();
+    for node in nodes.values() {
+        for (_, field) in inputs(node) {
+            if let Some((src, _)) = input_source(field) {
+                used.insert(src);
+            }
+        }
+    }
+
+    // Nodes contributing to the output.
+    let mut reachable = HashSet::new();
+    let mut stack = vec![output];
+    while let Some(id) = stack.pop() {
+        if !reachable.insert(id) {
+            continue;
+        }
+        if let Some(node) = nodes.get(id) {
+            stack.extend(inputs(node).filter_map(|(_, f)| input_source(f).map(|(src, _)| src)));
+        }
+    }
+
+    // Nodes that only depend on constants.
+    let mut constant: HashMap<&str, bool> = HashMap::new();
+    for id in nodes.keys() {
+        is_constant(id, &nodes, &mut constant);
+    }
+
+    let mut ids: Vec<&str> = nodes.keys().copied().collect();
+    ids.sort();
+    for id in ids {
+        let node = nodes[id];
+        if id != output {
+            if !used.contains(id) {
+                lint(
+                    LintLevel::Warning,
+                    LintKind::UnusedNode,
+                    id,
+                    "output is not used".to_string(),
+                );
+                continue;
+            }
+            if !reachable.contains(id) {
+                lint(
+                    LintLevel::Warning,
+                    LintKind::UnreachableNode,
+                    id,
+                    "doesn't contribute to the output".to_string(),
+                );
+                continue;
+            }
+        }
+
+        for (name, field) in inputs(node) {
+            if field["connected"].is_null() && field["value"].is_null() {
+                lint(
+                    LintLevel::Warning,
+                    LintKind::UnconnectedInput,
+                    id,
+                    format!("input `{name}` is not connected"),
+                );
+            }
+            if let Some(values) = value_floats(&field["value"]) {
+                if field["connected"].is_null() && values.iter().any(|v| !v.is_finite()) {
+                    lint(
+                        LintLevel::Warning,
+                        LintKind::SuspiciousValue,
+                        id,
+                        format!("input `{name}` is not a finite number"),
+                    );
+                }
+            }
+        }
+
+        match kinds[id] {
+            "Divide" => {
+                let b = &node["node"]["b"];
+                let zero = b["connected"].is_null()
+                    && value_floats(&b["value"]).is_some_and(|v| v.contains(&0.0));
+                if zero {
+                    lint(
+                        LintLevel::Warning,
+                        LintKind::SuspiciousValue,
+                        id,
+                        "divide by a constant zero".to_string(),
+                    );
+                }
+            }
+            "Swizzle" => {
+                let mask = node["node"]["swizzle"].as_str().unwrap_or_default();
+                if mask == "xyzw" || mask == "rgba" {
+                    lint(
+                        LintLevel::Info,
+                        LintKind::RedundantConversion,
+                        id,
+                        format!("swizzle `{mask}` does nothing"),
+                    );
+                }
+            }
+            "Combine" => {
+                // `Split` -> `Combine` with the components in order.
+                let sources: Vec<Option<(&str, u64)>> =
+                    inputs(node).map(|(_, f)| input_source(f)).collect();
+                let split = sources.first().copied().flatten().map(|(src, _)| src);
+                let round_trip = split.is_some_and(|split| {
+                    kinds.get(split) == Some(&"Split")
+                        && sources.len() >= 3
+                        && sources
+                            .iter()
+                            .take(3)
+                            .enumerate()
+                            .all(|(idx, src)| *src == Some((split, idx as u64)))
+                });
+                if round_trip {
+                    lint(
+                        LintLevel::Info,
+                        LintKind::RedundantConversion,
+                        id,
+                        "combines the components of a `Split` in order".to_string(),
+                    );
+                }
+            }
+            _ => {}
+        }
+
+        // Report the root of constant subgraphs, the output node can't be replaced.
+        let has_inputs = inputs(node).next().is_some();
+        let consumers_constant = nodes.values().any(|other| {
+            inputs(other).any(|(_, f)| input_source(f).is_some_and(|(src, _)| src == id))
+                && node_id(other) != output
+                && constant.get(node_id(other)) == Some(&true)
+        });
+        if has_inputs && id != output && constant[id] && !consumers_constant {
+            lint(
+                LintLevel::Info,
+                LintKind::ConstantSubgraph,
+                id,
+                "only depends on constants and could be replaced by a value".to_string(),
+            );
+        }
+    }
+    lints
+}
+
+/// The inputs of a document node, in input order.
+fn inputs(node: &Value) -> impl Iterator<Item = (&String, &Value)> {
+    node["node"]
+        .as_object()
+        .into_iter()
+        .flatten()
+        .filter(|(_, field)| is_input(field))
+}
+
+/// A node is constant if all its inputs are constant.  Nodes without inputs
+/// are constant if they only have numeric parameters (e.g. a float node).
+fn is_constant<'a>(
+    id: &'a str,
+    nodes: &HashMap<&'a str, &'a Value>,
+    cache: &mut HashMap<&'a str, bool>,
+) -> bool {
+    if let Some(constant) = cache.get(id) {
+        return *constant;
+    }
+    // Break cycles.
+    cache.insert(id, false);
+    let Some(node) = nodes.get(id) else {
+        return false;
+    };
+    let constant = if inputs(node).next().is_some() {
+        inputs(node).all(|(_, field)| match input_source(field) {
+            Some((src, _)) => match nodes.get_key_value(src) {
+                Some((src, _)) => is_constant(src, nodes, cache),
+                None => false,
+            },
+            None => !field["value"].is_null(),
+        })
+    } else {
+        let fields = node["node"].as_object();
+        let numeric = |v: &Value| {
+            v.is_number() || v.as_array().is_some_and(|a| a.iter().all(Value::is_number))
+        };
+        fields.is_some_and(|fields| !fields.is_empty() && fields.values().all(numeric))
+    };
+    cache.insert(id, constant);
+    constant
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::math::Vec2;
+
+    use super::*;
+    use crate::import::GraphBuilder;
+
+    fn lints(builder: &GraphBuilder) -> Vec<GraphLint> {
+        let mut templates = NodeTemplates::default();
+        lint_document(&builder.document(), &mut templates)
+    }
+
+    fn of_kind(lints: &[GraphLint], kind: LintKind) -> Vec<&str> {
+        let mut nodes: Vec<&str> = lints
+            .iter()
+            .filter(|lint| lint.kind == kind)
+            .filter_map(|lint| lint.node.as_deref())
+            .collect();
+        nodes.sort();
+        nodes
+    }
+
+    #[test]
+    fn no_output() {
+        let mut builder = GraphBuilder::default();
+        builder.add_node("Add", Vec2::ZERO).unwrap();
+        let lints = lints(&builder);
+        assert_eq!(lints.len(), 1);
+        assert_eq!(lints[0].node, None);
+        assert_eq!(lints[0].level, LintLevel::Warning);
+    }
+
+    #[test]
+    fn unused_and_unreachable() {
+        let mut builder = GraphBuilder::default();
+        builder.add_output("Pbr Fragment", Vec2::ZERO).unwrap();
+        let add = builder.add_node("Add", Vec2::ZERO).unwrap();
+        let multiply = builder.add_node("Multiply", Vec2::ZERO).unwrap();
+        builder.connect(&add, 0, &multiply, "a").unwrap();
+
+        let lints = lints(&builder);
+        assert_eq!(of_kind(&lints, LintKind::UnusedNode), [multiply.as_str()]);
+        assert_eq!(of_kind(&lints, LintKind::UnreachableNode), [add.as_str()]);
+        assert!(
+            lints
+                .iter()
+                .filter(|lint| lint.kind == LintKind::UnusedNode)
+                .all(|lint| lint.level == LintLevel::Warning
+                    && lint.message.starts_with("Multiply: "))
+        );
+    }
+
+    #[test]
+    fn divide_by_zero() {
+        let mut builder = GraphBuilder::default();
+        let output = builder.add_output("Pbr Fragment", Vec2::ZERO).unwrap();
+        let uv = builder.add_node("Uv", Vec2::ZERO).unwrap();
+        let divide = builder.add_node("Divide", Vec2::ZERO).unwrap();
+        builder.set_input(&divide, "b", &[0.0]);
+        builder.connect(&uv, 0, &divide, "a").unwrap();
+        builder.connect(&divide, 0, &output, "roughness").unwrap();
+
+        let lints = lints(&builder);
+        assert_eq!(
+            of_kind(&lints, LintKind::SuspiciousValue),
+            [divide.as_str()]
+        );
+        assert!(of_kind(&lints, LintKind::UnusedNode).is_empty());
+
+        // Connecting `b` removes the warning.
+        builder.connect(&uv, 0, &divide, "b").unwrap();
+        let lints = self::lints(&builder);
+        assert!(of_kind(&lints, LintKind::SuspiciousValue).is_empty());
+    }
+
+    #[test]
+    fn split_combine_round_trip() {
+        let mut builder = GraphBuilder::default();
+        let output = builder.add_output("Pbr Fragment", Vec2::ZERO).unwrap();
+        let uv = builder.add_node("Uv", Vec2::ZERO).unwrap();
+        let split = builder.add_node("Split", Vec2::ZERO).unwrap();
+        let combine = builder.add_node("Combine", Vec2::ZERO).unwrap();
+        builder.connect(&uv, 0, &split, "input").unwrap();
+        for (idx, input) in ["r", "g", "b"].into_iter().enumerate() {
+            builder
+                .connect(&split, idx as u32, &combine, input)
+                .unwrap();
+        }
+        builder.connect(&combine, 0, &output, "emission").unwrap();
+
+        let lints = lints(&builder);
+        assert_eq!(
+            of_kind(&lints, LintKind::RedundantConversion),
+            [combine.as_str()]
+        );
+
+        // Swapped components aren't redundant.
+        builder.connect(&split, 1, &combine, "r").unwrap();
+        let lints = self::lints(&builder);
+        assert!(of_kind(&lints, LintKind::RedundantConversion).is_empty());
+    }
+
+    #[test]
+    fn constant_subgraph() {
+        let mut builder = GraphBuilder::default();
+        let output = builder.add_output("Pbr Fragment", Vec2::ZERO).unwrap();
+        let add = builder.add_node("Add", Vec2::ZERO).unwrap();
+        let multiply = builder.add_node("Multiply", Vec2::ZERO).unwrap();
+        builder.set_input(&add, "a", &[1.0]);
+        builder.set_input(&add, "b", &[2.0]);
+        builder.set_input(&multiply, "b", &[0.5]);
+        builder.connect(&add, 0, &multiply, "a").unwrap();
+        builder.connect(&multiply, 0, &output, "metallic").unwrap();
+
+        // Only the root of the constant subgraph is reported.
+        let lints = lints(&builder);
+        assert_eq!(
+            of_kind(&lints, LintKind::ConstantSubgraph),
+            [multiply.as_str()]
+        );
+    }
+}
@@ -1,19 +1,92 @@
+use crate::attributes::GraphAttributeRequirements;
 use crate::extended_material::{
     ExtendedMaterial, MaterialExtension, MaterialExtensionKey, MaterialExtensionPipeline,
 };
+use crate::nodes::MasterMode;
 use bevy::{
-    mesh::MeshVertexBufferLayoutRef, prelude::*, reflect::Reflect, render::render_resource::*,
+    asset::{load_internal_asset, uuid_handle},
+    mesh::MeshVertexBufferLayoutRef,
+    pbr::{MaterialPipeline, MaterialPipelineKey, MeshPipelineKey},
+    prelude::*,
+    reflect::{GetTypeRegistration, Reflect},
+    render::render_resource::*,
 };
+use std::{hash::Hash, marker::PhantomData};
+
+/// Default fragment/prepass template, used when a `ShaderGraph` has no compiled fragment.
+pub const SHADER_GRAPH_DEFAULT_FRAGMENT: Handle<Shader> =
+    uuid_handle!("4f3a1a52-0d3e-4b89-9d2c-6b1b0a7e5c21");
+
+/// `bevy_shader_graph::pbr_input` shader library, imported by the templates and master nodes.
+pub const SHADER_GRAPH_PBR_INPUT: Handle<Shader> =
+    uuid_handle!("9b2d6c1e-3f47-4a8e-b5d0-7c6e2a914f38");
+
+/// `bevy_shader_graph::lighting` shader library, imported by the light nodes.
+pub const SHADER_GRAPH_LIGHTING: Handle<Shader> =
+    uuid_handle!("c5e81f7a-62d4-4b1e-9a37-0e4d8b2f6a93");
+
+/// Shader def set by `BlankMaterial`, the graph's `PbrInput` is built without
+/// `StandardMaterial` bindings.
+pub const SHADER_GRAPH_BLANK_BASE: &str = "SHADER_GRAPH_BLANK_BASE";
 
 #[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
 pub struct ShaderGraph {
     pub vertex: Option<Handle<Shader>>,
     pub fragment: Option<Handle<Shader>>,
+    /// Mesh vertex attributes used by the graph's shaders.
+    pub attributes: GraphAttributeRequirements,
 }
 
-pub type StandardShaderGraphMaterial = ExtendedMaterial<StandardMaterial, ShaderGraphMaterial>;
+/// A material (or component) that runs a graph's compiled fragment shader.
+///
+/// The editor only applies its shader to targets that support the graph's mode.
+pub trait ShaderGraphTarget {
+    /// Graph modes the target can run.
+    const MODES: &'static [MasterMode];
+
+    /// The compiled fragment shader.
+    fn fragment(&self) -> Option<&Handle<Shader>>;
+
+    /// Use a compiled fragment shader and the mesh attributes it reads.
+    fn set_fragment(&mut self, fragment: Handle<Shader>, attributes: GraphAttributeRequirements);
+}
+
+/// A base material `B` extended with a shader graph.
+pub type ShaderGraphExtendedMaterial<B> = ExtendedMaterial<B, ShaderGraphMaterial>;
+
+pub type StandardShaderGraphMaterial = ShaderGraphExtendedMaterial<StandardMaterial>;
+
+/// A shader graph on a blank base material.
+pub type BlankShaderGraphMaterial = ShaderGraphExtendedMaterial<BlankMaterial>;
+
+/// Base material without any bindings.
+///
+/// Graphs get a `PbrInput` with default material values, so only the `Unlit Fragment`
+/// and `Custom Lighting Fragment` outputs (and the default template) are supported,
+/// the PBR `Fragment` output reads the `StandardMaterial` bindings.
+#[derive(Asset, AsBindGroup, Reflect, Debug, Clone, Default)]
+#[reflect(Default, Debug)]
+pub struct BlankMaterial {}
+
+impl Material for BlankMaterial {
+    fn specialize(
+        _pipeline: &MaterialPipeline,
+        descriptor: &mut RenderPipelineDescriptor,
+        _layout: &MeshVertexBufferLayoutRef,
+        _key: MaterialPipelineKey<Self>,
+    ) -> Result<(), SpecializedMeshPipelineError> {
+        descriptor
+            .vertex
+            .shader_defs
+            .push(SHADER_GRAPH_BLANK_BASE.into());
+        if let Some(fragment) = descriptor.fragment.as_mut() {
+            fragment.shader_defs.push(SHADER_GRAPH_BLANK_BASE.into());
+        }
+        Ok(())
+    }
+}
 
-#[derive(Asset, AsBindGroup, Reflect, Debug, Clone)]
+#[derive(Asset, AsBindGroup, Reflect, Debug, Clone, Default)]
 #[bind_group_data(ShaderGraph)]
 #[reflect(Default, Debug)]
 pub struct ShaderGraphMaterial {
@@ -23,37 +96,56 @@ pub struct ShaderGraphMaterial {
     pub graph: ShaderGraph,
 }
 
-impl Default for ShaderGraphMaterial {
-    fn default() -> Self {
-        Self {
-            prop4: Default::default(),
-            graph: Default::default(),
-        }
-    }
-}
-
 impl From<&ShaderGraphMaterial> for ShaderGraph {
     fn from(material: &ShaderGraphMaterial) -> Self {
         material.graph.clone()
     }
 }
 
+impl<B: Material> ShaderGraphTarget for ShaderGraphExtendedMaterial<B> {
+    const MODES: &'static [MasterMode] = &[
+        MasterMode::Pbr,
+        MasterMode::Unlit,
+        MasterMode::CustomLighting,
+    ];
+
+    fn fragment(&self) -> Option<&Handle<Shader>> {
+        self.extension.graph.fragment.as_ref()
+    }
+
+    fn set_fragment(&mut self, fragment: Handle<Shader>, attributes: GraphAttributeRequirements) {
+        self.extension.graph.fragment = Some(fragment);
+        self.extension.graph.attributes = attributes;
+    }
+}
+
 impl MaterialExtension for ShaderGraphMaterial {
     fn specialize(
         _pipeline: &MaterialExtensionPipeline,
         descriptor: &mut RenderPipelineDescriptor,
-        _layout: &MeshVertexBufferLayoutRef,
+        layout: &MeshVertexBufferLayoutRef,
         key: MaterialExtensionKey<Self>,
     ) -> Result<(), SpecializedMeshPipelineError> {
         //eprintln!("-- mesh.layout={layout:#?}");
         //eprintln!("-- pipeline.layout={:#?}", descriptor.layout);
+        key.bind_group_data.attributes.specialize(layout)?;
+
         if let Some(new_vertex) = key.bind_group_data.vertex {
             descriptor.vertex.shader = new_vertex;
         }
 
-        if let Some(new_fragment) = key.bind_group_data.fragment {
-            if let Some(fragment) = descriptor.fragment.as_mut() {
-                fragment.shader = new_fragment;
+        if let Some(fragment) = descriptor.fragment.as_mut() {
+            match key.bind_group_data.fragment {
+                Some(new_fragment) => {
+                    fragment.shader = new_fragment;
+                }
+                None => {
+                    // The default template only handles the main pass and the deferred prepass.
+                    let prepass = fragment.shader_defs.contains(&"PREPASS_PIPELINE".into());
+                    if !prepass || key.mesh_key.contains(MeshPipelineKey::DEFERRED_PREPASS) {
+                        fragment.shader = SHADER_GRAPH_DEFAULT_FRAGMENT;
+                    }
+                }
             }
         }
 
@@ -61,13 +153,77 @@ impl MaterialExtension for ShaderGraphMaterial {
     }
 }
 
+/// Shaders shared by all `ShaderGraphMaterialPlugin`s.
 #[derive(Default, Clone, Debug)]
-pub struct ShaderGraphMaterialPlugin;
+pub struct ShaderGraphShadersPlugin;
+
+impl Plugin for ShaderGraphShadersPlugin {
+    fn build(&self, app: &mut App) {
+        load_internal_asset!(
+            app,
+            SHADER_GRAPH_PBR_INPUT,
+            "shaders/pbr_input.wgsl",
+            Shader::from_wgsl
+        );
+        load_internal_asset!(
+            app,
+            SHADER_GRAPH_LIGHTING,
+            "shaders/lighting.wgsl",
+            Shader::from_wgsl
+        );
+        load_internal_asset!(
+            app,
+            SHADER_GRAPH_DEFAULT_FRAGMENT,
+            "shaders/shader_graph.wgsl",
+            Shader::from_wgsl
+        );
+
+        app.register_asset_reflect::<ShaderGraphMaterial>();
+    }
+}
 
-impl Plugin for ShaderGraphMaterialPlugin {
+/// Registers `ShaderGraphExtendedMaterial<B>`, add one per base material.
+///
+/// ```ignore
+/// app.add_plugins((
+///     ShaderGraphMaterialPlugin::<StandardMaterial>::default(),
+///     ShaderGraphMaterialPlugin::<BlankMaterial>::default(),
+/// ));
+/// ```
+pub struct ShaderGraphMaterialPlugin<B: Material = StandardMaterial>(PhantomData<fn() -> B>);
+
+impl<B: Material> Default for ShaderGraphMaterialPlugin<B> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<B: Material> Clone for ShaderGraphMaterialPlugin<B> {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+impl<B: Material> std::fmt::Debug for ShaderGraphMaterialPlugin<B> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("ShaderGraphMaterialPlugin")
+            .field(&std::any::type_name::<B>())
+            .finish()
+    }
+}
+
+impl<B> Plugin for ShaderGraphMaterialPlugin<B>
+where
+    B: Material,
+    ShaderGraphExtendedMaterial<B>: FromReflect + GetTypeRegistration,
+    <ShaderGraphExtendedMaterial<B> as AsBindGroup>::Data: PartialEq + Eq + Hash + Clone,
+{
     fn build(&self, app: &mut App) {
-        app.add_plugins(MaterialPlugin::<StandardShaderGraphMaterial>::default())
-            .register_asset_reflect::<StandardShaderGraphMaterial>()
-            .register_asset_reflect::<ShaderGraphMaterial>();
+        if !app.is_plugin_added::<ShaderGraphShadersPlugin>() {
+            app.add_plugins(ShaderGraphShadersPlugin);
+        }
+
+        app.add_plugins(MaterialPlugin::<ShaderGraphExtendedMaterial<B>>::default())
+            .register_asset_reflect::<ShaderGraphExtendedMaterial<B>>();
     }
 }
@@ -0,0 +1,104 @@
+use crate::attributes::GraphAttributeRequirements;
+use crate::material::{ShaderGraph, ShaderGraphTarget};
+use crate::nodes::MasterMode;
+use bevy::{
+    asset::{load_internal_asset, uuid_handle},
+    mesh::MeshVertexBufferLayoutRef,
+    prelude::*,
+    reflect::Reflect,
+    render::render_resource::*,
+    shader::ShaderRef,
+    sprite_render::{AlphaMode2d, Material2d, Material2dKey, Material2dPlugin},
+};
+
+/// Default 2D fragment template, used when a `ShaderGraph` has no compiled fragment.
+pub const SHADER_GRAPH_DEFAULT_FRAGMENT_2D: Handle<Shader> =
+    uuid_handle!("c8e41f0a-62b7-4d5e-9a13-5f7d2b8e60c4");
+
+/// A shader graph on a `Mesh2d`, the graph's output node is `2D Fragment`.
+#[derive(Asset, AsBindGroup, Reflect, Debug, Clone)]
+#[bind_group_data(ShaderGraph)]
+#[reflect(Default, Debug)]
+pub struct ShaderGraphMaterial2d {
+    #[uniform(100)]
+    pub prop4: Vec4,
+    pub alpha_mode: AlphaMode2d,
+    #[reflect(ignore)]
+    pub graph: ShaderGraph,
+}
+
+impl Default for ShaderGraphMaterial2d {
+    fn default() -> Self {
+        Self {
+            prop4: Default::default(),
+            alpha_mode: AlphaMode2d::Blend,
+            graph: Default::default(),
+        }
+    }
+}
+
+impl From<&ShaderGraphMaterial2d> for ShaderGraph {
+    fn from(material: &ShaderGraphMaterial2d) -> Self {
+        material.graph.clone()
+    }
+}
+
+impl ShaderGraphTarget for ShaderGraphMaterial2d {
+    const MODES: &'static [MasterMode] = &[MasterMode::Mesh2d];
+
+    fn fragment(&self) -> Option<&Handle<Shader>> {
+        self.graph.fragment.as_ref()
+    }
+
+    fn set_fragment(&mut self, fragment: Handle<Shader>, attributes: GraphAttributeRequirements) {
+        self.graph.fragment = Some(fragment);
+        self.graph.attributes = attributes;
+    }
+}
+
+impl Material2d for ShaderGraphMaterial2d {
+    fn fragment_shader() -> ShaderRef {
+        SHADER_GRAPH_DEFAULT_FRAGMENT_2D.into()
+    }
+
+    fn alpha_mode(&self) -> AlphaMode2d {
+        self.alpha_mode
+    }
+
+    fn specialize(
+        descriptor: &mut RenderPipelineDescriptor,
+        layout: &MeshVertexBufferLayoutRef,
+        key: Material2dKey<Self>,
+    ) -> Result<(), SpecializedMeshPipelineError> {
+        key.bind_group_data.attributes.specialize(layout)?;
+
+        if let Some(new_vertex) = key.bind_group_data.vertex {
+            descriptor.vertex.shader = new_vertex;
+        }
+
+        if let (Some(fragment), Some(new_fragment)) =
+            (descriptor.fragment.as_mut(), key.bind_group_data.fragment)
+        {
+            fragment.shader = new_fragment;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Default, Clone, Debug)]
+pub struct ShaderGraphMaterial2dPlugin;
+
+impl Plugin for ShaderGraphMaterial2dPlugin {
+    fn build(&self, app: &mut App) {
+        load_internal_asset!(
+            app,
+            SHADER_GRAPH_DEFAULT_FRAGMENT_2D,
+            "shaders/shader_graph_2d.wgsl",
+            Shader::from_wgsl
+        );
+
+        app.add_plugins(Material2dPlugin::<ShaderGraphMaterial2d>::default())
+            .register_asset_reflect::<ShaderGraphMaterial2d>();
+    }
+}
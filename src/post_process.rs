@@ -0,0 +1,324 @@
+use bevy::{
+    asset::{load_internal_asset, uuid_handle},
+    core_pipeline::{
+        core_3d::graph::{Core3d, Node3d},
+        prepass::{DepthPrepass, NormalPrepass, ViewPrepassTextures},
+        FullscreenShader,
+    },
+    ecs::query::QueryItem,
+    prelude::*,
+    render::{
+        extract_component::{
+            ComponentUniforms, DynamicUniformIndex, ExtractComponent, ExtractComponentPlugin,
+            UniformComponentPlugin,
+        },
+        globals::{GlobalsBuffer, GlobalsUniform},
+        render_graph::{
+            NodeRunError, RenderGraphContext, RenderGraphExt, RenderLabel, ViewNode, ViewNodeRunner,
+        },
+        render_resource::{
+            binding_types::{sampler, texture_2d, texture_depth_2d, uniform_buffer},
+            *,
+        },
+        renderer::{RenderContext, RenderDevice},
+        view::{ViewTarget, ViewUniform, ViewUniformOffset, ViewUniforms},
+        Render, RenderApp, RenderSystems,
+    },
+    shader::ShaderDefVal,
+};
+
+use crate::attributes::GraphAttributeRequirements;
+use crate::material::ShaderGraphTarget;
+use crate::nodes::MasterMode;
+
+/// `bevy_shader_graph::post_process` shader library, with the screen, depth and normal textures.
+pub const SHADER_GRAPH_POST_PROCESS: Handle<Shader> =
+    uuid_handle!("5e0a9d37-b1c4-4f86-a2e8-93d6c7f1b40e");
+
+/// Run a post-process graph as a fullscreen pass on this camera.
+///
+/// The graph's output node is `Post Process`.  The depth and normal prepasses are
+/// required for the `Scene Depth` and `Scene Normal` nodes.  The pass binds them as
+/// single sampled textures, so `Msaa::Off` is required and the pass is skipped on
+/// cameras with MSAA.
+#[derive(Component, Clone, Debug, Default, Reflect)]
+#[reflect(Component, Default, Debug)]
+#[require(DepthPrepass, NormalPrepass, Msaa::Off)]
+pub struct ShaderGraphPostProcess {
+    /// Graph properties, the same uniform as `ShaderGraphMaterial::prop4`.
+    pub prop4: Vec4,
+    /// Compiled graph, the pass is skipped until it is set.
+    #[reflect(ignore)]
+    pub shader: Option<Handle<Shader>>,
+}
+
+impl ShaderGraphTarget for ShaderGraphPostProcess {
+    const MODES: &'static [MasterMode] = &[MasterMode::PostProcess];
+
+    fn fragment(&self) -> Option<&Handle<Shader>> {
+        self.shader.as_ref()
+    }
+
+    /// Fullscreen passes don't have mesh attributes.
+    fn set_fragment(&mut self, fragment: Handle<Shader>, _attributes: GraphAttributeRequirements) {
+        self.shader = Some(fragment);
+    }
+}
+
+/// Graph properties of a post-process pass.
+#[derive(Component, ShaderType, Clone, Copy, Debug, Default)]
+pub struct ShaderGraphPostProcessUniform {
+    pub prop4: Vec4,
+}
+
+/// Compiled graph of a post-process pass, in the render world.
+#[derive(Component, Clone, Debug)]
+pub struct ShaderGraphPostProcessShader(pub Handle<Shader>);
+
+impl ExtractComponent for ShaderGraphPostProcess {
+    type QueryData = &'static Self;
+    type QueryFilter = With<Camera>;
+    type Out = (ShaderGraphPostProcessUniform, ShaderGraphPostProcessShader);
+
+    fn extract_component(item: QueryItem<'_, '_, Self::QueryData>) -> Option<Self::Out> {
+        let shader = item.shader.clone()?;
+        Some((
+            ShaderGraphPostProcessUniform { prop4: item.prop4 },
+            ShaderGraphPostProcessShader(shader),
+        ))
+    }
+}
+
+#[derive(Default, Clone, Debug)]
+pub struct ShaderGraphPostProcessPlugin;
+
+impl Plugin for ShaderGraphPostProcessPlugin {
+    fn build(&self, app: &mut App) {
+        load_internal_asset!(
+            app,
+            SHADER_GRAPH_POST_PROCESS,
+            "shaders/post_process.wgsl",
+            Shader::from_wgsl
+        );
+
+        app.register_type::<ShaderGraphPostProcess>().add_plugins((
+            ExtractComponentPlugin::<ShaderGraphPostProcess>::default(),
+            UniformComponentPlugin::<ShaderGraphPostProcessUniform>::default(),
+        ));
+
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        render_app
+            .init_resource::<SpecializedRenderPipelines<ShaderGraphPostProcessPipeline>>()
+            .add_systems(
+                Render,
+                prepare_post_process_pipelines.in_set(RenderSystems::Prepare),
+            )
+            .add_render_graph_node::<ViewNodeRunner<ShaderGraphPostProcessNode>>(
+                Core3d,
+                ShaderGraphPostProcessLabel,
+            )
+            .add_render_graph_edges(
+                Core3d,
+                (
+                    Node3d::Tonemapping,
+                    ShaderGraphPostProcessLabel,
+                    Node3d::EndMainPassPostProcessing,
+                ),
+            );
+    }
+
+    fn finish(&self, app: &mut App) {
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        render_app.init_resource::<ShaderGraphPostProcessPipeline>();
+    }
+}
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct ShaderGraphPostProcessLabel;
+
+#[derive(Resource)]
+pub struct ShaderGraphPostProcessPipeline {
+    layout: BindGroupLayout,
+    sampler: Sampler,
+    fullscreen_shader: FullscreenShader,
+}
+
+impl FromWorld for ShaderGraphPostProcessPipeline {
+    fn from_world(world: &mut World) -> Self {
+        let render_device = world.resource::<RenderDevice>();
+        // Bindings 0-5 match `bevy_shader_graph::post_process`, the graph properties
+        // keep the material binding.
+        let layout = render_device.create_bind_group_layout(
+            "shader_graph_post_process_bind_group_layout",
+            &BindGroupLayoutEntries::with_indices(
+                ShaderStages::FRAGMENT,
+                (
+                    (0, texture_2d(TextureSampleType::Float { filterable: true })),
+                    (1, sampler(SamplerBindingType::Filtering)),
+                    (2, texture_depth_2d()),
+                    (
+                        3,
+                        texture_2d(TextureSampleType::Float { filterable: false }),
+                    ),
+                    (4, uniform_buffer::<GlobalsUniform>(false)),
+                    (5, uniform_buffer::<ViewUniform>(true)),
+                    (100, uniform_buffer::<ShaderGraphPostProcessUniform>(true)),
+                ),
+            ),
+        );
+        let sampler = render_device.create_sampler(&SamplerDescriptor::default());
+        let fullscreen_shader = world.resource::<FullscreenShader>().clone();
+        Self {
+            layout,
+            sampler,
+            fullscreen_shader,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ShaderGraphPostProcessKey {
+    pub shader: Handle<Shader>,
+    pub format: TextureFormat,
+}
+
+impl SpecializedRenderPipeline for ShaderGraphPostProcessPipeline {
+    type Key = ShaderGraphPostProcessKey;
+
+    fn specialize(&self, key: Self::Key) -> RenderPipelineDescriptor {
+        RenderPipelineDescriptor {
+            label: Some("shader_graph_post_process_pipeline".into()),
+            layout: vec![self.layout.clone()],
+            vertex: self.fullscreen_shader.to_vertex_state(),
+            fragment: Some(FragmentState {
+                shader: key.shader,
+                // Graph properties are declared in the material bind group.
+                shader_defs: vec![ShaderDefVal::UInt("MATERIAL_BIND_GROUP".into(), 0)],
+                targets: vec![Some(ColorTargetState {
+                    format: key.format,
+                    blend: None,
+                    write_mask: ColorWrites::ALL,
+                })],
+                ..default()
+            }),
+            ..default()
+        }
+    }
+}
+
+/// Pipeline of a camera's post-process pass.
+#[derive(Component)]
+pub struct ShaderGraphPostProcessPipelineId(pub CachedRenderPipelineId);
+
+fn prepare_post_process_pipelines(
+    mut commands: Commands,
+    pipeline_cache: Res<PipelineCache>,
+    mut pipelines: ResMut<SpecializedRenderPipelines<ShaderGraphPostProcessPipeline>>,
+    pipeline: Res<ShaderGraphPostProcessPipeline>,
+    views: Query<(Entity, &ViewTarget, &ShaderGraphPostProcessShader)>,
+) {
+    for (entity, target, shader) in &views {
+        let key = ShaderGraphPostProcessKey {
+            shader: shader.0.clone(),
+            format: target.main_texture_format(),
+        };
+        let id = pipelines.specialize(&pipeline_cache, &pipeline, key);
+        commands
+            .entity(entity)
+            .insert(ShaderGraphPostProcessPipelineId(id));
+    }
+}
+
+#[derive(Default)]
+pub struct ShaderGraphPostProcessNode;
+
+impl ViewNode for ShaderGraphPostProcessNode {
+    type ViewQuery = (
+        &'static ViewTarget,
+        &'static ViewPrepassTextures,
+        &'static ShaderGraphPostProcessPipelineId,
+        &'static DynamicUniformIndex<ShaderGraphPostProcessUniform>,
+        &'static ViewUniformOffset,
+        &'static Msaa,
+    );
+
+    fn run<'w>(
+        &self,
+        _graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext<'w>,
+        (view_target, prepass_textures, pipeline_id, uniform_index, view_offset, msaa): QueryItem<
+            'w,
+            '_,
+            Self::ViewQuery,
+        >,
+        world: &'w World,
+    ) -> Result<(), NodeRunError> {
+        // The layout binds single sampled prepass textures.
+        if *msaa != Msaa::Off {
+            bevy::utils::once!(log::warn!(
+                "ShaderGraphPostProcess needs `Msaa::Off` on the camera, skipping the pass"
+            ));
+            return Ok(());
+        }
+        let pipeline = world.resource::<ShaderGraphPostProcessPipeline>();
+        let pipeline_cache = world.resource::<PipelineCache>();
+        // The graph is still compiling (or failed to).
+        let Some(render_pipeline) = pipeline_cache.get_render_pipeline(pipeline_id.0) else {
+            return Ok(());
+        };
+        let (Some(depth), Some(normal)) = (
+            prepass_textures.depth_view(),
+            prepass_textures.normal_view(),
+        ) else {
+            return Ok(());
+        };
+        let uniforms = world.resource::<ComponentUniforms<ShaderGraphPostProcessUniform>>();
+        let Some(uniforms) = uniforms.uniforms().binding() else {
+            return Ok(());
+        };
+        let Some(globals) = world.resource::<GlobalsBuffer>().buffer.binding() else {
+            return Ok(());
+        };
+        let Some(view) = world.resource::<ViewUniforms>().uniforms.binding() else {
+            return Ok(());
+        };
+
+        let post_process = view_target.post_process_write();
+        let bind_group = render_context.render_device().create_bind_group(
+            "shader_graph_post_process_bind_group",
+            &pipeline.layout,
+            &BindGroupEntries::with_indices((
+                (0, post_process.source),
+                (1, &pipeline.sampler),
+                (2, depth),
+                (3, normal),
+                (4, globals),
+                (5, view),
+                (100, uniforms),
+            )),
+        );
+
+        let mut render_pass = render_context.begin_tracked_render_pass(RenderPassDescriptor {
+            label: Some("shader_graph_post_process_pass"),
+            color_attachments: &[Some(RenderPassColorAttachment {
+                view: post_process.destination,
+                depth_slice: None,
+                resolve_target: None,
+                ops: Operations::default(),
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        render_pass.set_render_pipeline(render_pipeline);
+        // Dynamic offsets are in binding order.
+        render_pass.set_bind_group(0, &bind_group, &[view_offset.offset, uniform_index.index()]);
+        render_pass.draw(0..3, 0..1);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,73 @@
+#define_import_path bevy_shader_graph::lighting
+
+#import bevy_pbr::pbr_types::PbrInput
+
+#ifndef PREPASS_PIPELINE
+#import bevy_pbr::{
+	mesh_view_bindings::{lights, view},
+	mesh_view_types::DIRECTIONAL_LIGHT_FLAGS_SHADOWS_ENABLED_BIT,
+	shadows::fetch_directional_shadow,
+}
+#endif
+
+// Light data for `Custom Lighting Fragment` graphs.  Colors are scaled by the
+// camera exposure, like Bevy's PBR lighting.  The prepass has no light bindings,
+// so it gets no light.
+
+// Direction towards directional light `idx`, zero if the scene has no such light.
+fn graph_light_direction(idx: u32) -> vec3<f32> {
+#ifndef PREPASS_PIPELINE
+	if idx < lights.n_directional_lights {
+		return lights.directional_lights[idx].direction_to_light;
+	}
+#endif
+	return vec3<f32>(0.0);
+}
+
+// Color of directional light `idx`, scaled by illuminance and exposure.
+fn graph_light_color(idx: u32) -> vec3<f32> {
+#ifndef PREPASS_PIPELINE
+	if idx < lights.n_directional_lights {
+		return lights.directional_lights[idx].color.rgb * view.exposure;
+	}
+#endif
+	return vec3<f32>(0.0);
+}
+
+// Shadow attenuation of directional light `idx`, 1.0 is fully lit.
+fn graph_light_shadow(pbr_input: PbrInput, idx: u32) -> f32 {
+#ifndef PREPASS_PIPELINE
+	if idx < lights.n_directional_lights
+		&& (lights.directional_lights[idx].flags & DIRECTIONAL_LIGHT_FLAGS_SHADOWS_ENABLED_BIT) != 0u {
+		let view_z = dot(vec4<f32>(
+			view.view_from_world[0].z,
+			view.view_from_world[1].z,
+			view.view_from_world[2].z,
+			view.view_from_world[3].z
+		), pbr_input.world_position);
+		return fetch_directional_shadow(idx, pbr_input.world_position, pbr_input.world_normal, view_z);
+	}
+#endif
+	return 1.0;
+}
+
+// Lambert diffuse light of all directional lights, with shadows.
+fn graph_directional_diffuse(pbr_input: PbrInput) -> vec3<f32> {
+	var diffuse = vec3<f32>(0.0);
+#ifndef PREPASS_PIPELINE
+	for (var idx = 0u; idx < lights.n_directional_lights; idx += 1u) {
+		let n_dot_l = saturate(dot(pbr_input.N, graph_light_direction(idx)));
+		diffuse += graph_light_color(idx) * n_dot_l * graph_light_shadow(pbr_input, idx);
+	}
+#endif
+	return diffuse;
+}
+
+// Ambient light color, scaled by brightness and exposure.
+fn graph_ambient_light() -> vec3<f32> {
+#ifndef PREPASS_PIPELINE
+	return lights.ambient_color.rgb * view.exposure;
+#else
+	return vec3<f32>(0.0);
+#endif
+}
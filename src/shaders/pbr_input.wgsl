@@ -0,0 +1,79 @@
+#define_import_path bevy_shader_graph::pbr_input
+
+#import bevy_pbr::{
+	pbr_functions::{apply_normal_mapping, calculate_tbn_mikktspace},
+	pbr_types::{PbrInput, STANDARD_MATERIAL_FLAGS_DOUBLE_SIDED_BIT},
+}
+
+#ifdef PREPASS_PIPELINE
+#import bevy_pbr::prepass_io::VertexOutput
+#else
+#import bevy_pbr::forward_io::VertexOutput
+#endif
+
+#ifdef SHADER_GRAPH_BLANK_BASE
+#import bevy_pbr::{
+	mesh_view_bindings::view,
+	pbr_functions::{calculate_view, prepare_world_normal},
+	pbr_types::pbr_input_new,
+}
+#else
+#import bevy_pbr::pbr_fragment::pbr_input_from_standard_material
+#endif
+
+// The graph's `PbrInput`, from the `StandardMaterial` bindings of the base material,
+// or with default material values on a blank base.
+fn graph_pbr_input(in: VertexOutput, is_front: bool) -> PbrInput {
+#ifdef SHADER_GRAPH_BLANK_BASE
+	var pbr_input = pbr_input_new();
+	let is_orthographic = view.clip_from_view[3].w == 1.0;
+
+	pbr_input.frag_coord = in.position;
+	pbr_input.world_position = in.world_position;
+	pbr_input.is_orthographic = is_orthographic;
+	pbr_input.V = calculate_view(in.world_position, is_orthographic);
+#ifdef PREPASS_PIPELINE
+#ifdef NORMAL_PREPASS_OR_DEFERRED_PREPASS
+	pbr_input.world_normal = prepare_world_normal(in.world_normal, false, is_front);
+#endif
+#else
+	pbr_input.world_normal = prepare_world_normal(in.world_normal, false, is_front);
+#endif
+	pbr_input.N = normalize(pbr_input.world_normal);
+	return pbr_input;
+#else
+	return pbr_input_from_standard_material(in, is_front);
+#endif
+}
+
+// The world normal from a tangent space `normal`, or `pbr_input.N` if the mesh has no tangents.
+fn graph_normal_mapping(
+	pbr_input: PbrInput,
+	in: VertexOutput,
+	is_front: bool,
+	normal: vec3<f32>,
+) -> vec3<f32> {
+	var N = pbr_input.N;
+#ifdef VERTEX_TANGENTS
+#ifdef PREPASS_PIPELINE
+#ifdef NORMAL_PREPASS_OR_DEFERRED_PREPASS
+	N = graph_tangent_normal(pbr_input, in.world_tangent, is_front, normal);
+#endif
+#else
+	N = graph_tangent_normal(pbr_input, in.world_tangent, is_front, normal);
+#endif
+#endif
+	return N;
+}
+
+fn graph_tangent_normal(
+	pbr_input: PbrInput,
+	world_tangent: vec4<f32>,
+	is_front: bool,
+	normal: vec3<f32>,
+) -> vec3<f32> {
+	let flags = pbr_input.material.flags;
+	let TBN = calculate_tbn_mikktspace(pbr_input.world_normal, world_tangent);
+	let double_sided = (flags & STANDARD_MATERIAL_FLAGS_DOUBLE_SIDED_BIT) != 0u;
+	return apply_normal_mapping(flags, TBN, double_sided, is_front, normal);
+}
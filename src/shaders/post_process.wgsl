@@ -0,0 +1,30 @@
+#define_import_path bevy_shader_graph::post_process
+
+#import bevy_render::{globals::Globals, view::View}
+
+@group(0) @binding(0) var screen_texture: texture_2d<f32>;
+@group(0) @binding(1) var screen_sampler: sampler;
+@group(0) @binding(2) var depth_texture: texture_depth_2d;
+@group(0) @binding(3) var normal_texture: texture_2d<f32>;
+@group(0) @binding(4) var<uniform> globals: Globals;
+@group(0) @binding(5) var<uniform> view: View;
+
+// Color of the rendered scene.
+fn screen_color(uv: vec2<f32>) -> vec4<f32> {
+	return textureSample(screen_texture, screen_sampler, uv);
+}
+
+fn prepass_texel(uv: vec2<f32>) -> vec2<i32> {
+	return vec2<i32>(uv * vec2<f32>(textureDimensions(depth_texture)));
+}
+
+// Non-linear (reverse-z) depth of the scene, 0 at the far plane.
+fn scene_depth(uv: vec2<f32>) -> f32 {
+	return textureLoad(depth_texture, prepass_texel(uv), 0);
+}
+
+// World space normal of the scene.
+fn scene_normal(uv: vec2<f32>) -> vec3<f32> {
+	let encoded = textureLoad(normal_texture, prepass_texel(uv), 0).xyz;
+	return normalize(encoded * 2.0 - 1.0);
+}
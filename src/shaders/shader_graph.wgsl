@@ -1,7 +1,5 @@
-#import bevy_pbr::{
-	pbr_fragment::pbr_input_from_standard_material,
-	pbr_functions::alpha_discard,
-}
+#import bevy_pbr::pbr_functions::alpha_discard
+#import bevy_shader_graph::pbr_input::graph_pbr_input
 
 #ifdef PREPASS_PIPELINE
 #import bevy_pbr::{
@@ -21,8 +19,8 @@ fn fragment(
   in: VertexOutput,
   @builtin(front_facing) is_front: bool,
 ) -> FragmentOutput {
-	// get PbrInput from StandardMaterial bindings.
-	var pbr_input = pbr_input_from_standard_material(in, is_front);
+	// get PbrInput from the base material.
+	var pbr_input = graph_pbr_input(in, is_front);
 
 	// alpha discard
   pbr_input.material.base_color = alpha_discard(pbr_input.material, pbr_input.material.base_color);
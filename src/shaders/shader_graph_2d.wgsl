@@ -0,0 +1,11 @@
+#import bevy_sprite::mesh2d_vertex_output::VertexOutput
+
+@fragment
+fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
+	// No graph yet, use the vertex color.
+#ifdef VERTEX_COLORS
+	return in.color;
+#else
+	return vec4<f32>(1.0);
+#endif
+}
@@ -0,0 +1,7 @@
+#import bevy_ui::ui_vertex_output::UiVertexOutput
+
+@fragment
+fn fragment(in: UiVertexOutput) -> @location(0) vec4<f32> {
+	// No graph yet.
+	return vec4<f32>(1.0);
+}
@@ -0,0 +1,84 @@
+use crate::attributes::GraphAttributeRequirements;
+use crate::material::{ShaderGraph, ShaderGraphTarget};
+use crate::nodes::MasterMode;
+use bevy::{
+    asset::{load_internal_asset, uuid_handle},
+    prelude::*,
+    reflect::Reflect,
+    render::render_resource::*,
+    shader::ShaderRef,
+    ui_render::{
+        ui_material::{UiMaterial, UiMaterialKey},
+        UiMaterialPlugin,
+    },
+};
+
+/// Default UI fragment template, used when a `ShaderGraph` has no compiled fragment.
+pub const SHADER_GRAPH_DEFAULT_FRAGMENT_UI: Handle<Shader> =
+    uuid_handle!("1d7b3e95-a046-4c2f-8e61-b39f0c5a27d8");
+
+/// A shader graph on a UI node (`MaterialNode`), the graph's output node is `UI Fragment`.
+#[derive(Asset, AsBindGroup, Reflect, Debug, Clone, Default)]
+#[bind_group_data(ShaderGraph)]
+#[reflect(Default, Debug)]
+pub struct ShaderGraphUiMaterial {
+    #[uniform(100)]
+    pub prop4: Vec4,
+    #[reflect(ignore)]
+    pub graph: ShaderGraph,
+}
+
+impl From<&ShaderGraphUiMaterial> for ShaderGraph {
+    fn from(material: &ShaderGraphUiMaterial) -> Self {
+        material.graph.clone()
+    }
+}
+
+impl ShaderGraphTarget for ShaderGraphUiMaterial {
+    const MODES: &'static [MasterMode] = &[MasterMode::Ui];
+
+    fn fragment(&self) -> Option<&Handle<Shader>> {
+        self.graph.fragment.as_ref()
+    }
+
+    /// UI nodes don't have mesh attributes.
+    fn set_fragment(&mut self, fragment: Handle<Shader>, _attributes: GraphAttributeRequirements) {
+        self.graph.fragment = Some(fragment);
+    }
+}
+
+impl UiMaterial for ShaderGraphUiMaterial {
+    fn fragment_shader() -> ShaderRef {
+        SHADER_GRAPH_DEFAULT_FRAGMENT_UI.into()
+    }
+
+    fn specialize(descriptor: &mut RenderPipelineDescriptor, key: UiMaterialKey<Self>) {
+        // UI nodes don't have mesh attributes, only the shaders are swapped.
+        if let Some(new_vertex) = key.bind_group_data.vertex {
+            descriptor.vertex.shader = new_vertex;
+        }
+
+        if let (Some(fragment), Some(new_fragment)) =
+            (descriptor.fragment.as_mut(), key.bind_group_data.fragment)
+        {
+            fragment.shader = new_fragment;
+        }
+    }
+}
+
+#[derive(Default, Clone, Debug)]
+pub struct ShaderGraphUiMaterialPlugin;
+
+impl Plugin for ShaderGraphUiMaterialPlugin {
+    fn build(&self, app: &mut App) {
+        load_internal_asset!(
+            app,
+            SHADER_GRAPH_DEFAULT_FRAGMENT_UI,
+            "shaders/shader_graph_ui.wgsl",
+            Shader::from_wgsl
+        );
+
+        app.add_plugins(UiMaterialPlugin::<ShaderGraphUiMaterial>::default())
+            .register_asset_reflect::<ShaderGraphUiMaterial>();
+    }
+}